@@ -30,6 +30,12 @@ fn main() {
     if env::var("CARGO_FEATURE_MEMLOCK").is_ok() {
         feats.push("memlock");
     }
+    if env::var("CARGO_FEATURE_AUTOTYPE").is_ok() {
+        feats.push("autotype");
+    }
+    if env::var("CARGO_FEATURE_TESTING").is_ok() {
+        feats.push("testing");
+    }
     let features = if feats.is_empty() {
         "default".to_string()
     } else {
@@ -0,0 +1,82 @@
+# Bash completion for kevi.
+#
+# Install: source this file from ~/.bashrc, or copy it to
+# ~/.local/share/bash-completion/completions/kevi
+#
+# Entry labels for get/show/edit/rm come from `kevi _complete-labels`, which
+# prints nothing (and never prompts) unless the vault is unlocked with
+# `kevi unlock`. Profile names for --profile and `profile show/rm/default`
+# come from `kevi _complete-profiles`, which only reads config.toml.
+
+_kevi_labels() {
+    local cur=$1 i global=() path=()
+    # Complete against the same vault the command line points at
+    for ((i = 1; i < ${#COMP_WORDS[@]} - 1; i++)); do
+        ((i == COMP_CWORD)) && continue
+        case ${COMP_WORDS[i]} in
+            --profile | --env-file) global+=("${COMP_WORDS[i]}" "${COMP_WORDS[i + 1]}") ;;
+            --path) path=(--path "${COMP_WORDS[i + 1]}") ;;
+        esac
+    done
+    local IFS=$'\n'
+    COMPREPLY=($(kevi "${global[@]}" _complete-labels "${path[@]}" -- "$cur" 2>/dev/null))
+}
+
+_kevi_profiles() {
+    local IFS=$'\n'
+    COMPREPLY=($(kevi _complete-profiles -- "$1" 2>/dev/null))
+}
+
+_kevi() {
+    local cur=${COMP_WORDS[COMP_CWORD]}
+    local prev=${COMP_WORDS[COMP_CWORD - 1]}
+    local commands="profile get repeat doctor fix-perms header info init
+        migrate-from-plaintext show add edit rm trash repair exec list unlock lock tui help"
+
+    case $prev in
+        --path | --to-file | --to-fifo | --keys-file)
+            COMPREPLY=($(compgen -f -- "$cur"))
+            return
+            ;;
+        --profile)
+            _kevi_profiles "$cur"
+            return
+            ;;
+    esac
+
+    local i cmd=
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case ${COMP_WORDS[i]} in
+            --profile | --env-file) ((i++)) ;;
+            -*) ;;
+            *)
+                cmd=${COMP_WORDS[i]}
+                break
+                ;;
+        esac
+    done
+
+    if [[ -z $cmd ]]; then
+        COMPREPLY=($(compgen -W "$commands" -- "$cur"))
+        return
+    fi
+    case $cmd in
+        get | show | edit | rm)
+            [[ $cur == -* ]] || _kevi_labels "$cur"
+            ;;
+        profile)
+            # `profile show|rm|default <name>`: the word right after the subcommand
+            case ${COMP_WORDS[i + 1]} in
+                show | rm | default)
+                    ((COMP_CWORD == i + 2)) && [[ $cur != -* ]] && _kevi_profiles "$cur"
+                    ;;
+                *)
+                    ((COMP_CWORD == i + 1)) &&
+                        COMPREPLY=($(compgen -W "list show current add rm default" -- "$cur"))
+                    ;;
+            esac
+            ;;
+    esac
+}
+
+complete -F _kevi kevi
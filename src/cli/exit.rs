@@ -0,0 +1,65 @@
+use crate::config::app_config::ConfigError;
+use thiserror::Error;
+
+pub const EXIT_ERROR: i32 = 1;
+pub const EXIT_NOT_FOUND: i32 = 2;
+pub const EXIT_WRONG_PASSWORD: i32 = 3;
+pub const EXIT_NO_PASSWORD: i32 = 4;
+pub const EXIT_CONFIG: i32 = 5;
+
+/// Failures scripts may want to branch on; each has its own exit code (see
+/// [`exit_code`]). Return them through `anyhow` as usual, never as context.
+#[derive(Debug, Error)]
+pub enum KeviError {
+    #[error("no entry found with key '{0}'")]
+    NotFound(String),
+    #[error("no trashed entry found with key '{0}'")]
+    NotInTrash(String),
+    #[error("no {field} set for '{label}'")]
+    FieldNotSet { field: String, label: String },
+    #[error("Failed to decrypt vault (wrong key?)")]
+    WrongPassword,
+    #[error(
+        "no password available (set KEVI_PASSWORD, or run `kevi unlock` from a terminal first)"
+    )]
+    NoPassword,
+}
+
+/// Exit code for a failed command: the first typed error in the chain decides,
+/// anything else is a generic 1.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<KeviError>() {
+            return match e {
+                KeviError::NotFound(_)
+                | KeviError::NotInTrash(_)
+                | KeviError::FieldNotSet { .. } => EXIT_NOT_FOUND,
+                KeviError::WrongPassword => EXIT_WRONG_PASSWORD,
+                KeviError::NoPassword => EXIT_NO_PASSWORD,
+            };
+        }
+        if cause.is::<ConfigError>() {
+            return EXIT_CONFIG;
+        }
+    }
+    EXIT_ERROR
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn typed_errors_map_to_codes_through_context() {
+        let not_found: anyhow::Error = KeviError::NotFound("gh".into()).into();
+        assert_eq!(exit_code(&not_found), EXIT_NOT_FOUND);
+        let wrapped = Err::<(), _>(KeviError::WrongPassword)
+            .context("loading vault")
+            .unwrap_err();
+        assert_eq!(exit_code(&wrapped), EXIT_WRONG_PASSWORD);
+        let config: anyhow::Error = ConfigError::UnknownProfile("work".into()).into();
+        assert_eq!(exit_code(&config), EXIT_CONFIG);
+        assert_eq!(exit_code(&anyhow::anyhow!("boom")), EXIT_ERROR);
+    }
+}
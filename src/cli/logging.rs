@@ -0,0 +1,46 @@
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::io::Write;
+
+/// Stderr logger for `-v`/`-vv`: only kevi's own records, one line each.
+/// Messages describe events (cache hit/miss, KDF parameters, backup rotation,
+/// clipboard backend); secrets, keys and key material are never passed to it.
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target().starts_with("kevi")
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let level = match record.level() {
+            Level::Error => "error",
+            Level::Warn => "warn",
+            Level::Info => "info",
+            Level::Debug => "debug",
+            Level::Trace => "trace",
+        };
+        let _ = writeln!(std::io::stderr().lock(), "kevi[{level}]: {}", record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+/// Level for a `-v` count: silent by default, `-v` debug, `-vv` and more trace.
+pub fn level_for(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Install the stderr logger. Safe to call more than once; later calls only
+/// adjust the level.
+pub fn init(verbosity: u8) {
+    static LOGGER: StderrLogger = StderrLogger;
+    let _ = log::set_logger(&LOGGER);
+    log::set_max_level(level_for(verbosity));
+}
@@ -1,2 +1,6 @@
 pub mod clap_models;
+pub mod exit;
+pub mod logging;
+pub mod output;
+pub mod prompt;
 pub mod runner;
@@ -0,0 +1,169 @@
+use std::fmt::Display;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+static NO_COLOR_FLAG: AtomicBool = AtomicBool::new(false);
+static NO_PROGRESS_FLAG: AtomicBool = AtomicBool::new(false);
+static STATUS_STYLE: AtomicU8 = AtomicU8::new(StatusStyle::Emoji as u8);
+
+/// How [`status`] prefixes messages: emoji, ASCII tags like `[OK]`, or nothing.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StatusStyle {
+    Emoji,
+    Ascii,
+    None,
+}
+
+impl StatusStyle {
+    pub const NAMES: [&'static str; 3] = ["emoji", "ascii", "none"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "emoji" => Some(Self::Emoji),
+            "ascii" => Some(Self::Ascii),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+/// Set the status prefix style for this process (from `--ascii` or `status_style`).
+pub fn set_status_style(style: StatusStyle) {
+    STATUS_STYLE.store(style as u8, Ordering::Relaxed);
+}
+
+fn status_style() -> StatusStyle {
+    match STATUS_STYLE.load(Ordering::Relaxed) {
+        1 => StatusStyle::Ascii,
+        2 => StatusStyle::None,
+        _ => StatusStyle::Emoji,
+    }
+}
+
+/// ASCII stand-in for a status emoji.
+fn ascii_tag(emoji: &str) -> &'static str {
+    match emoji.trim_end_matches('\u{fe0f}') {
+        "✅" => "[OK]",
+        "❌" => "[ERROR]",
+        "❎" => "[CANCELLED]",
+        "⚠" => "[WARN]",
+        "ℹ" => "[INFO]",
+        "↪" => "[USING]",
+        "⌨" => "[TYPE]",
+        "♻" => "[RESTORE]",
+        "📝" => "[WRITE]",
+        "🔍" => "[SEARCH]",
+        "🔒" => "[LOCK]",
+        "🔓" => "[UNLOCK]",
+        "🧹" => "[CLEAN]",
+        "📦" => "[EXPORT]",
+        "📥" => "[IMPORT]",
+        "📋" => "[COPY]",
+        "🔄" => "[ROTATE]",
+        "🗑" => "[DELETE]",
+        "🩹" => "[REPAIR]",
+        "⚙" => "[CONFIG]",
+        "⏱" => "[TIME]",
+        "⏳" => "[WAIT]",
+        "✂" => "[TRIM]",
+        _ => "[*]",
+    }
+}
+
+/// Force plain output for this process (set from the global `--no-color` flag).
+pub fn set_no_color(enabled: bool) {
+    NO_COLOR_FLAG.store(enabled, Ordering::Relaxed);
+}
+
+/// True when `--no-color` was given or `NO_COLOR` is set to a non-empty value
+/// (see <https://no-color.org>).
+pub fn no_color() -> bool {
+    NO_COLOR_FLAG.load(Ordering::Relaxed) || std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty())
+}
+
+/// Prefix a status message with `emoji`, its ASCII tag, or nothing, per the
+/// status style. Plain mode (`--no-color`) drops emoji but keeps ASCII tags.
+pub fn status(emoji: &str, msg: impl Display) -> String {
+    match status_style() {
+        StatusStyle::Ascii => format!("{} {msg}", ascii_tag(emoji)),
+        StatusStyle::Emoji if !no_color() => format!("{emoji} {msg}"),
+        _ => msg.to_string(),
+    }
+}
+
+/// Suppress progress indicators (e.g. for machine-readable `--json` runs).
+pub fn set_no_progress(enabled: bool) {
+    NO_PROGRESS_FLAG.store(enabled, Ordering::Relaxed);
+}
+
+/// Minimal stderr spinner shown while a slow operation runs.
+/// Inert unless stderr is a terminal and progress is not suppressed; cleared on drop.
+pub struct Spinner {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+    width: usize,
+}
+
+impl Spinner {
+    pub fn start(msg: &str) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        if NO_PROGRESS_FLAG.load(Ordering::Relaxed) || !std::io::stderr().is_terminal() {
+            return Self {
+                stop,
+                handle: None,
+                width: 0,
+            };
+        }
+        let msg = msg.to_string();
+        let width = msg.chars().count() + 2;
+        let stop_flag = stop.clone();
+        let handle = thread::spawn(move || {
+            let frames = ['|', '/', '-', '\\'];
+            let mut i = 0;
+            while !stop_flag.load(Ordering::Relaxed) {
+                let mut err = std::io::stderr();
+                let _ = write!(err, "\r{} {msg}", frames[i % frames.len()]);
+                let _ = err.flush();
+                i += 1;
+                thread::sleep(Duration::from_millis(100));
+            }
+        });
+        Self {
+            stop,
+            handle: Some(handle),
+            width,
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+            let mut err = std::io::stderr();
+            let _ = write!(err, "\r{}\r", " ".repeat(self.width));
+            let _ = err.flush();
+        }
+    }
+}
+
+/// [`Progress`](crate::vault::ports::Progress) for key resolvers: a spinner
+/// while `step` runs.
+pub fn spinner_progress(msg: &str, step: &mut dyn FnMut()) {
+    with_spinner(msg, step)
+}
+
+/// [`Notice`](crate::vault::ports::Notice) for the vault service: a warning on stderr.
+pub fn stderr_notice(msg: &str) {
+    eprintln!("{}", status("⚠️", msg));
+}
+
+/// Run `f` with a spinner labelled `msg` (see [`Spinner`]).
+pub fn with_spinner<T>(msg: &str, f: impl FnOnce() -> T) -> T {
+    let _spinner = Spinner::start(msg);
+    f()
+}
@@ -0,0 +1,244 @@
+use crate::cli::exit::KeviError;
+use crate::config::app_config::cmd_timeout;
+use anyhow::{anyhow, Result};
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+static NO_PROMPT_FLAG: AtomicBool = AtomicBool::new(false);
+
+/// Never prompt in this process (set from the global `--no-prompt` flag).
+pub fn set_no_prompt(enabled: bool) {
+    NO_PROMPT_FLAG.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether interactive prompts can be shown: stdin is a terminal and neither
+/// `--no-prompt` nor `KEVI_NO_PROMPT` is set.
+pub fn is_interactive() -> bool {
+    !NO_PROMPT_FLAG.load(Ordering::Relaxed)
+        && !std::env::var("KEVI_NO_PROMPT").is_ok_and(|v| !v.is_empty() && v != "0")
+        && std::io::stdin().is_terminal()
+}
+
+/// Fail fast instead of prompting when stdin is not a terminal (CI, pipes).
+pub fn require_interactive(missing: &str) -> Result<()> {
+    if !is_interactive() {
+        anyhow::bail!("missing {missing} in non-interactive mode");
+    }
+    Ok(())
+}
+
+/// Like `require_interactive`, for the master password.
+pub fn require_password_prompt() -> Result<()> {
+    if !is_interactive() {
+        return Err(KeviError::NoPassword.into());
+    }
+    Ok(())
+}
+
+/// Run a blocking call (prompt, clipboard access) on its own detached thread,
+/// failing with "operation timed out" once `KEVI_CMD_TIMEOUT` elapses. A call
+/// that never returns is abandoned rather than joined, so it cannot keep the
+/// runtime from shutting down.
+pub async fn with_cmd_timeout<T, E>(
+    what: &str,
+    f: impl FnOnce() -> Result<T, E> + Send + 'static,
+) -> Result<T>
+where
+    T: Send + 'static,
+    E: Into<anyhow::Error> + Send + 'static,
+{
+    with_timeout(what, cmd_timeout(), f).await
+}
+
+async fn with_timeout<T, E>(
+    what: &str,
+    limit: Option<Duration>,
+    f: impl FnOnce() -> Result<T, E> + Send + 'static,
+) -> Result<T>
+where
+    T: Send + 'static,
+    E: Into<anyhow::Error> + Send + 'static,
+{
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    let joined = match limit {
+        Some(limit) => tokio::time::timeout(limit, rx)
+            .await
+            .map_err(|_| timed_out(what, limit))?,
+        None => rx.await,
+    };
+    joined
+        .map_err(|_| anyhow!("{what} failed"))?
+        .map_err(Into::into)
+}
+
+/// [`with_cmd_timeout`] for synchronous code: the call runs on its own thread
+/// when a limit is set.
+pub fn block_with_cmd_timeout<T, E>(
+    what: &str,
+    f: impl FnOnce() -> Result<T, E> + Send + 'static,
+) -> Result<T>
+where
+    T: Send + 'static,
+    E: Into<anyhow::Error> + Send + 'static,
+{
+    let Some(limit) = cmd_timeout() else {
+        return f().map_err(Into::into);
+    };
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(f());
+    });
+    match rx.recv_timeout(limit) {
+        Ok(res) => res.map_err(Into::into),
+        Err(RecvTimeoutError::Timeout) => Err(timed_out(what, limit)),
+        Err(RecvTimeoutError::Disconnected) => Err(anyhow!("{what} failed")),
+    }
+}
+
+fn timed_out(what: &str, limit: Duration) -> anyhow::Error {
+    // An abandoned prompt may have left the terminal in raw mode.
+    let _ = crossterm::terminal::disable_raw_mode();
+    anyhow!(
+        "{what}: operation timed out after {}s (KEVI_CMD_TIMEOUT)",
+        limit.as_secs()
+    )
+}
+
+/// What to do with a generated secret shown in a preview.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PreviewChoice {
+    Accept,
+    Regenerate,
+    Reveal,
+    Hide,
+}
+
+/// Let the user accept, reroll or reveal a generated secret before it is saved.
+/// `choose` gets the text to show (masked unless revealed) and whether it is
+/// revealed; each regenerated candidate starts masked again.
+pub fn review_generated(
+    first: String,
+    mut regenerate: impl FnMut() -> Result<String>,
+    mut choose: impl FnMut(&str, bool) -> Result<PreviewChoice>,
+) -> Result<String> {
+    let mut current = Zeroizing::new(first);
+    let mut revealed = false;
+    loop {
+        let shown = if revealed {
+            current.clone()
+        } else {
+            Zeroizing::new("*".repeat(current.chars().count()))
+        };
+        match choose(&shown, revealed)? {
+            PreviewChoice::Accept => return Ok(std::mem::take(&mut *current)),
+            PreviewChoice::Regenerate => {
+                current = Zeroizing::new(regenerate()?);
+                revealed = false;
+            }
+            PreviewChoice::Reveal => revealed = true,
+            PreviewChoice::Hide => revealed = false,
+        }
+    }
+}
+
+/// [`review_generated`] as a terminal menu: Accept / Regenerate / Reveal (or Hide).
+pub fn preview_generated(
+    first: String,
+    regenerate: impl FnMut() -> Result<String>,
+) -> Result<String> {
+    review_generated(first, regenerate, |shown, revealed| {
+        let toggle = if revealed { "Hide" } else { "Reveal" };
+        let message = Zeroizing::new(format!("Generated password: {shown}"));
+        let picked = block_with_cmd_timeout("preview prompt", move || {
+            inquire::Select::new(&message, vec!["Accept", "Regenerate", toggle])
+                .with_help_message("nothing is saved until you accept")
+                .prompt()
+        })?;
+        Ok(match picked {
+            "Accept" => PreviewChoice::Accept,
+            "Regenerate" => PreviewChoice::Regenerate,
+            "Hide" => PreviewChoice::Hide,
+            _ => PreviewChoice::Reveal,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn review_masks_until_revealed_and_rerolls_until_accepted() {
+        let mut rolls = vec!["third".to_string(), "second".to_string()];
+        let mut seen = Vec::new();
+        let mut script = vec![
+            PreviewChoice::Accept,
+            PreviewChoice::Regenerate,
+            PreviewChoice::Hide,
+            PreviewChoice::Reveal,
+            PreviewChoice::Regenerate,
+        ];
+        let accepted = review_generated(
+            "first".to_string(),
+            || Ok(rolls.pop().unwrap()),
+            |shown, revealed| {
+                seen.push((shown.to_string(), revealed));
+                Ok(script.pop().unwrap())
+            },
+        )
+        .unwrap();
+        assert_eq!(accepted, "third");
+        assert_eq!(
+            seen,
+            [
+                ("*****".to_string(), false),
+                ("******".to_string(), false),
+                ("second".to_string(), true),
+                ("******".to_string(), false),
+                ("*****".to_string(), false),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stuck_call_fails_once_the_limit_elapses() {
+        let err = with_timeout("clipboard", Some(Duration::from_millis(50)), || {
+            std::thread::sleep(Duration::from_secs(2));
+            Ok::<_, anyhow::Error>(())
+        })
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("operation timed out"), "{err}");
+
+        let done = with_timeout("clipboard", None, || Ok::<_, anyhow::Error>(7))
+            .await
+            .unwrap();
+        assert_eq!(done, 7);
+    }
+
+    #[test]
+    fn abandoned_call_does_not_hold_up_runtime_shutdown() {
+        let started = std::time::Instant::now();
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        let res = rt.block_on(with_timeout(
+            "clipboard",
+            Some(Duration::from_millis(50)),
+            || {
+                std::thread::sleep(Duration::from_secs(5));
+                Ok::<_, anyhow::Error>(())
+            },
+        ));
+        drop(rt);
+        assert!(res.is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
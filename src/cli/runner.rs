@@ -1,54 +1,225 @@
-use crate::cli::clap_models::{Cli, Commands, GetFieldArg, ProfileCommand};
+use crate::cli::clap_models::{
+    BackupCommand, Cli, Commands, ConfigCommand, EntryFormatArg, GetFieldArg, KdfPresetArg,
+    ListJsonFieldArg, ListSortArg, MissingFieldArg, ProfileCommand, SearchFieldArg, TrashCommand,
+};
+use crate::cli::output::{set_no_color, set_no_progress, set_status_style, StatusStyle};
+use crate::cli::prompt::set_no_prompt;
 use crate::config::app_config::{
-    load_file_config_with_path, save_file_config, Config, FileProfileConfig,
+    config_file_path, configured_status_style, effective_settings, kevi_config_dir, kevi_data_dir,
+    load_file_config_with_path, resolve_vault, save_file_config, set_config_file, set_use_backup,
+    Config, ConfigError, FileConfig, FileProfileConfig,
 };
+use crate::config::env_file::load_env_file;
+use crate::cryptography::primitives::KdfPreset;
+use crate::session_management::resolver::dk_session_file_for;
 use crate::tui;
-use crate::vault::handlers::Vault;
+use crate::vault::credential_uri::CredentialUri;
+use crate::vault::handlers::{InitKdf, ListJsonField, MissingField, Vault};
+use crate::vault::search::SearchField;
+use anyhow::Context;
 use clap::Parser;
+use secrecy::ExposeSecret;
 use std::path::PathBuf;
+use std::time::Duration;
 
 pub async fn run() -> anyhow::Result<()> {
     let cli = Cli::parse();
+    crate::cli::logging::init(cli.verbosity);
+    // Apply before anything reads the environment (config, passwords, TTLs)
+    if let Some(env_file) = &cli.env_file {
+        load_env_file(env_file)?;
+    }
+    set_config_file(cli.config.clone());
+    let style = if cli.ascii {
+        Some(StatusStyle::Ascii)
+    } else {
+        configured_status_style().and_then(|name| {
+            let style = StatusStyle::from_name(&name);
+            if style.is_none() {
+                log::warn!(
+                    "ignoring unknown status_style '{name}' (expected {})",
+                    StatusStyle::NAMES.join(", ")
+                );
+            }
+            style
+        })
+    };
+    if let Some(style) = style {
+        set_status_style(style);
+    }
+    set_no_color(cli.no_color);
+    set_no_prompt(cli.no_prompt);
+    set_use_backup(cli.use_backup);
 
     match cli.command {
-        Commands::Init { path } => {
+        Commands::Init {
+            path,
+            auto_kdf,
+            kdf_target_ms,
+            preset,
+        } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            let kdf = match preset {
+                Some(KdfPresetArg::Fast) => InitKdf::Preset(KdfPreset::Fast),
+                Some(KdfPresetArg::Balanced) => InitKdf::Preset(KdfPreset::Balanced),
+                Some(KdfPresetArg::Paranoid) => InitKdf::Preset(KdfPreset::Paranoid),
+                None if auto_kdf => InitKdf::Calibrate(Duration::from_millis(kdf_target_ms)),
+                None => InitKdf::Default,
+            };
+            vault.handle_init(config.vault_path.to_str(), kdf).await?;
+        }
+        Commands::Gen { wordlist_info } => {
+            if wordlist_info {
+                let config = Config::create(None, cli.profile.clone())?;
+                crate::vault::handlers::print_wordlist_info(&config)?;
+            }
+        }
+        Commands::OpenConfig => open_config()?,
+        Commands::Where { path, json } => {
+            print_where(path.map(PathBuf::from), cli.profile.as_deref(), json)?;
+        }
+        Commands::Doctor { path } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            vault.handle_doctor().await?;
+        }
+        Commands::FixPerms { path } => {
             let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
             let vault = Vault::create(&config);
-            vault.handle_init(config.vault_path.to_str()).await?;
+            vault.handle_fix_perms().await?;
         }
-        Commands::Header { path } => {
+        Commands::Header { path, verify } => {
             let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
             let vault = Vault::create(&config);
-            vault.handle_header().await?;
+            vault.handle_header(verify).await?;
+        }
+        Commands::Info { path, incomplete } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            vault.handle_info(incomplete).await?;
+        }
+        Commands::Export {
+            path,
+            encrypted: _,
+            out,
+            new_password,
+        } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            vault
+                .handle_export_encrypted(PathBuf::from(out), new_password)
+                .await?;
+        }
+        Commands::Import {
+            file,
+            path,
+            encrypted: _,
+        } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            vault.handle_import_encrypted(PathBuf::from(file)).await?;
+        }
+        Commands::MigrateFromPlaintext {
+            path,
+            i_have_a_plaintext_vault,
+        } => {
+            let config = Config::create(Some(PathBuf::from(path)), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            vault
+                .handle_migrate_from_plaintext(i_have_a_plaintext_vault)
+                .await?;
         }
         Commands::Show {
             key,
             reveal_password,
+            copy_password,
+            copy_user,
+            json,
+            exact,
             path,
         } => {
             let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
             let vault = Vault::create(&config);
-            vault.handle_show(&key, reveal_password).await?;
+            let copy = if copy_user {
+                Some(crate::vault::handlers::GetField::User)
+            } else {
+                copy_password.then_some(crate::vault::handlers::GetField::Password)
+            };
+            vault
+                .handle_show(&key, reveal_password, copy, exact, json)
+                .await?;
         }
         Commands::Get {
             key,
             path,
             field,
+            field_fallback,
             no_copy,
             echo,
             ttl,
             once,
+            exact,
+            format,
+            reveal,
+            raw,
+            print0,
+            wait,
+            verbose,
+            to_file,
+            to_fifo,
+            clear_on_paste,
+            no_restore,
+            autotype,
+            keys_file,
+            measure,
         } => {
-            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            set_no_progress(matches!(format, EntryFormatArg::Json));
+            let mut config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            if no_restore {
+                config.clipboard_no_restore = Some(true);
+            }
             let vault = Vault::create(&config);
-            let field_core = match field {
-                GetFieldArg::Password => crate::vault::handlers::GetField::Password,
-                GetFieldArg::User => crate::vault::handlers::GetField::User,
-                GetFieldArg::Notes => crate::vault::handlers::GetField::Notes,
+            let field_core = get_field_core(field);
+            let format_core = match format {
+                EntryFormatArg::Dotenv => crate::vault::format::EntryFormat::Dotenv,
+                EntryFormatArg::Json => crate::vault::format::EntryFormat::Json,
             };
-            vault
-                .handle_get(&key, field_core, no_copy, ttl, echo, once)
-                .await?
+            if let Some(keys_file) = keys_file {
+                return vault
+                    .handle_get_keys_file(&keys_file, field_core, once, exact, print0)
+                    .await;
+            }
+            if measure {
+                let key = key.ok_or_else(|| anyhow::anyhow!("missing <KEY>"))?;
+                return vault.handle_measure(&key, field_core, once, exact).await;
+            }
+            let opts = crate::vault::handlers::GetOptions {
+                field: field_core,
+                field_fallback: field_fallback.into_iter().map(get_field_core).collect(),
+                no_copy,
+                ttl,
+                echo,
+                once,
+                exact,
+                format: format_core,
+                reveal,
+                raw,
+                print0,
+                wait,
+                verbose,
+                to_file,
+                to_fifo,
+                clear_on_paste,
+                autotype,
+            };
+            let key = key.ok_or_else(|| anyhow::anyhow!("missing <KEY> (or --keys-file)"))?;
+            vault.handle_get(&key, opts).await?
+        }
+        Commands::Repeat { path } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            vault.handle_repeat().await?;
         }
         Commands::Add {
             path,
@@ -62,10 +233,33 @@ pub async fn run() -> anyhow::Result<()> {
             passphrase,
             words,
             sep,
+            preset,
+            max_length,
             label,
+            label_template,
+            count,
             user,
             notes,
+            mut set,
+            from_uri,
+            yes,
         } => {
+            let parsed = from_uri.as_deref().map(CredentialUri::parse).transpose()?;
+            let (label, user, password) = match parsed {
+                Some(c) => {
+                    if c.password.is_some() && generate {
+                        anyhow::bail!("--from-uri already has a password; drop --generate");
+                    }
+                    if let Some(totp) = c.totp {
+                        if set.iter().any(|(name, _)| name == "totp") {
+                            anyhow::bail!("--from-uri sets the totp field; drop --set totp=...");
+                        }
+                        set.push(("totp".to_string(), totp.expose_secret().to_string()));
+                    }
+                    (Some(c.label), c.username, c.password)
+                }
+                None => (label, user, None),
+            };
             let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
             let vault = Vault::create(&config);
             let opts = crate::vault::handlers::AddOptions {
@@ -79,51 +273,367 @@ pub async fn run() -> anyhow::Result<()> {
                 passphrase,
                 words,
                 sep,
+                preset,
+                max_length,
                 label,
+                label_template,
+                count,
                 user,
                 notes,
+                custom: set,
+                password,
+                yes,
             };
             vault.handle_add(opts).await?;
         }
-        Commands::Rm { key, path, yes } => {
+        Commands::Edit {
+            key,
+            path,
+            rotate_like_current,
+            append_note,
+            prepend_note,
+            exact,
+            yes,
+        } => {
             let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
             let vault = Vault::create(&config);
-            vault.handle_rm(&key, yes).await?;
+            let opts = crate::vault::handlers::EditOptions {
+                rotate_like_current,
+                append_note,
+                prepend_note,
+                exact,
+                yes,
+            };
+            vault.handle_edit(&key, opts).await?;
+        }
+        Commands::Rm {
+            keys,
+            path,
+            yes,
+            exact,
+        } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            vault.handle_rm(&keys, yes, exact).await?;
+        }
+        Commands::Trash { path, command } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            match command {
+                TrashCommand::List => vault.handle_trash_list().await?,
+                TrashCommand::Restore { label } => vault.handle_trash_restore(&label).await?,
+                TrashCommand::Empty { yes } => vault.handle_trash_empty(yes).await?,
+            }
+        }
+        Commands::Backup { path, command } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            match command {
+                BackupCommand::List => vault.handle_backup_list().await?,
+            }
+        }
+        Commands::Exec { path } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            vault.handle_exec().await?;
+        }
+        Commands::Repair { path, yes } => {
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            vault.handle_repair(yes).await?;
         }
         Commands::List {
             path,
             show_users,
             query,
             json,
+            json_lines,
+            json_fields,
+            porcelain,
+            print0,
+            sort,
+            recent,
+            reverse,
+            missing,
+        } => {
+            if !json && !json_lines && !json_fields.is_empty() {
+                anyhow::bail!("--json-fields needs --json or --json-lines");
+            }
+            set_no_progress(json || json_lines || porcelain || print0);
+            let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            let vault = Vault::create(&config);
+            let fields: Vec<ListJsonField> = json_fields
+                .into_iter()
+                .map(|f| match f {
+                    ListJsonFieldArg::Label => ListJsonField::Label,
+                    ListJsonFieldArg::Username => ListJsonField::Username,
+                    ListJsonFieldArg::Notes => ListJsonField::Notes,
+                    ListJsonFieldArg::CreatedAt => ListJsonField::CreatedAt,
+                    ListJsonFieldArg::UpdatedAt => ListJsonField::UpdatedAt,
+                    ListJsonFieldArg::LastAccessedAt => ListJsonField::LastAccessedAt,
+                    ListJsonFieldArg::CustomFields => ListJsonField::CustomFields,
+                })
+                .collect();
+            let output = if porcelain || print0 {
+                crate::vault::handlers::ListOutput::Porcelain { nul: print0 }
+            } else if json_lines {
+                crate::vault::handlers::ListOutput::JsonLines { fields }
+            } else if json {
+                crate::vault::handlers::ListOutput::Json { fields }
+            } else {
+                crate::vault::handlers::ListOutput::Text
+            };
+            let sort_core = sort.map(|s| match s {
+                ListSortArg::Label => crate::vault::handlers::ListSort::Label,
+                ListSortArg::User => crate::vault::handlers::ListSort::User,
+                ListSortArg::Created => crate::vault::handlers::ListSort::Created,
+                ListSortArg::Updated => crate::vault::handlers::ListSort::Updated,
+            });
+            let sort_core =
+                sort_core.or(recent.then_some(crate::vault::handlers::ListSort::Recent));
+            let missing: Vec<MissingField> = missing
+                .into_iter()
+                .map(|f| match f {
+                    MissingFieldArg::User => MissingField::User,
+                    MissingFieldArg::Notes => MissingField::Notes,
+                    MissingFieldArg::Custom => MissingField::Custom,
+                })
+                .collect();
+            let show_users = show_users || !config.usernames_secret();
+            vault
+                .handle_list(query, show_users, output, sort_core, reverse, &missing)
+                .await?;
+        }
+        Commands::Search {
+            term,
+            path,
+            fields,
+            show_users,
+            json,
         } => {
+            set_no_progress(json);
             let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
             let vault = Vault::create(&config);
-            vault.handle_list(query, show_users, json).await?;
+            let fields: Vec<SearchField> = fields
+                .into_iter()
+                .map(|f| match f {
+                    SearchFieldArg::Label => SearchField::Label,
+                    SearchFieldArg::User => SearchField::User,
+                    SearchFieldArg::Notes => SearchField::Notes,
+                    SearchFieldArg::Custom => SearchField::Custom,
+                })
+                .collect();
+            let show_users = show_users || !config.usernames_secret();
+            vault
+                .handle_search(&term, &fields, show_users, json)
+                .await?;
         }
-        Commands::Unlock { path, ttl } => {
+        Commands::Unlock {
+            path,
+            ttl,
+            force_derive,
+            extend,
+            time,
+        } => {
             let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
             let vault = Vault::create(&config);
-            vault.handle_unlock(ttl).await?;
+            vault
+                .handle_unlock(ttl, force_derive, extend, force_derive && time)
+                .await?;
         }
-        Commands::Lock { path } => {
+        Commands::Lock {
+            path,
+            wipe_sessions,
+            wipe_backups,
+            yes,
+        } => {
             let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
             let vault = Vault::create(&config);
-            vault.handle_lock().await?;
+            if wipe_sessions || wipe_backups {
+                vault
+                    .handle_lock_wipe(wipe_sessions, wipe_backups, yes)
+                    .await?;
+            } else {
+                vault.handle_lock().await?;
+            }
         }
-        Commands::Tui { path } => {
+        Commands::CompleteLabels { prefix, path } => {
             let config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
-            tui::launch(&config).await?;
+            let vault = Vault::create(&config);
+            vault.handle_complete_labels(&prefix).await?;
+        }
+        Commands::CompleteProfiles { prefix } => {
+            complete_profiles(&prefix);
+        }
+        Commands::Tui {
+            path,
+            theme,
+            clear_on_paste,
+            no_restore,
+            select,
+            details,
+            ephemeral,
+        } => {
+            let mut config = Config::create(path.map(PathBuf::from), cli.profile.clone())?;
+            if clear_on_paste {
+                config.clipboard_clear_on_paste = Some(true);
+            }
+            if no_restore {
+                config.clipboard_no_restore = Some(true);
+            }
+            tui::launch(
+                &config,
+                theme.as_deref(),
+                select.as_deref(),
+                details,
+                ephemeral,
+            )
+            .await?;
         }
         Commands::Profile(cmd) => {
-            handle_profile_commands(cmd)?;
+            handle_profile_commands(cmd, cli.profile.as_deref())?;
+        }
+        Commands::Config(cmd) => {
+            handle_config_commands(cmd, cli.profile.as_deref())?;
         }
     }
 
     Ok(())
 }
 
-fn handle_profile_commands(cmd: ProfileCommand) -> anyhow::Result<()> {
-    let (path, mut cfg) = load_file_config_with_path();
+/// Open the config file in `$VISUAL`, else `$EDITOR` (which may carry arguments,
+/// e.g. `code -w`), else `vi` (`notepad` on Windows). The file itself is left
+/// for the editor to create.
+fn open_config() -> anyhow::Result<()> {
+    let path = config_file_path();
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("cannot create {}", parent.display()))?;
+    }
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("cannot run editor '{editor}' (set $EDITOR)"))?;
+    if !status.success() {
+        anyhow::bail!("editor '{editor}' exited with {status}");
+    }
+    Ok(())
+}
+
+/// `kevi where`: the config file and directory, vault, session cache and data
+/// directory as resolved for this invocation. Nothing is created.
+fn print_where(path: Option<PathBuf>, cli_profile: Option<&str>, json: bool) -> anyhow::Result<()> {
+    let vault = resolve_vault(path, cli_profile)?.path;
+    let paths = [
+        ("config_file", config_file_path()),
+        ("config_dir", kevi_config_dir()),
+        ("vault_path", vault.clone()),
+        ("session_path", dk_session_file_for(&vault)),
+        ("data_dir", kevi_data_dir()),
+    ];
+    if json {
+        let obj: serde_json::Map<String, serde_json::Value> = paths
+            .iter()
+            .map(|(key, p)| (key.to_string(), p.display().to_string().into()))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&obj)?);
+    } else {
+        for (key, p) in &paths {
+            println!("{key}: {}", p.display());
+        }
+    }
+    Ok(())
+}
+
+fn handle_config_commands(cmd: ConfigCommand, cli_profile: Option<&str>) -> anyhow::Result<()> {
+    match cmd {
+        ConfigCommand::Show { path, json } => {
+            let settings = effective_settings(path.map(PathBuf::from), cli_profile)?;
+            if json {
+                let obj: serde_json::Map<String, serde_json::Value> = settings
+                    .into_iter()
+                    .map(|s| {
+                        (
+                            s.key,
+                            serde_json::json!({"value": s.value, "source": s.source}),
+                        )
+                    })
+                    .collect();
+                println!("{}", serde_json::to_string_pretty(&obj)?);
+                return Ok(());
+            }
+            // TOML lines annotated with their source; unset settings stay commented out
+            for s in settings {
+                match toml::Value::try_from(without_nulls(&s.value)) {
+                    Ok(v) if !s.value.is_null() => println!("{} = {v}  # {}", s.key, s.source),
+                    _ => println!("# {} (unset)", s.key),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `value` with null object members dropped, since TOML has no null.
+fn without_nulls(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .filter(|(_, v)| !v.is_null())
+            .map(|(k, v)| (k.clone(), without_nulls(v)))
+            .collect(),
+        other => other.clone(),
+    }
+}
+
+/// `_complete-profiles`: sorted profile names starting with `prefix`. Reads only
+/// config.toml, so it works whatever `--profile` says; nothing is printed when
+/// there are no profiles.
+fn complete_profiles(prefix: &str) {
+    let Ok((_, cfg)) = load_file_config_with_path() else {
+        return;
+    };
+    let mut names: Vec<String> = cfg
+        .profiles
+        .unwrap_or_default()
+        .into_keys()
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+    names.sort();
+    for name in names {
+        println!("{name}");
+    }
+}
+
+fn get_field_core(field: GetFieldArg) -> crate::vault::handlers::GetField {
+    match field {
+        GetFieldArg::Password => crate::vault::handlers::GetField::Password,
+        GetFieldArg::User => crate::vault::handlers::GetField::User,
+        GetFieldArg::Notes => crate::vault::handlers::GetField::Notes,
+        GetFieldArg::All => crate::vault::handlers::GetField::All,
+        GetFieldArg::Custom(name) => crate::vault::handlers::GetField::Custom(name),
+    }
+}
+
+fn handle_profile_commands(cmd: ProfileCommand, cli_profile: Option<&str>) -> anyhow::Result<()> {
+    let (path, mut cfg) = match load_file_config_with_path() {
+        // `profile add` may create the file `--config` names
+        Err(ConfigError::UnreadableFile { path, source })
+            if matches!(cmd, ProfileCommand::Add { .. })
+                && source.kind() == std::io::ErrorKind::NotFound =>
+        {
+            (path, FileConfig::default())
+        }
+        loaded => loaded?,
+    };
     let profiles = cfg.profiles.get_or_insert_with(Default::default);
 
     match cmd {
@@ -142,6 +652,17 @@ fn handle_profile_commands(cmd: ProfileCommand) -> anyhow::Result<()> {
                 }
             }
         }
+        ProfileCommand::Current { path } => {
+            // Read-only: report the resolution without rewriting config.toml
+            let resolved = resolve_vault(path.map(PathBuf::from), cli_profile)?;
+            println!(
+                "profile: {}",
+                resolved.profile.as_deref().unwrap_or("(none)")
+            );
+            println!("vault_path: {}", resolved.path.display());
+            println!("source: {}", resolved.source);
+            return Ok(());
+        }
         ProfileCommand::Show { name } => {
             if let Some(p) = profiles.get(&name) {
                 println!("profile: {name}\n  vault_path: {}", p.vault_path);
@@ -1,15 +1,35 @@
+use crate::config::duration::parse_duration_secs;
+use crate::filesystem::secure::BackupNaming;
+use crate::session_management::resolver::SessionSliding;
+use crate::vault::ports::GenPolicy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
 use thiserror::Error;
 
+static CONFIG_FILE: RwLock<Option<PathBuf>> = RwLock::new(None);
+static USE_BACKUP: AtomicBool = AtomicBool::new(false);
+
 #[derive(Debug, Error)]
 pub enum ConfigError {
     #[error("profile \"{0}\" is not defined in config.toml")]
     UnknownProfile(String),
     #[error("profile \"{0}\" is missing a vault_path")]
     InvalidProfile(String),
+    #[error("cannot read config file {path}: {source}")]
+    UnreadableFile {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("invalid config file {path}: {source}")]
+    InvalidFile {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
@@ -17,17 +37,114 @@ pub struct FileConfig {
     pub vault_path: Option<String>,
     pub clipboard_ttl: Option<u64>,
     pub backups: Option<usize>,
+    // Backup naming: dot-prefixed (Unix) and/or an extra suffix before the number
+    pub backup_hidden: Option<bool>,
+    pub backup_suffix: Option<String>,
+    // Write vault plaintext as compact (non-pretty) RON before encryption
+    pub compact_storage: Option<bool>,
+    // Encrypt the vault in fixed-size chunks (header v2) for bounded memory
+    pub stream_encryption: Option<bool>,
     // Generator defaults (optional)
     pub generator_length: Option<u16>,
+    // Longest password/passphrase the generator will produce (default 4096)
+    pub generator_max_length: Option<u16>,
     pub generator_words: Option<u16>,
     pub generator_sep: Option<String>,
     pub avoid_ambiguous: Option<bool>,
+    // Named generation presets: [generator.presets.<name>]
+    pub generator: Option<FileGeneratorConfig>,
+    // TUI color theme name (dark, light, high-contrast)
+    pub tui_theme: Option<String>,
+    // Status line prefixes: emoji (default), ascii, or none
+    pub status_style: Option<String>,
+    // TUI tick interval in milliseconds (toast decay, idle wake-ups)
+    pub tui_tick_ms: Option<u64>,
+    // Render basic markdown in notes in the TUI details view
+    pub tui_render_markdown: Option<bool>,
+    // Days to keep soft-deleted entries in the trash
+    pub trash_retention_days: Option<u64>,
+    // Size limits for free-form entry fields (bytes)
+    pub max_username_bytes: Option<usize>,
+    pub max_notes_bytes: Option<usize>,
+    // false: show usernames in list/search output without --show-users
+    pub username_secret: Option<bool>,
+    // Shell command run after the clipboard TTL clears (off by default)
+    pub clipboard_clear_hook: Option<String>,
+    // Confirm successful `get` copies on stderr
+    pub get_verbose: Option<bool>,
+    // Restore the clipboard once it changes away from a copied secret (get/TUI)
+    pub clipboard_clear_on_paste: Option<bool>,
+    // Clear the clipboard to empty after the TTL instead of restoring what it held
+    pub clipboard_no_restore: Option<bool>,
+    // Ask for the master password again before revealing secrets (show/get/TUI)
+    pub require_reveal_reauth: Option<bool>,
+    // Record `last_accessed_at` on get/show/TUI copy (costs a save per read)
+    pub track_access: Option<bool>,
+    // Extend the unlock session on every use, up to session_max_secs after unlocking
+    pub session_sliding: Option<bool>,
+    pub session_max_secs: Option<u64>,
+    // Warn on get/show when a stored password is longer than this many characters
+    pub warn_password_len: Option<usize>,
 
     // Profile management
     pub default_profile: Option<String>,
     pub profiles: Option<HashMap<String, FileProfileConfig>>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct FileGeneratorConfig {
+    #[serde(default)]
+    pub presets: HashMap<String, GeneratorPreset>,
+}
+
+/// A named set of generator settings (`add --generate --preset <name>`). Unset
+/// fields keep the configured defaults; command-line flags still override it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct GeneratorPreset {
+    pub length: Option<u16>,
+    pub lower: Option<bool>,
+    pub upper: Option<bool>,
+    pub digits: Option<bool>,
+    pub symbols: Option<bool>,
+    /// Only these symbols, for sites that accept a few (e.g. `"!#$%"`)
+    pub symbol_set: Option<String>,
+    pub avoid_ambiguous: Option<bool>,
+    pub passphrase: Option<bool>,
+    pub words: Option<u16>,
+    pub sep: Option<String>,
+}
+
+impl GeneratorPreset {
+    /// Overwrite the settings this preset defines.
+    pub fn apply_to(&self, policy: &mut GenPolicy) {
+        let toggles = [
+            (self.lower, &mut policy.lower),
+            (self.upper, &mut policy.upper),
+            (self.digits, &mut policy.digits),
+            (self.symbols, &mut policy.symbols),
+            (self.avoid_ambiguous, &mut policy.avoid_ambiguous),
+            (self.passphrase, &mut policy.passphrase),
+        ];
+        for (value, field) in toggles {
+            if let Some(v) = value {
+                *field = v;
+            }
+        }
+        if let Some(length) = self.length {
+            policy.length = length;
+        }
+        if let Some(words) = self.words {
+            policy.words = words;
+        }
+        if let Some(sep) = &self.sep {
+            policy.sep = sep.clone();
+        }
+        if let Some(symbol_set) = &self.symbol_set {
+            policy.symbol_set = Some(symbol_set.clone());
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileProfileConfig {
     pub vault_path: String,
@@ -38,11 +155,35 @@ pub struct Config {
     pub vault_path: PathBuf,
     pub clipboard_ttl: Option<u64>,
     pub backups: Option<usize>,
+    // Backup naming: dot-prefixed (Unix) and/or an extra suffix before the number
+    pub backup_hidden: Option<bool>,
+    pub backup_suffix: Option<String>,
+    pub compact_storage: Option<bool>,
+    pub stream_encryption: Option<bool>,
     // Generator defaults (optional)
     pub generator_length: Option<u16>,
+    pub generator_max_length: Option<u16>,
     pub generator_words: Option<u16>,
     pub generator_sep: Option<String>,
     pub avoid_ambiguous: Option<bool>,
+    pub generator_presets: HashMap<String, GeneratorPreset>,
+    pub tui_theme: Option<String>,
+    pub status_style: Option<String>,
+    pub tui_tick_ms: Option<u64>,
+    pub tui_render_markdown: Option<bool>,
+    pub trash_retention_days: Option<u64>,
+    pub max_username_bytes: Option<usize>,
+    pub max_notes_bytes: Option<usize>,
+    pub username_secret: Option<bool>,
+    pub clipboard_clear_hook: Option<String>,
+    pub get_verbose: Option<bool>,
+    pub clipboard_clear_on_paste: Option<bool>,
+    pub clipboard_no_restore: Option<bool>,
+    pub require_reveal_reauth: Option<bool>,
+    pub track_access: Option<bool>,
+    pub session_sliding: Option<bool>,
+    pub session_max_secs: Option<u64>,
+    pub warn_password_len: Option<usize>,
 
     pub default_profile: Option<String>,
     pub profiles: HashMap<String, ProfileConfig>,
@@ -56,15 +197,15 @@ pub struct ProfileConfig {
 impl Config {
     pub fn create(path: Option<PathBuf>, profile: Option<String>) -> Result<Self, ConfigError> {
         // 1) Load config file if present
-        let file_cfg = load_file_config();
+        let file_cfg = load_file_config()?;
 
         // 2) Resolve vault path precedence
-        let vault_path = resolve_vault_path(path, profile.as_deref(), &file_cfg)?;
+        let vault_path = resolve_vault_path(path, profile.as_deref(), &file_cfg)?.path;
 
         // 3) Resolve clipboard TTL precedence: env > config file > None (use command default)
         let clipboard_ttl = env::var("KEVI_CLIP_TTL")
             .ok()
-            .and_then(|s| s.parse::<u64>().ok())
+            .and_then(|s| parse_duration_secs(&s).ok())
             .or(file_cfg.clipboard_ttl);
 
         // 4) Resolve backups precedence: env > config file > None (library default is 2)
@@ -72,12 +213,34 @@ impl Config {
             .ok()
             .and_then(|s| s.parse::<usize>().ok())
             .or(file_cfg.backups);
+        let backup_hidden = env::var("KEVI_BACKUP_HIDDEN")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.backup_hidden);
+        // A suffix must not turn the backup name into a path
+        let backup_suffix = env::var("KEVI_BACKUP_SUFFIX")
+            .ok()
+            .or(file_cfg.backup_suffix)
+            .filter(|s| !s.contains(['/', '\\']));
+        let compact_storage = env::var("KEVI_COMPACT_STORAGE")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.compact_storage);
+        let stream_encryption = env::var("KEVI_STREAM_ENCRYPTION")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.stream_encryption);
 
         // 5) Generator defaults precedence: env > config file > None
         let gen_len = env::var("KEVI_GEN_LENGTH")
             .ok()
             .and_then(|s| s.parse::<u16>().ok())
             .or(file_cfg.generator_length);
+        let gen_max_len = env::var("KEVI_GEN_MAX_LENGTH")
+            .ok()
+            .and_then(|s| s.parse::<u16>().ok())
+            .or(file_cfg.generator_max_length)
+            .filter(|n| *n > 0);
         let gen_words = env::var("KEVI_GEN_WORDS")
             .ok()
             .and_then(|s| s.parse::<u16>().ok())
@@ -88,6 +251,93 @@ impl Config {
             .and_then(|s| s.parse::<bool>().ok())
             .or(file_cfg.avoid_ambiguous);
 
+        // 6) TUI theme precedence: env > config file > None (default theme)
+        let tui_theme = env::var("KEVI_TUI_THEME").ok().or(file_cfg.tui_theme);
+        let status_style = env::var("KEVI_STATUS_STYLE").ok().or(file_cfg.status_style);
+        let tui_tick_ms = env::var("KEVI_TUI_TICK_MS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .or(file_cfg.tui_tick_ms)
+            .filter(|ms| *ms > 0);
+        let tui_render_markdown = env::var("KEVI_TUI_RENDER_MARKDOWN")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.tui_render_markdown);
+
+        // 7) Trash retention precedence: env > config file > None (service default is 30 days)
+        let trash_retention_days = env::var("KEVI_TRASH_RETENTION_DAYS")
+            .ok()
+            .and_then(|s| s.parse::<u64>().ok())
+            .or(file_cfg.trash_retention_days);
+
+        // 8) Field size limits precedence: env > config file > None (default 64 KiB)
+        let max_username_bytes = env::var("KEVI_MAX_USERNAME_BYTES")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .or(file_cfg.max_username_bytes);
+        let max_notes_bytes = env::var("KEVI_MAX_NOTES_BYTES")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .or(file_cfg.max_notes_bytes);
+        let username_secret = env::var("KEVI_USERNAME_SECRET")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.username_secret);
+
+        // 9) Clipboard clear hook precedence: env > config file > None (no hook)
+        let clipboard_clear_hook = env::var("KEVI_CLIPBOARD_CLEAR_HOOK")
+            .ok()
+            .or(file_cfg.clipboard_clear_hook)
+            .filter(|s| !s.trim().is_empty());
+
+        // 10) Verbose get precedence: env > config file > None (quiet)
+        let get_verbose = env::var("KEVI_GET_VERBOSE")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.get_verbose);
+
+        // 11) Clear-on-paste precedence: env > config file > None (TTL only)
+        let clipboard_clear_on_paste = env::var("KEVI_CLIP_CLEAR_ON_PASTE")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.clipboard_clear_on_paste);
+
+        // 12) Reveal re-authentication precedence: env > config file > None (off)
+        let require_reveal_reauth = env::var("KEVI_REQUIRE_REVEAL_REAUTH")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.require_reveal_reauth);
+
+        // 13) Access tracking precedence: env > config file > None (off)
+        let track_access = env::var("KEVI_TRACK_ACCESS")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.track_access);
+
+        // 14) No-restore precedence: env > config file > None (restore)
+        let clipboard_no_restore = env::var("KEVI_CLIP_NO_RESTORE")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.clipboard_no_restore);
+
+        // 15) Sliding session precedence: env > config file > None (fixed expiry)
+        let session_sliding = env::var("KEVI_SESSION_SLIDING")
+            .ok()
+            .and_then(|s| s.parse::<bool>().ok())
+            .or(file_cfg.session_sliding);
+
+        // 16) Session lifetime cap precedence: env > config file > None (uncapped)
+        let session_max_secs = env::var("KEVI_SESSION_MAX")
+            .ok()
+            .and_then(|s| parse_duration_secs(&s).ok())
+            .or(file_cfg.session_max_secs);
+
+        // 17) Password length warning precedence: env > config file > None (no warning)
+        let warn_password_len = env::var("KEVI_WARN_PASSWORD_LEN")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .or(file_cfg.warn_password_len);
+
         let profiles = file_cfg
             .profiles
             .unwrap_or_default()
@@ -96,7 +346,7 @@ impl Config {
                 (
                     name,
                     ProfileConfig {
-                        vault_path: PathBuf::from(p.vault_path),
+                        vault_path: expand_path(&p.vault_path),
                     },
                 )
             })
@@ -106,77 +356,389 @@ impl Config {
             vault_path,
             clipboard_ttl,
             backups,
+            backup_hidden,
+            backup_suffix,
+            compact_storage,
+            stream_encryption,
             generator_length: gen_len,
+            generator_max_length: gen_max_len,
             generator_words: gen_words,
             generator_sep: gen_sep,
             avoid_ambiguous: avoid_amb,
+            generator_presets: file_cfg.generator.map(|g| g.presets).unwrap_or_default(),
+            tui_theme,
+            status_style,
+            tui_tick_ms,
+            tui_render_markdown,
+            trash_retention_days,
+            max_username_bytes,
+            max_notes_bytes,
+            username_secret,
+            clipboard_clear_hook,
+            get_verbose,
+            clipboard_clear_on_paste,
+            clipboard_no_restore,
+            require_reveal_reauth,
+            track_access,
+            session_sliding,
+            session_max_secs,
+            warn_password_len,
             default_profile: file_cfg.default_profile,
             profiles,
         })
     }
+
+    /// The generator preset called `name`, or an error listing the defined ones.
+    pub fn generator_preset(&self, name: &str) -> anyhow::Result<&GeneratorPreset> {
+        self.generator_presets.get(name).ok_or_else(|| {
+            let mut names: Vec<&str> = self.generator_presets.keys().map(String::as_str).collect();
+            names.sort_unstable();
+            if names.is_empty() {
+                anyhow::anyhow!(
+                    "unknown generator preset '{name}' (none defined; add [generator.presets.{name}] to config.toml)"
+                )
+            } else {
+                anyhow::anyhow!(
+                    "unknown generator preset '{name}' (defined: {})",
+                    names.join(", ")
+                )
+            }
+        })
+    }
+
+    /// Backup naming from `backup_hidden` / `backup_suffix`.
+    pub fn backup_naming(&self) -> BackupNaming {
+        BackupNaming {
+            hidden: self.backup_hidden == Some(true),
+            suffix: self.backup_suffix.clone(),
+        }
+    }
+
+    /// Whether usernames are hidden unless asked for (`username_secret`, default
+    /// true). When false, list and search show them as if `--show-users` was given.
+    pub fn usernames_secret(&self) -> bool {
+        self.username_secret != Some(false)
+    }
+
+    /// Sliding session renewal from `session_sliding` / `session_max_secs`; None keeps
+    /// the fixed expiry set at unlock.
+    pub fn session_sliding(&self) -> Option<SessionSliding> {
+        (self.session_sliding == Some(true)).then_some(SessionSliding {
+            max_secs: self.session_max_secs,
+        })
+    }
+}
+
+/// The environment variable that overrides each setting (see [`Config::create`]).
+const ENV_OVERRIDES: &[(&str, &str)] = &[
+    ("clipboard_ttl", "KEVI_CLIP_TTL"),
+    ("backups", "KEVI_BACKUPS"),
+    ("backup_hidden", "KEVI_BACKUP_HIDDEN"),
+    ("backup_suffix", "KEVI_BACKUP_SUFFIX"),
+    ("compact_storage", "KEVI_COMPACT_STORAGE"),
+    ("stream_encryption", "KEVI_STREAM_ENCRYPTION"),
+    ("generator_length", "KEVI_GEN_LENGTH"),
+    ("generator_max_length", "KEVI_GEN_MAX_LENGTH"),
+    ("generator_words", "KEVI_GEN_WORDS"),
+    ("generator_sep", "KEVI_GEN_SEP"),
+    ("avoid_ambiguous", "KEVI_AVOID_AMBIGUOUS"),
+    ("tui_theme", "KEVI_TUI_THEME"),
+    ("status_style", "KEVI_STATUS_STYLE"),
+    ("tui_tick_ms", "KEVI_TUI_TICK_MS"),
+    ("tui_render_markdown", "KEVI_TUI_RENDER_MARKDOWN"),
+    ("trash_retention_days", "KEVI_TRASH_RETENTION_DAYS"),
+    ("max_username_bytes", "KEVI_MAX_USERNAME_BYTES"),
+    ("max_notes_bytes", "KEVI_MAX_NOTES_BYTES"),
+    ("username_secret", "KEVI_USERNAME_SECRET"),
+    ("clipboard_clear_hook", "KEVI_CLIPBOARD_CLEAR_HOOK"),
+    ("get_verbose", "KEVI_GET_VERBOSE"),
+    ("clipboard_clear_on_paste", "KEVI_CLIP_CLEAR_ON_PASTE"),
+    ("clipboard_no_restore", "KEVI_CLIP_NO_RESTORE"),
+    ("require_reveal_reauth", "KEVI_REQUIRE_REVEAL_REAUTH"),
+    ("track_access", "KEVI_TRACK_ACCESS"),
+    ("session_sliding", "KEVI_SESSION_SLIDING"),
+    ("session_max_secs", "KEVI_SESSION_MAX"),
+    ("warn_password_len", "KEVI_WARN_PASSWORD_LEN"),
+];
+
+/// One resolved setting for `kevi config show`: its value (null when unset, so
+/// the command's built-in default applies) and where the value came from.
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveSetting {
+    pub key: String,
+    pub value: serde_json::Value,
+    pub source: String,
+}
+
+/// Every [`Config`] setting as [`Config::create`] resolves it for `path` and
+/// `profile`, vault path first and the rest by name.
+pub fn effective_settings(
+    path: Option<PathBuf>,
+    profile: Option<&str>,
+) -> Result<Vec<EffectiveSetting>, ConfigError> {
+    let file_cfg = load_file_config()?;
+    let resolved = resolve_vault_path(path.clone(), profile, &file_cfg)?;
+    let config = Config::create(path, profile.map(str::to_string))?;
+    let file = serde_json::to_value(&file_cfg).unwrap_or_default();
+    let serde_json::Value::Object(values) = serde_json::to_value(&config).unwrap_or_default()
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut settings = Vec::with_capacity(values.len());
+    for (key, value) in values {
+        let source = if key == "vault_path" {
+            resolved.source.to_string()
+        } else {
+            let from_file = match key.as_str() {
+                "generator_presets" => &file["generator"]["presets"],
+                _ => &file[key.as_str()],
+            };
+            let env_var = ENV_OVERRIDES
+                .iter()
+                .find(|(k, _)| *k == key)
+                .map(|(_, var)| *var)
+                .filter(|var| env::var_os(var).is_some());
+            match env_var {
+                _ if value.is_null() => "unset".to_string(),
+                Some(var) if from_file.is_null() || *from_file != value => var.to_string(),
+                _ if !from_file.is_null() => "config.toml".to_string(),
+                _ => "default".to_string(),
+            }
+        };
+        settings.push(EffectiveSetting { key, value, source });
+    }
+    settings.sort_by_key(|s| s.key != "vault_path");
+    Ok(settings)
+}
+
+/// Which precedence rule picked the vault path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VaultPathSource {
+    CliPath,
+    CliProfile,
+    Env,
+    DefaultProfile,
+    FileVaultPath,
+    BuiltinDefault,
+}
+
+impl std::fmt::Display for VaultPathSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            VaultPathSource::CliPath => "--path",
+            VaultPathSource::CliProfile => "--profile",
+            VaultPathSource::Env => "KEVI_VAULT_PATH",
+            VaultPathSource::DefaultProfile => "default_profile in config.toml",
+            VaultPathSource::FileVaultPath => "vault_path in config.toml",
+            VaultPathSource::BuiltinDefault => "built-in default",
+        })
+    }
+}
+
+/// The effective vault path, the profile it came from (if any) and the rule that won.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedVaultPath {
+    pub path: PathBuf,
+    pub profile: Option<String>,
+    pub source: VaultPathSource,
+}
+
+/// Resolve the vault path the way [`Config::create`] does, reporting how it was chosen.
+pub fn resolve_vault(
+    path: Option<PathBuf>,
+    profile: Option<&str>,
+) -> Result<ResolvedVaultPath, ConfigError> {
+    resolve_vault_path(path, profile, &load_file_config()?)
 }
 
 fn resolve_vault_path(
     cli_path: Option<PathBuf>,
     cli_profile: Option<&str>,
     file_cfg: &FileConfig,
-) -> Result<PathBuf, ConfigError> {
+) -> Result<ResolvedVaultPath, ConfigError> {
+    let resolved = |path: PathBuf, profile: Option<&str>, source| ResolvedVaultPath {
+        path,
+        profile: profile.map(str::to_string),
+        source,
+    };
+
     if let Some(p) = cli_path {
-        return Ok(p);
+        return Ok(resolved(p, None, VaultPathSource::CliPath));
     }
 
     if let Some(name) = cli_profile {
         if let Some(profiles) = file_cfg.profiles.as_ref() {
             if let Some(prof) = profiles.get(name) {
-                return Ok(PathBuf::from(&prof.vault_path));
+                return Ok(resolved(
+                    expand_path(&prof.vault_path),
+                    Some(name),
+                    VaultPathSource::CliProfile,
+                ));
             }
         }
         return Err(ConfigError::UnknownProfile(name.to_string()));
     }
 
     if let Ok(p) = env::var("KEVI_VAULT_PATH") {
-        return Ok(PathBuf::from(p));
+        return Ok(resolved(expand_path(&p), None, VaultPathSource::Env));
     }
 
     if let Some(default_name) = file_cfg.default_profile.as_deref() {
         if let Some(profs) = file_cfg.profiles.as_ref() {
             if let Some(prof) = profs.get(default_name) {
-                return Ok(PathBuf::from(&prof.vault_path));
+                return Ok(resolved(
+                    expand_path(&prof.vault_path),
+                    Some(default_name),
+                    VaultPathSource::DefaultProfile,
+                ));
             }
         }
         // If default_profile points to a missing profile, ignore it and fall through
     }
 
     if let Some(p) = file_cfg.vault_path.as_ref() {
-        return Ok(PathBuf::from(p));
+        return Ok(resolved(
+            expand_path(p),
+            None,
+            VaultPathSource::FileVaultPath,
+        ));
     }
 
-    Ok(default_vault_path())
+    Ok(resolved(
+        default_vault_path(),
+        None,
+        VaultPathSource::BuiltinDefault,
+    ))
 }
 
-fn load_file_config() -> FileConfig {
-    let (_, cfg) = load_file_config_with_path();
-    cfg
+/// Expand a leading `~` and `$VAR`/`${VAR}` references in a configured path, as
+/// a shell would. Unset variables (and a `~` without a home) are left as written.
+fn expand_path(raw: &str) -> PathBuf {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    if rest == "~" || rest.starts_with("~/") {
+        if let Some(home) = home_dir() {
+            out.push_str(&home);
+            rest = &rest[1..];
+        }
+    }
+    while let Some(i) = rest.find('$') {
+        out.push_str(&rest[..i]);
+        let after = &rest[i + 1..];
+        let (name, len) = match after.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after.len());
+                (&after[..end], end)
+            }
+        };
+        match env::var(name) {
+            Ok(value) if !name.is_empty() => out.push_str(&value),
+            _ => out.push_str(&rest[i..i + 1 + len]),
+        }
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    PathBuf::from(out)
+}
+
+fn home_dir() -> Option<String> {
+    env::var("HOME")
+        .ok()
+        .filter(|h| !h.is_empty())
+        .or_else(|| dirs::home_dir().map(|p| p.to_string_lossy().into_owned()))
+}
+
+/// `status_style` as configured (env > config file), read without resolving a
+/// vault so it can apply before any command runs. A broken `--config` file is
+/// left for the command itself to report.
+pub fn configured_status_style() -> Option<String> {
+    env::var("KEVI_STATUS_STYLE")
+        .ok()
+        .or_else(|| load_file_config().ok()?.status_style)
 }
 
-pub fn load_file_config_with_path() -> (PathBuf, FileConfig) {
+/// Limit for clipboard access and interactive prompts (`KEVI_CMD_TIMEOUT`,
+/// e.g. `30s`), so a stuck backend fails instead of hanging. Unset or `0`
+/// means no limit.
+pub fn cmd_timeout() -> Option<Duration> {
+    env::var("KEVI_CMD_TIMEOUT")
+        .ok()
+        .and_then(|s| parse_duration_secs(&s).ok())
+        .filter(|&secs| secs > 0)
+        .map(Duration::from_secs)
+}
+
+fn load_file_config() -> Result<FileConfig, ConfigError> {
+    let (_, cfg) = load_file_config_with_path()?;
+    Ok(cfg)
+}
+
+/// kevi's config directory: `<KEVI_CONFIG_DIR or platform config dir>/kevi`.
+pub fn kevi_config_dir() -> PathBuf {
     // Allow tests/users to override config dir via KEVI_CONFIG_DIR; else use platform default
     let cfg_dir = if let Ok(p) = env::var("KEVI_CONFIG_DIR") {
         PathBuf::from(p)
     } else {
         dirs::config_dir().unwrap_or_else(|| PathBuf::from("."))
     };
-    let path = cfg_dir.join("kevi").join("config.toml");
-    let cfg = if let Ok(bytes) = std::fs::read(&path) {
-        if let Ok(s) = String::from_utf8(bytes) {
-            toml::from_str::<FileConfig>(&s).unwrap_or_default()
-        } else {
-            FileConfig::default()
-        }
-    } else {
-        FileConfig::default()
+    cfg_dir.join("kevi")
+}
+
+/// Use `path` as the config file for this process instead of looking in
+/// [`kevi_config_dir`] (set from the global `--config` flag; `None` restores the lookup).
+pub fn set_config_file(path: Option<PathBuf>) {
+    *CONFIG_FILE.write().unwrap_or_else(|e| e.into_inner()) = path;
+}
+
+/// The config file in effect: `--config`, else `<kevi_config_dir>/config.toml`.
+pub fn config_file_path() -> PathBuf {
+    CONFIG_FILE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+        .unwrap_or_else(|| kevi_config_dir().join("config.toml"))
+}
+
+/// Read an intact backup when the vault fails to decrypt (set from the global
+/// `--use-backup` flag).
+pub fn set_use_backup(enabled: bool) {
+    USE_BACKUP.store(enabled, Ordering::Relaxed);
+}
+
+pub fn use_backup() -> bool {
+    USE_BACKUP.load(Ordering::Relaxed)
+}
+
+/// The config file in effect and its contents. The default config.toml is
+/// optional and read leniently; a file named by `--config` must exist and parse.
+pub fn load_file_config_with_path() -> Result<(PathBuf, FileConfig), ConfigError> {
+    let explicit = CONFIG_FILE
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .is_some();
+    let path = config_file_path();
+    if !explicit {
+        let cfg = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| toml::from_str::<FileConfig>(&s).ok())
+            .unwrap_or_default();
+        return Ok((path, cfg));
+    }
+    let s = match std::fs::read_to_string(&path) {
+        Ok(s) => s,
+        Err(source) => return Err(ConfigError::UnreadableFile { path, source }),
     };
-    (path, cfg)
+    match toml::from_str::<FileConfig>(&s) {
+        Ok(cfg) => Ok((path, cfg)),
+        Err(source) => Err(ConfigError::InvalidFile { path, source }),
+    }
 }
 
 pub fn save_file_config(path: &PathBuf, cfg: &FileConfig) -> std::io::Result<()> {
@@ -187,16 +749,19 @@ pub fn save_file_config(path: &PathBuf, cfg: &FileConfig) -> std::io::Result<()>
     std::fs::write(path, s)
 }
 
-fn default_vault_path() -> PathBuf {
-    // Prefer platform data_dir, allow override via KEVI_DATA_DIR, fallback to ~/.kevi/vault.ron
+/// kevi's data directory, which holds the default vault:
+/// `<KEVI_DATA_DIR or platform data dir>/kevi`, falling back to `~/.kevi`.
+pub fn kevi_data_dir() -> PathBuf {
     if let Ok(base) = env::var("KEVI_DATA_DIR") {
-        return PathBuf::from(base).join("kevi").join("vault.ron");
+        return PathBuf::from(base).join("kevi");
     }
-    if let Some(mut p) = dirs::data_dir() {
-        p.push("kevi");
-        p.push("vault.ron");
-        return p;
+    if let Some(p) = dirs::data_dir() {
+        return p.join("kevi");
     }
     let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(&home).join(".kevi").join("vault.ron")
+    PathBuf::from(&home).join(".kevi")
+}
+
+fn default_vault_path() -> PathBuf {
+    kevi_data_dir().join("vault.ron")
 }
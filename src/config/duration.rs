@@ -0,0 +1,78 @@
+/// Parse a human duration into whole seconds.
+///
+/// Accepts a bare integer (seconds, for backward compatibility) or a sequence of
+/// `<number><unit>` parts with units `s`, `m`, `h`, `d`, e.g. `"900"`, `"5m"`, `"1h30m"`.
+/// Used by every TTL input (CLI flags and `KEVI_*_TTL` env vars).
+pub fn parse_duration_secs(input: &str) -> Result<u64, String> {
+    let s = input.trim();
+    if s.is_empty() {
+        return Err("empty duration".to_string());
+    }
+    if let Ok(secs) = s.parse::<u64>() {
+        return Ok(secs);
+    }
+
+    let mut total: u64 = 0;
+    let mut digits = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit: u64 = match c.to_ascii_lowercase() {
+            's' => 1,
+            'm' => 60,
+            'h' => 60 * 60,
+            'd' => 24 * 60 * 60,
+            other => return Err(format!("invalid duration unit '{other}' in \"{input}\"")),
+        };
+        if digits.is_empty() {
+            return Err(format!("missing number before '{c}' in \"{input}\""));
+        }
+        let n: u64 = digits
+            .parse()
+            .map_err(|_| format!("invalid number in \"{input}\""))?;
+        digits.clear();
+        total = n
+            .checked_mul(unit)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(|| format!("duration \"{input}\" is too large"))?;
+    }
+    if !digits.is_empty() {
+        return Err(format!(
+            "missing unit after '{digits}' in \"{input}\" (use s, m, h or d)"
+        ));
+    }
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_integers_are_seconds() {
+        assert_eq!(parse_duration_secs("900"), Ok(900));
+        assert_eq!(parse_duration_secs(" 0 "), Ok(0));
+    }
+
+    #[test]
+    fn units_and_compounds() {
+        assert_eq!(parse_duration_secs("45s"), Ok(45));
+        assert_eq!(parse_duration_secs("5m"), Ok(300));
+        assert_eq!(parse_duration_secs("1h"), Ok(3600));
+        assert_eq!(parse_duration_secs("1h30m"), Ok(5400));
+        assert_eq!(parse_duration_secs("1d2h3m4s"), Ok(93784));
+        assert_eq!(parse_duration_secs("2H"), Ok(7200));
+    }
+
+    #[test]
+    fn invalid_inputs_rejected() {
+        assert!(parse_duration_secs("").is_err());
+        assert!(parse_duration_secs("m").is_err());
+        assert!(parse_duration_secs("5x").is_err());
+        assert!(parse_duration_secs("1h30").is_err());
+        assert!(parse_duration_secs("-5").is_err());
+        assert!(parse_duration_secs("99999999999999999999d").is_err());
+    }
+}
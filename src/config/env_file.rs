@@ -0,0 +1,104 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+#[cfg(target_family = "unix")]
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// Load `KEY=value` pairs from a dotenv file into the process environment.
+///
+/// Variables that are already set win over the file. Blank lines, `#`
+/// comments and an `export ` prefix are accepted; double-quoted values use the
+/// same escapes as `get --format dotenv`. On Unix a world-readable file is
+/// refused, and a file holding `KEVI_PASSWORD` that is not 0600 gets a warning.
+/// Returns the number of variables applied.
+pub fn load_env_file(path: &Path) -> Result<usize> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("cannot read env file {}", path.display()))?;
+    let pairs =
+        parse_env_file(&content).with_context(|| format!("invalid env file {}", path.display()))?;
+    check_permissions(path, pairs.iter().any(|(k, _)| k == "KEVI_PASSWORD"))?;
+
+    let mut applied = 0;
+    for (key, value) in pairs {
+        if env::var_os(&key).is_none() {
+            env::set_var(key, value);
+            applied += 1;
+        }
+    }
+    Ok(applied)
+}
+
+/// Parse dotenv content into ordered `(key, value)` pairs.
+pub fn parse_env_file(content: &str) -> Result<Vec<(String, String)>> {
+    let mut out = Vec::new();
+    for (n, raw) in content.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let Some((key, value)) = line.split_once('=') else {
+            anyhow::bail!("line {}: expected KEY=value", n + 1);
+        };
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            anyhow::bail!("line {}: invalid variable name '{key}'", n + 1);
+        }
+        out.push((key.to_string(), unquote(value.trim())));
+    }
+    Ok(out)
+}
+
+fn unquote(value: &str) -> String {
+    if let Some(inner) = value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')) {
+        return inner.to_string();
+    }
+    let Some(inner) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+        return value.to_string();
+    };
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(target_family = "unix")]
+fn check_permissions(path: &Path, has_password: bool) -> Result<()> {
+    let mode = fs::metadata(path)?.permissions().mode() & 0o777;
+    if mode & 0o004 != 0 {
+        anyhow::bail!(
+            "refusing world-readable env file {} (mode {mode:o}); run `chmod 600` on it",
+            path.display()
+        );
+    }
+    if has_password && mode != 0o600 {
+        eprintln!(
+            "{}",
+            crate::cli::output::status(
+                "⚠️",
+                format_args!(
+                    "env file {} contains KEVI_PASSWORD but has mode {mode:o}; use 0600",
+                    path.display()
+                )
+            )
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(target_family = "unix"))]
+fn check_permissions(_path: &Path, _has_password: bool) -> Result<()> {
+    Ok(())
+}
@@ -1 +1,3 @@
 pub mod app_config;
+pub mod duration;
+pub mod env_file;
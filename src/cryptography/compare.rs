@@ -0,0 +1,13 @@
+use subtle::ConstantTimeEq;
+
+/// Constant-time equality for secret bytes. Only the lengths may leak: slices
+/// of different length compare unequal immediately.
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+/// Compare two secret strings (passwords, passphrases) in constant time.
+/// Use this instead of `==` wherever both sides are secrets.
+pub fn secrets_equal(a: &str, b: &str) -> bool {
+    ct_eq(a.as_bytes(), b.as_bytes())
+}
@@ -1,6 +1,10 @@
 use anyhow::{anyhow, Result};
 use ring::rand::{SecureRandom, SystemRandom};
+#[cfg(any(test, feature = "testing"))]
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
+#[cfg(any(test, feature = "testing"))]
+use std::sync::Mutex;
 
 use crate::cryptography::wordlist::WORDS;
 use crate::vault::ports::{GenPolicy, PasswordGenerator, Rng};
@@ -15,6 +19,56 @@ impl Rng for SystemRng {
     }
 }
 
+/// Deterministic RNG for reproducible test runs: SHA-256 in counter mode over a u64 seed.
+/// Never use for real secrets; only compiled with `cfg(test)` or the `testing` feature.
+#[cfg(any(test, feature = "testing"))]
+pub struct SeededRng {
+    seed: u64,
+    state: Mutex<(u64, Vec<u8>)>,
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            state: Mutex::new((0, Vec::new())),
+        }
+    }
+}
+
+#[cfg(any(test, feature = "testing"))]
+impl Rng for SeededRng {
+    fn fill(&self, bytes: &mut [u8]) -> Result<()> {
+        let mut guard = self.state.lock().unwrap();
+        let (counter, pending) = &mut *guard;
+        for b in bytes.iter_mut() {
+            if pending.is_empty() {
+                let mut hasher = Sha256::new();
+                hasher.update(self.seed.to_le_bytes());
+                hasher.update(counter.to_le_bytes());
+                *pending = hasher.finalize().to_vec();
+                *counter += 1;
+            }
+            *b = pending.remove(0);
+        }
+        Ok(())
+    }
+}
+
+/// RNG used by CLI/TUI generation flows.
+/// With the `testing` feature, `KEVI_TEST_SEED=<u64>` selects a deterministic `SeededRng`.
+pub fn generator_rng() -> Arc<dyn Rng> {
+    #[cfg(any(test, feature = "testing"))]
+    if let Some(seed) = std::env::var("KEVI_TEST_SEED")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+    {
+        return Arc::new(SeededRng::new(seed));
+    }
+    Arc::new(SystemRng)
+}
+
 pub struct DefaultPasswordGenerator {
     rng: Arc<dyn Rng>,
     wordlist: &'static [&'static str],
@@ -36,6 +90,7 @@ impl DefaultPasswordGenerator {
 
 impl PasswordGenerator for DefaultPasswordGenerator {
     fn generate(&self, policy: &GenPolicy) -> Result<String> {
+        check_length_bound(policy, self.wordlist)?;
         if policy.passphrase {
             return generate_passphrase(&*self.rng, self.wordlist, policy.words, &policy.sep);
         }
@@ -43,6 +98,38 @@ impl PasswordGenerator for DefaultPasswordGenerator {
     }
 }
 
+/// Reject policies whose output could exceed `policy.max_length` characters
+/// before anything is allocated. Passphrases are bounded by their worst case:
+/// every word as long as the longest in the list.
+fn check_length_bound(policy: &GenPolicy, wordlist: &[&str]) -> Result<()> {
+    let max = usize::from(policy.max_length);
+    if policy.passphrase {
+        let words = usize::from(policy.words.max(1));
+        let longest = wordlist
+            .iter()
+            .map(|w| w.chars().count())
+            .max()
+            .unwrap_or(0);
+        let worst = longest
+            .checked_mul(words)
+            .and_then(|n| n.checked_add(policy.sep.chars().count().checked_mul(words - 1)?));
+        match worst {
+            Some(n) if n <= max => Ok(()),
+            _ => Err(anyhow!(
+                "a {words}-word passphrase can exceed the generator maximum of {max} characters \
+                 (generator_max_length)"
+            )),
+        }
+    } else if usize::from(policy.length) > max {
+        Err(anyhow!(
+            "length {} exceeds the generator maximum of {max} characters (generator_max_length)",
+            policy.length
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 // ===== Character-mode generator =====
 
 const LOWER: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
@@ -51,12 +138,27 @@ const DIGITS: &[u8] = b"0123456789";
 const SYMBOLS: &[u8] = b"!@#$%^&*()-_=+[]{};:,.?/\\|`~";
 const AMBIGUOUS: &[u8] = b"O0Il|1"; // Avoid common ambiguities
 
+/// The symbols `policy` draws from: its `symbol_set` without duplicates, or the
+/// built-in set. A custom set may only contain ASCII punctuation.
+fn symbol_class(policy: &GenPolicy) -> Result<Vec<u8>> {
+    let Some(set) = &policy.symbol_set else {
+        return Ok(SYMBOLS.to_vec());
+    };
+    if !set.bytes().all(|c| c.is_ascii_punctuation()) {
+        return Err(anyhow!("symbol_set may only contain ASCII punctuation"));
+    }
+    let mut v = set.as_bytes().to_vec();
+    v.sort_unstable();
+    v.dedup();
+    Ok(v)
+}
+
 fn filter_ambiguous(mut v: Vec<u8>) -> Vec<u8> {
     v.retain(|c| !AMBIGUOUS.contains(c));
     v
 }
 
-fn uniform_index(rng: &dyn Rng, len: usize) -> Result<usize> {
+pub(crate) fn uniform_index(rng: &dyn Rng, len: usize) -> Result<usize> {
     if len == 0 {
         return Err(anyhow!("empty pool"));
     }
@@ -96,7 +198,7 @@ fn generate_chars(rng: &dyn Rng, policy: &GenPolicy) -> Result<String> {
         classes.push(DIGITS.to_vec());
     }
     if policy.symbols {
-        classes.push(SYMBOLS.to_vec());
+        classes.push(symbol_class(policy)?);
     }
     if classes.is_empty() {
         return Err(anyhow!("No character classes selected"));
@@ -113,7 +215,7 @@ fn generate_chars(rng: &dyn Rng, policy: &GenPolicy) -> Result<String> {
         ));
     }
 
-    let need = policy.length as usize;
+    let need = usize::from(policy.length);
     if need < classes.len() {
         return Err(anyhow!("Length must be >= number of selected classes"));
     }
@@ -153,7 +255,7 @@ fn generate_passphrase(
     if wordlist.is_empty() {
         return Err(anyhow!("wordlist empty"));
     }
-    let count = words.max(1) as usize;
+    let count = usize::from(words.max(1));
     let mut parts: Vec<&'static str> = Vec::with_capacity(count);
     let n = wordlist.len();
     for _ in 0..count {
@@ -163,6 +265,34 @@ fn generate_passphrase(
     Ok(parts.join(sep))
 }
 
+// ===== Policy inference =====
+
+/// Character-mode policy shaped like `sample`: the same length and the classes it
+/// uses (any other ASCII character counts as a symbol). Ambiguous characters are
+/// only allowed if the sample already contains one.
+pub fn infer_policy(sample: &str) -> Result<GenPolicy> {
+    let length = u16::try_from(sample.chars().count())
+        .map_err(|_| anyhow!("password too long to infer a policy"))?;
+    let policy = GenPolicy {
+        length,
+        lower: sample.chars().any(|c| c.is_ascii_lowercase()),
+        upper: sample.chars().any(|c| c.is_ascii_uppercase()),
+        digits: sample.chars().any(|c| c.is_ascii_digit()),
+        symbols: sample
+            .chars()
+            .any(|c| c.is_ascii() && !c.is_ascii_alphanumeric()),
+        avoid_ambiguous: !sample.bytes().any(|b| AMBIGUOUS.contains(&b)),
+        passphrase: false,
+        ..GenPolicy::default()
+    };
+    if !(policy.lower || policy.upper || policy.digits || policy.symbols) {
+        return Err(anyhow!(
+            "cannot infer a policy: the password has no ASCII letters, digits or symbols"
+        ));
+    }
+    Ok(policy)
+}
+
 // ===== Basic strength estimator (optional UI hint) =====
 pub fn estimate_bits_char_mode(policy: &GenPolicy) -> f64 {
     let mut pool: usize = 0;
@@ -176,7 +306,7 @@ pub fn estimate_bits_char_mode(policy: &GenPolicy) -> f64 {
         pool += DIGITS.len();
     }
     if policy.symbols {
-        pool += SYMBOLS.len();
+        pool += symbol_class(policy).map_or(0, |v| v.len());
     }
     if policy.avoid_ambiguous {
         // Remove ambiguous characters approximately
@@ -198,6 +328,28 @@ pub fn estimate_bits_passphrase(words: u16, wordlist_len: usize) -> f64 {
     (wordlist_len as f64).log2() * (words as f64)
 }
 
+/// Rough entropy estimate for an existing password: length × log2 of the
+/// character pool implied by the classes it uses.
+pub fn estimate_bits_password(password: &str) -> f64 {
+    let mut pool: usize = 0;
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        pool += LOWER.len();
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        pool += UPPER.len();
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        pool += DIGITS.len();
+    }
+    if password.chars().any(|c| !c.is_ascii_alphanumeric()) {
+        pool += SYMBOLS.len();
+    }
+    if pool == 0 {
+        return 0.0;
+    }
+    (pool as f64).log2() * (password.chars().count() as f64)
+}
+
 pub fn strength_label(bits: f64) -> &'static str {
     if bits < 28.0 {
         "very weak"
@@ -274,6 +426,25 @@ mod tests {
         assert!(s.chars().all(|c| c.is_ascii_alphabetic()));
     }
 
+    #[test]
+    fn char_generator_draws_symbols_from_the_symbol_set() {
+        let gen = DefaultPasswordGenerator::new(Arc::new(MockRng::new(&[1, 5, 9, 13, 17])));
+        let mut p = GenPolicy {
+            length: 32,
+            symbol_set: Some("!#!".to_string()),
+            ..GenPolicy::default()
+        };
+        let s = gen.generate(&p).unwrap();
+        assert!(s.contains(['!', '#']), "{s}");
+        assert!(s
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '!' || c == '#'));
+        p.symbol_set = Some("!a".to_string());
+        assert!(gen.generate(&p).is_err());
+        p.symbol_set = Some(String::new());
+        assert!(gen.generate(&p).is_err());
+    }
+
     #[test]
     fn invalid_policy_rejected() {
         let rng = Arc::new(MockRng::new(&[0; 32]));
@@ -294,6 +465,44 @@ mod tests {
         assert!(gen.generate(&p).is_err());
     }
 
+    #[test]
+    fn length_is_bounded_by_max_length() {
+        let gen = DefaultPasswordGenerator::new(Arc::new(MockRng::new(&[7; 8])));
+        let mut p = GenPolicy {
+            length: 64,
+            max_length: 64,
+            ..GenPolicy::default()
+        };
+        assert_eq!(gen.generate(&p).unwrap().len(), 64);
+        p.length = 65;
+        let err = gen.generate(&p).unwrap_err().to_string();
+        assert!(err.contains("generator_max_length"), "{err}");
+        p.length = u16::MAX;
+        p.max_length = u16::MAX - 1;
+        assert!(gen.generate(&p).is_err());
+    }
+
+    #[test]
+    fn passphrase_worst_case_is_bounded_without_overflow() {
+        static LIST: &[&str] = &["abcd", "ef"];
+        let gen =
+            DefaultPasswordGenerator::new_with_wordlist(Arc::new(MockRng::new(&[3; 8])), LIST);
+        // 3 words of at most 4 characters plus 2 separators: 14
+        let mut p = GenPolicy {
+            passphrase: true,
+            words: 3,
+            sep: ":".to_string(),
+            max_length: 14,
+            ..GenPolicy::default()
+        };
+        assert!(gen.generate(&p).is_ok());
+        p.max_length = 13;
+        assert!(gen.generate(&p).is_err());
+        p.words = u16::MAX;
+        p.max_length = u16::MAX;
+        assert!(gen.generate(&p).is_err());
+    }
+
     #[test]
     fn passphrase_mode_generates_words() {
         let rng = Arc::new(MockRng::new(&[1, 2, 3, 4, 5, 6, 7, 8]));
@@ -310,4 +519,51 @@ mod tests {
         assert!(parts.iter().all(|w| !w.is_empty()));
         assert!(s.chars().all(|c| c.is_ascii_lowercase() || c == ':'));
     }
+
+    #[test]
+    fn seeded_rng_is_reproducible() {
+        let p = GenPolicy::default();
+        let a = DefaultPasswordGenerator::new(Arc::new(SeededRng::new(42)))
+            .generate(&p)
+            .unwrap();
+        let b = DefaultPasswordGenerator::new(Arc::new(SeededRng::new(42)))
+            .generate(&p)
+            .unwrap();
+        let c = DefaultPasswordGenerator::new(Arc::new(SeededRng::new(43)))
+            .generate(&p)
+            .unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn estimate_bits_password_grows_with_classes_and_length() {
+        assert_eq!(estimate_bits_password(""), 0.0);
+        let lower = estimate_bits_password("abcdefgh");
+        let mixed = estimate_bits_password("abcDEF12");
+        assert!(mixed > lower);
+        assert!(estimate_bits_password("abcdefghabcdefgh") > lower);
+    }
+
+    #[test]
+    fn infer_policy_matches_length_and_classes() {
+        let p = infer_policy("abcd2345").unwrap();
+        assert_eq!(p.length, 8);
+        assert!(p.lower && p.digits && !p.upper && !p.symbols);
+        assert!(p.avoid_ambiguous, "no ambiguous characters observed");
+
+        let p = infer_policy("Pass-w0rd!").unwrap();
+        assert!(p.lower && p.upper && p.digits && p.symbols);
+        assert!(!p.avoid_ambiguous, "0 is ambiguous, so keep allowing it");
+
+        let gen = DefaultPasswordGenerator::new(Arc::new(SeededRng::new(7)));
+        let rotated = gen.generate(&infer_policy("abcd2345").unwrap()).unwrap();
+        assert_eq!(rotated.len(), 8);
+        assert!(rotated
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+
+        assert!(infer_policy("").is_err());
+        assert!(infer_policy("äöü").is_err());
+    }
 }
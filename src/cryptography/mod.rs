@@ -1,3 +1,4 @@
+pub mod compare;
 pub mod generator;
 pub mod memlock;
 pub mod primitives;
@@ -5,7 +5,10 @@ use ring::{
     rand::{SecureRandom, SystemRandom},
 };
 use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use zeroize::Zeroizing;
 
 pub const KEY_LEN: usize = 32; // 256-bit key
 pub const NONCE_LEN: usize = 12; // 96-bit GCM nonce
@@ -13,7 +16,7 @@ pub const SALT_LEN: usize = 16; // Argon2 salt
 
 // Header layout (little-endian):
 // magic: 4 bytes = b"KEVI"
-// version: u16 = 1
+// version: u16 = 1 (one AEAD over the whole plaintext) or 2 (chunked, see below)
 // kdf_id: u8 (2 = Argon2id; other values unsupported)
 // aead_id: u8 (1 = AES-256-GCM, 2 reserved for CHACHA20-POLY1305)
 // m_cost_kib: u32
@@ -22,7 +25,18 @@ pub const SALT_LEN: usize = 16; // Argon2 salt
 // salt: [u8; SALT_LEN]
 // nonce: [u8; NONCE_LEN]
 pub const HEADER_MAGIC: &[u8; 4] = b"KEVI";
+pub const HEADER_LEN: usize = 4 + 2 + 1 + 1 + 4 * 3 + SALT_LEN + NONCE_LEN;
 pub const HEADER_VERSION: u16 = 1;
+/// Chunked AEAD (STREAM construction): the plaintext is sealed in
+/// `STREAM_CHUNK_LEN` pieces, each under nonce `header_nonce[..7] || counter
+/// (u32 BE) || last flag`, with the header as AAD. Reordered, dropped or
+/// truncated chunks fail to open. Memory stays bounded by a couple of chunks.
+pub const HEADER_VERSION_STREAM: u16 = 2;
+pub const STREAM_CHUNK_LEN: usize = 64 * 1024;
+/// Plaintext size from which the chunked format is worth enabling
+/// (`stream_encryption`); below it the single-shot format costs nothing extra.
+pub const STREAM_SUGGESTED_MIN_LEN: usize = 8 * 1024 * 1024;
+const STREAM_PREFIX_LEN: usize = NONCE_LEN - 5;
 pub const KDF_ARGON2ID: u8 = 2;
 pub const AEAD_AES256GCM: u8 = 1;
 
@@ -31,6 +45,70 @@ pub fn default_params() -> (u32, u32, u32) {
     (64 * 1024, 3, 1)
 }
 
+/// Named Argon2id settings for `init --preset`, stored in the header like any other params.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KdfPreset {
+    /// OWASP minimum (19 MiB, 2 iterations); for slow or memory-constrained machines
+    Fast,
+    /// Same as `default_params()`
+    Balanced,
+    /// 256 MiB, 4 iterations, 4 lanes; for vaults worth a slower unlock
+    Paranoid,
+}
+
+/// `(preset, (m_cost_kib, t_cost, p_lanes))`
+pub const KDF_PRESETS: [(KdfPreset, (u32, u32, u32)); 3] = [
+    (KdfPreset::Fast, (19 * 1024, 2, 1)),
+    (KdfPreset::Balanced, (64 * 1024, 3, 1)),
+    (KdfPreset::Paranoid, (256 * 1024, 4, 4)),
+];
+
+impl KdfPreset {
+    pub fn params(self) -> (u32, u32, u32) {
+        KDF_PRESETS
+            .iter()
+            .find(|(p, _)| *p == self)
+            .map(|(_, params)| *params)
+            .unwrap_or_else(default_params)
+    }
+}
+
+/// Upper bounds for `calibrate_params`, so a fast machine does not produce a
+/// vault that slower machines cannot reasonably open.
+pub const MAX_AUTO_M_COST_KIB: u32 = 1024 * 1024;
+pub const MAX_AUTO_T_COST: u32 = 10;
+
+/// Pick Argon2id params that keep one derivation on this machine under `target`.
+///
+/// Memory is doubled first (the stronger knob against GPU attacks), then the
+/// remaining budget goes to iterations. Never returns less than `default_params()`.
+pub fn calibrate_params(target: Duration) -> Result<(u32, u32, u32)> {
+    let (mut m_cost_kib, t_cost, p_lanes) = default_params();
+    let salt = [0u8; SALT_LEN];
+    let time = |m: u32, t: u32| -> Result<Duration> {
+        let start = Instant::now();
+        derive_key_argon2id("kevi-calibration", &salt, m, t, p_lanes)?;
+        Ok(start.elapsed())
+    };
+
+    let mut elapsed = time(m_cost_kib, t_cost)?;
+    if elapsed >= target {
+        return Ok(default_params());
+    }
+    while m_cost_kib < MAX_AUTO_M_COST_KIB {
+        let next = time(m_cost_kib * 2, t_cost)?;
+        if next >= target {
+            break;
+        }
+        m_cost_kib *= 2;
+        elapsed = next;
+    }
+    // Derivation time scales roughly linearly with iterations
+    let per_iter = (elapsed.as_nanos() / u128::from(t_cost)).max(1);
+    let fit = (target.as_nanos() / per_iter).min(u128::from(MAX_AUTO_T_COST)) as u32;
+    Ok((m_cost_kib, fit.max(t_cost), p_lanes))
+}
+
 pub fn derive_key_argon2id(
     password: &str,
     salt: &[u8],
@@ -49,6 +127,7 @@ pub fn derive_key_argon2id(
 }
 
 fn build_header(
+    version: u16,
     salt: &[u8; SALT_LEN],
     nonce: &[u8; NONCE_LEN],
     m_cost_kib: u32,
@@ -57,7 +136,7 @@ fn build_header(
 ) -> Vec<u8> {
     let mut h = Vec::with_capacity(4 + 2 + 1 + 1 + 4 * 3 + SALT_LEN + NONCE_LEN);
     h.extend_from_slice(HEADER_MAGIC);
-    h.extend_from_slice(&HEADER_VERSION.to_le_bytes());
+    h.extend_from_slice(&version.to_le_bytes());
     h.push(KDF_ARGON2ID);
     h.push(AEAD_AES256GCM);
     h.extend_from_slice(&m_cost_kib.to_le_bytes());
@@ -95,15 +174,14 @@ pub enum HeaderError {
 }
 
 pub fn parse_kevi_header(data: &[u8]) -> std::result::Result<(KeviHeader, usize), HeaderError> {
-    let min_len = 4 + 2 + 1 + 1 + 4 * 3 + SALT_LEN + NONCE_LEN;
-    if data.len() < min_len {
+    if data.len() < HEADER_LEN {
         return Err(HeaderError::TooShort);
     }
     if &data[0..4] != HEADER_MAGIC {
         return Err(HeaderError::InvalidMagic);
     }
     let version = u16::from_le_bytes([data[4], data[5]]);
-    if version != HEADER_VERSION {
+    if version != HEADER_VERSION && version != HEADER_VERSION_STREAM {
         return Err(HeaderError::UnsupportedVersion(version));
     }
     let kdf_id = data[6];
@@ -140,11 +218,17 @@ pub fn parse_kevi_header(data: &[u8]) -> std::result::Result<(KeviHeader, usize)
 }
 
 /// Compute a fingerprint of header fields excluding the nonce. This allows
-/// binding a derived-key cache to a specific vault configuration.
+/// binding a derived-key cache to a specific vault configuration. The chunked
+/// format derives the same key, so it shares the version 1 fingerprint and
+/// switching `stream_encryption` keeps the session.
 pub fn header_fingerprint_excluding_nonce(hdr: &KeviHeader) -> String {
+    let version = match hdr.version {
+        HEADER_VERSION_STREAM => HEADER_VERSION,
+        v => v,
+    };
     let mut hasher = Sha256::new();
     hasher.update(HEADER_MAGIC);
-    hasher.update(hdr.version.to_le_bytes());
+    hasher.update(version.to_le_bytes());
     hasher.update([hdr.kdf_id]);
     hasher.update([hdr.aead_id]);
     hasher.update(hdr.m_cost_kib.to_le_bytes());
@@ -156,8 +240,16 @@ pub fn header_fingerprint_excluding_nonce(hdr: &KeviHeader) -> String {
 }
 
 pub fn encrypt_vault(data: &[u8], password: &str) -> Result<Vec<u8>> {
-    // Derive key using defaults, then delegate to key-based path to avoid AEAD duplication
-    let (m_cost_kib, t_cost, p_lanes) = default_params();
+    encrypt_vault_with_params(data, password, default_params())
+}
+
+/// Encrypt with a password using explicit `(m_cost_kib, t_cost, p_lanes)` and a fresh salt.
+pub fn encrypt_vault_with_params(
+    data: &[u8],
+    password: &str,
+    (m_cost_kib, t_cost, p_lanes): (u32, u32, u32),
+) -> Result<Vec<u8>> {
+    // Derive key, then delegate to key-based path to avoid AEAD duplication
     let rng = SystemRandom::new();
     let mut salt = [0u8; SALT_LEN];
     rng.fill(&mut salt)
@@ -192,7 +284,14 @@ pub fn encrypt_vault_with_key(
     let sealing_key = aead::LessSafeKey::new(unbound);
     let nonce = aead::Nonce::assume_unique_for_key(nonce_bytes);
 
-    let header = build_header(&salt, &nonce_bytes, m_cost_kib, t_cost, p_lanes);
+    let header = build_header(
+        HEADER_VERSION,
+        &salt,
+        &nonce_bytes,
+        m_cost_kib,
+        t_cost,
+        p_lanes,
+    );
     let mut in_out = data.to_vec();
     in_out.reserve(aead::AES_256_GCM.tag_len());
     sealing_key
@@ -204,8 +303,15 @@ pub fn encrypt_vault_with_key(
 }
 
 /// Decrypt with a provided derived key. Uses header as AAD and verifies.
+/// Both the single-shot and the chunked format are accepted.
 pub fn decrypt_vault_with_key(data: &[u8], derived_key: &[u8; KEY_LEN]) -> Result<Vec<u8>> {
-    let (_hdr, ct_offset) = parse_kevi_header(data).map_err(|e| anyhow!("invalid header: {e}"))?;
+    let (hdr, ct_offset) = parse_kevi_header(data).map_err(|e| anyhow!("invalid header: {e}"))?;
+    if hdr.version == HEADER_VERSION_STREAM {
+        // Wiped if a later chunk fails to open
+        let mut out = Zeroizing::new(Vec::with_capacity(data.len()));
+        decrypt_stream_with_key(data, &mut *out, derived_key)?;
+        return Ok(std::mem::take(&mut *out));
+    }
     let ciphertext = &data[ct_offset..];
     let unbound = aead::UnboundKey::new(&aead::AES_256_GCM, derived_key)
         .map_err(|_| anyhow!("failed to create opening key"))?;
@@ -220,3 +326,141 @@ pub fn decrypt_vault_with_key(data: &[u8], derived_key: &[u8; KEY_LEN]) -> Resul
         .map_err(|_| anyhow!("decryption failed"))?;
     Ok(pt.to_vec())
 }
+
+/// Like [`encrypt_vault_with_key`], but writes the chunked format
+/// (`HEADER_VERSION_STREAM`) without copying the whole plaintext.
+pub fn encrypt_vault_with_key_stream(
+    data: &[u8],
+    m_cost_kib: u32,
+    t_cost: u32,
+    p_lanes: u32,
+    salt: [u8; SALT_LEN],
+    derived_key: &[u8; KEY_LEN],
+) -> Result<Vec<u8>> {
+    let chunks = data.len() / STREAM_CHUNK_LEN + 1;
+    let mut out =
+        Vec::with_capacity(HEADER_LEN + data.len() + chunks * aead::AES_256_GCM.tag_len());
+    encrypt_stream_with_key(
+        data,
+        &mut out,
+        m_cost_kib,
+        t_cost,
+        p_lanes,
+        salt,
+        derived_key,
+    )?;
+    Ok(out)
+}
+
+/// Encrypt everything `reader` yields into `writer` in the chunked format,
+/// holding at most two plaintext chunks in memory.
+pub fn encrypt_stream_with_key<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    m_cost_kib: u32,
+    t_cost: u32,
+    p_lanes: u32,
+    salt: [u8; SALT_LEN],
+    derived_key: &[u8; KEY_LEN],
+) -> Result<()> {
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce_bytes)
+        .map_err(|_| anyhow!("failed to generate nonce"))?;
+    let header = build_header(
+        HEADER_VERSION_STREAM,
+        &salt,
+        &nonce_bytes,
+        m_cost_kib,
+        t_cost,
+        p_lanes,
+    );
+    writer.write_all(&header)?;
+    let key = stream_key(derived_key)?;
+
+    let tag_len = aead::AES_256_GCM.tag_len();
+    let mut cur = Zeroizing::new(Vec::with_capacity(STREAM_CHUNK_LEN + tag_len));
+    let mut next = Zeroizing::new(Vec::with_capacity(STREAM_CHUNK_LEN + tag_len));
+    read_chunk(&mut reader, &mut cur, STREAM_CHUNK_LEN)?;
+    for counter in 0u32.. {
+        read_chunk(&mut reader, &mut next, STREAM_CHUNK_LEN)?;
+        let last = next.is_empty();
+        key.seal_in_place_append_tag(
+            stream_nonce(&nonce_bytes, counter, last),
+            aead::Aad::from(&header),
+            &mut *cur,
+        )
+        .map_err(|_| anyhow!("encryption failed"))?;
+        writer.write_all(&cur)?;
+        if last {
+            return Ok(());
+        }
+        std::mem::swap(&mut cur, &mut next);
+    }
+    Err(anyhow!("plaintext too large for the chunked format"))
+}
+
+/// Decrypt a chunked vault from `reader` into `writer`. Chunks are verified one
+/// at a time, so on error `writer` may already hold a prefix of the plaintext
+/// that the caller must discard.
+pub fn decrypt_stream_with_key<R: Read, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    derived_key: &[u8; KEY_LEN],
+) -> Result<()> {
+    let mut header = [0u8; HEADER_LEN];
+    reader
+        .read_exact(&mut header)
+        .map_err(|_| anyhow!("invalid header: {}", HeaderError::TooShort))?;
+    let (hdr, _) = parse_kevi_header(&header).map_err(|e| anyhow!("invalid header: {e}"))?;
+    if hdr.version != HEADER_VERSION_STREAM {
+        return Err(anyhow!(
+            "not a chunked vault (header version {})",
+            hdr.version
+        ));
+    }
+    let key = stream_key(derived_key)?;
+
+    let sealed_len = STREAM_CHUNK_LEN + aead::AES_256_GCM.tag_len();
+    let mut cur = Zeroizing::new(Vec::with_capacity(sealed_len));
+    let mut next = Zeroizing::new(Vec::with_capacity(sealed_len));
+    read_chunk(&mut reader, &mut cur, sealed_len)?;
+    for counter in 0u32.. {
+        read_chunk(&mut reader, &mut next, sealed_len)?;
+        let last = next.is_empty();
+        let pt = key
+            .open_in_place(
+                stream_nonce(&hdr.nonce, counter, last),
+                aead::Aad::from(&header),
+                &mut cur,
+            )
+            .map_err(|_| anyhow!("decryption failed"))?;
+        writer.write_all(pt)?;
+        if last {
+            return Ok(());
+        }
+        std::mem::swap(&mut cur, &mut next);
+    }
+    Err(anyhow!("decryption failed"))
+}
+
+fn stream_key(derived_key: &[u8; KEY_LEN]) -> Result<aead::LessSafeKey> {
+    let unbound = aead::UnboundKey::new(&aead::AES_256_GCM, derived_key)
+        .map_err(|_| anyhow!("failed to create stream key"))?;
+    Ok(aead::LessSafeKey::new(unbound))
+}
+
+fn stream_nonce(base: &[u8; NONCE_LEN], counter: u32, last: bool) -> aead::Nonce {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[..STREAM_PREFIX_LEN].copy_from_slice(&base[..STREAM_PREFIX_LEN]);
+    nonce[STREAM_PREFIX_LEN..NONCE_LEN - 1].copy_from_slice(&counter.to_be_bytes());
+    nonce[NONCE_LEN - 1] = u8::from(last);
+    aead::Nonce::assume_unique_for_key(nonce)
+}
+
+/// Replace `buf` with up to `len` bytes from `reader`; shorter only at end of input.
+fn read_chunk<R: Read>(reader: &mut R, buf: &mut Vec<u8>, len: usize) -> Result<()> {
+    buf.clear();
+    reader.by_ref().take(len as u64).read_to_end(buf)?;
+    Ok(())
+}
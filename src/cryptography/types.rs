@@ -40,3 +40,35 @@ pub mod secret_string_option {
         Ok(opt.map(|s| SecretString::new(s.into())))
     }
 }
+
+pub mod secret_string_map {
+    use secrecy::{ExposeSecret, SecretString};
+    use serde::ser::SerializeMap;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::collections::BTreeMap;
+
+    pub fn serialize<S>(
+        value: &BTreeMap<String, SecretString>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(value.len()))?;
+        for (k, v) in value {
+            map.serialize_entry(k, v.expose_secret())?;
+        }
+        map.end()
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<BTreeMap<String, SecretString>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw: BTreeMap<String, String> = BTreeMap::deserialize(deserializer)?;
+        Ok(raw
+            .into_iter()
+            .map(|(k, v)| (k, SecretString::new(v.into())))
+            .collect())
+    }
+}
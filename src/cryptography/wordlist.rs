@@ -48,7 +48,37 @@ pub static WORDS: &[&str] = &[
     "cycle", "dad", "daily", "daisy", "dance", "danger", "dart", "dash", "data", "date",
     "daughter", "dawn", "day", "debit", "debris", "decade", "decide", "deck", "decorate", "deer",
     "defend", "define", "degree", "delay", "deliver", "delta", "demand", "denim", "dent", "deny",
-    "depart", "depend", "deposit", "derive", "desk", "desert", "design", "desk", "detail",
-    "detect", "develop", "device", "devote", "diagram", "dial", "diamond", "diary", "dice",
-    "diesel", "diet", "differ", "digital", "dignity", "dilemma",
+    "depart", "depend", "deposit", "derive", "desert", "design", "desk", "detail", "detect",
+    "develop", "device", "devote", "diagram", "dial", "diamond", "diary", "dice", "diesel", "diet",
+    "differ", "digital", "dignity", "dilemma",
 ];
+
+/// Words that appear more than once in `list` (each reported once, sorted).
+/// Duplicates would make passphrase entropy lower than `log2(list.len())` per word.
+pub fn duplicate_words<'a>(list: &[&'a str]) -> Vec<&'a str> {
+    let mut sorted = list.to_vec();
+    sorted.sort_unstable();
+    let mut dups: Vec<&str> = sorted
+        .windows(2)
+        .filter(|w| w[0] == w[1])
+        .map(|w| w[0])
+        .collect();
+    dups.dedup();
+    dups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_wordlist_has_unique_entries() {
+        assert_eq!(duplicate_words(WORDS), Vec::<&str>::new());
+        assert!(WORDS.iter().all(|w| !w.is_empty() && w.is_ascii()));
+    }
+
+    #[test]
+    fn duplicate_words_reports_each_repeat_once() {
+        assert_eq!(duplicate_words(&["b", "a", "b", "c", "b"]), vec!["b"]);
+    }
+}
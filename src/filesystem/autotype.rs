@@ -0,0 +1,85 @@
+use anyhow::{anyhow, Result};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+/// External program used by `get --type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoTypeTool {
+    Xdotool,
+    Wtype,
+}
+
+impl AutoTypeTool {
+    pub fn program(self) -> &'static str {
+        match self {
+            AutoTypeTool::Xdotool => "xdotool",
+            AutoTypeTool::Wtype => "wtype",
+        }
+    }
+
+    fn stdin_args(self) -> &'static [&'static str] {
+        match self {
+            AutoTypeTool::Xdotool => &["type", "--clearmodifiers", "--file", "-"],
+            AutoTypeTool::Wtype => &["-"],
+        }
+    }
+}
+
+/// The tool matching the display server: `wtype` when `WAYLAND_DISPLAY` is set,
+/// `xdotool` when only `DISPLAY` is. `None` without a graphical session.
+pub fn tool_for_session(wayland: bool, x11: bool) -> Option<AutoTypeTool> {
+    if wayland {
+        Some(AutoTypeTool::Wtype)
+    } else if x11 {
+        Some(AutoTypeTool::Xdotool)
+    } else {
+        None
+    }
+}
+
+/// Locate `program` on `PATH`.
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|p| p.is_file())
+}
+
+/// Pick the tool for this session and check that it is installed.
+pub fn detect_tool() -> Result<AutoTypeTool> {
+    let tool = tool_for_session(
+        std::env::var_os("WAYLAND_DISPLAY").is_some(),
+        std::env::var_os("DISPLAY").is_some(),
+    )
+    .ok_or_else(|| {
+        anyhow!("auto-type needs a graphical session (no WAYLAND_DISPLAY or DISPLAY)")
+    })?;
+    if find_in_path(tool.program()).is_none() {
+        return Err(anyhow!(
+            "auto-type needs `{}` on PATH; install it or use the clipboard instead",
+            tool.program()
+        ));
+    }
+    Ok(tool)
+}
+
+/// Type `text` into the focused window with `tool`. The text goes over stdin so
+/// it never shows up in the tool's argv (readable via `/proc/<pid>/cmdline`).
+pub fn type_text(tool: AutoTypeTool, text: &str) -> Result<()> {
+    let mut child = Command::new(tool.program())
+        .args(tool.stdin_args())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| anyhow!("failed to run {}: {e}", tool.program()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(anyhow!("{} exited with {status}", tool.program()));
+    }
+    Ok(())
+}
@@ -1,10 +1,15 @@
 use crate::config::app_config::Config;
+use crate::config::duration::parse_duration_secs;
+use crate::cryptography::compare::secrets_equal;
 use anyhow::{anyhow, Result};
 use copypasta::{ClipboardContext, ClipboardProvider};
 use secrecy::{ExposeSecret, SecretString};
+use std::path::Path;
+use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use zeroize::Zeroizing;
 
 pub trait ClipboardEngine: Send + Sync + 'static {
     fn get_contents(&self) -> Result<Option<String>>;
@@ -17,6 +22,7 @@ pub struct SystemClipboardEngine {
 
 impl SystemClipboardEngine {
     pub fn new() -> Result<Self> {
+        log::debug!("clipboard backend: {}", backend_name());
         let ctx =
             ClipboardContext::new().map_err(|e| anyhow!("Failed to access clipboard: {e}"))?;
         Ok(Self {
@@ -25,6 +31,17 @@ impl SystemClipboardEngine {
     }
 }
 
+/// Which platform clipboard `ClipboardContext` talks to, for diagnostics.
+fn backend_name() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "macOS pasteboard"
+    } else if cfg!(windows) {
+        "Windows clipboard"
+    } else {
+        "X11 selection (Wayland via XWayland)"
+    }
+}
+
 impl ClipboardEngine for SystemClipboardEngine {
     fn get_contents(&self) -> Result<Option<String>> {
         let mut guard = self.ctx.lock().unwrap();
@@ -42,33 +59,259 @@ impl ClipboardEngine for SystemClipboardEngine {
     }
 }
 
+/// Callback invoked by the restore thread once the clipboard has been cleared.
+pub type ClearCallback = Box<dyn FnOnce() + Send + 'static>;
+
+/// Clipboard contents saved for restoring after the TTL. They may be an earlier
+/// secret, so they are wiped from memory once restored or dropped.
+type Previous = Option<Zeroizing<String>>;
+
+/// Save the current contents for restoring, or nothing when `restore_previous`
+/// is off, in which case the clipboard is cleared to empty instead.
+fn capture_previous(engine: &dyn ClipboardEngine, restore_previous: bool) -> Result<Previous> {
+    if !restore_previous {
+        return Ok(None);
+    }
+    Ok(engine.get_contents()?.map(Zeroizing::new))
+}
+
 pub fn copy_with_ttl(
     engine: Arc<dyn ClipboardEngine>,
     secret: &SecretString,
     ttl: Duration,
 ) -> Result<()> {
-    let previous = engine.get_contents()?;
+    copy_with_ttl_then(engine, secret, ttl, true, None)
+}
+
+/// Like [`copy_with_ttl`], additionally running `on_clear` after the TTL restore.
+/// Without `restore_previous` the clipboard is cleared rather than restored.
+pub fn copy_with_ttl_then(
+    engine: Arc<dyn ClipboardEngine>,
+    secret: &SecretString,
+    ttl: Duration,
+    restore_previous: bool,
+    on_clear: Option<ClearCallback>,
+) -> Result<()> {
+    let previous = capture_previous(engine.as_ref(), restore_previous)?;
     engine.set_contents(secret.expose_secret())?;
 
     let engine_clone = engine.clone();
     thread::spawn(move || {
         thread::sleep(ttl);
-        let _ = match &previous {
-            Some(prev) => engine_clone.set_contents(prev),
-            None => engine_clone.set_contents(""),
-        };
+        restore(engine_clone.as_ref(), previous, on_clear);
+    });
+
+    Ok(())
+}
+
+/// Copy and hold the clipboard until `ttl` elapses or Ctrl-C arrives, restoring
+/// the previous contents either way. Returns true when interrupted.
+///
+/// The SIGINT handler is only installed here, so it never affects flows that
+/// do not copy.
+pub async fn copy_and_wait(
+    engine: Arc<dyn ClipboardEngine>,
+    secret: &SecretString,
+    ttl: Duration,
+    restore_previous: bool,
+    on_clear: Option<ClearCallback>,
+) -> Result<bool> {
+    let previous = capture_previous(engine.as_ref(), restore_previous)?;
+    engine.set_contents(secret.expose_secret())?;
+
+    let interrupted = tokio::select! {
+        _ = tokio::time::sleep(ttl) => false,
+        _ = tokio::signal::ctrl_c() => true,
+    };
+    restore(engine.as_ref(), previous, on_clear);
+    Ok(interrupted)
+}
+
+/// How often clear-on-paste re-reads the clipboard while waiting.
+pub const PASTE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How a clear-on-paste hold ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HoldOutcome {
+    /// The TTL elapsed with the secret still on the clipboard
+    Elapsed,
+    /// The clipboard stopped holding the secret (pasted and replaced, or overwritten)
+    Changed,
+    /// Ctrl-C arrived first
+    Interrupted,
+}
+
+/// Poll the clipboard every `interval` (at most) until it no longer holds `secret`
+/// or `ttl` elapses. Returns true when the contents changed first.
+///
+/// Clipboard APIs do not report pastes, so a change of contents is the only
+/// signal available; unreadable contents count as unchanged.
+pub fn wait_until_changed(
+    engine: &dyn ClipboardEngine,
+    secret: &str,
+    ttl: Duration,
+    interval: Duration,
+) -> bool {
+    let deadline = Instant::now() + ttl;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        thread::sleep(interval.min(deadline - now));
+        if changed_from(engine, secret) {
+            return true;
+        }
+    }
+}
+
+/// True when the clipboard is readable and holds something other than `secret`.
+/// The read contents may still be the secret, so they are wiped afterwards.
+fn changed_from(engine: &dyn ClipboardEngine, secret: &str) -> bool {
+    match engine.get_contents() {
+        Ok(Some(current)) => {
+            let current = Zeroizing::new(current);
+            !secrets_equal(&current, secret)
+        }
+        _ => false,
+    }
+}
+
+/// Like [`copy_with_ttl_then`], but the restore thread also ends the hold as soon
+/// as the clipboard changes away from the secret. New contents are left alone
+/// rather than overwritten with the previous ones; `on_clear` runs either way.
+pub fn copy_until_paste_then(
+    engine: Arc<dyn ClipboardEngine>,
+    secret: &SecretString,
+    ttl: Duration,
+    restore_previous: bool,
+    on_clear: Option<ClearCallback>,
+) -> Result<()> {
+    let previous = capture_previous(engine.as_ref(), restore_previous)?;
+    engine.set_contents(secret.expose_secret())?;
+
+    let engine_clone = engine.clone();
+    let secret = secret.clone();
+    thread::spawn(move || {
+        let changed = wait_until_changed(
+            engine_clone.as_ref(),
+            secret.expose_secret(),
+            ttl,
+            PASTE_POLL_INTERVAL,
+        );
+        finish_hold(engine_clone.as_ref(), previous, changed, on_clear);
     });
 
     Ok(())
 }
 
+/// [`copy_and_wait`] with clear-on-paste: holds until the clipboard changes away
+/// from the secret, `ttl` elapses, or Ctrl-C arrives.
+pub async fn copy_and_wait_for_paste(
+    engine: Arc<dyn ClipboardEngine>,
+    secret: &SecretString,
+    ttl: Duration,
+    restore_previous: bool,
+    on_clear: Option<ClearCallback>,
+) -> Result<HoldOutcome> {
+    let previous = capture_previous(engine.as_ref(), restore_previous)?;
+    engine.set_contents(secret.expose_secret())?;
+
+    let watch = async {
+        let deadline = Instant::now() + ttl;
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            tokio::time::sleep(PASTE_POLL_INTERVAL.min(deadline - now)).await;
+            if changed_from(engine.as_ref(), secret.expose_secret()) {
+                return true;
+            }
+        }
+    };
+    let outcome = tokio::select! {
+        changed = watch => if changed { HoldOutcome::Changed } else { HoldOutcome::Elapsed },
+        _ = tokio::signal::ctrl_c() => HoldOutcome::Interrupted,
+    };
+    finish_hold(
+        engine.as_ref(),
+        previous,
+        outcome == HoldOutcome::Changed,
+        on_clear,
+    );
+    Ok(outcome)
+}
+
+fn finish_hold(
+    engine: &dyn ClipboardEngine,
+    previous: Previous,
+    changed: bool,
+    on_clear: Option<ClearCallback>,
+) {
+    if changed {
+        if let Some(cb) = on_clear {
+            cb();
+        }
+    } else {
+        restore(engine, previous, on_clear);
+    }
+}
+
+fn restore(engine: &dyn ClipboardEngine, previous: Previous, on_clear: Option<ClearCallback>) {
+    let _ = match &previous {
+        Some(prev) => engine.set_contents(prev),
+        None => engine.set_contents(""),
+    };
+    // Wipe our copy now rather than whenever the caller's frame ends
+    drop(previous);
+    if let Some(cb) = on_clear {
+        cb();
+    }
+}
+
+/// Build the clear callback from `config.clipboard_clear_hook`, if one is configured.
+///
+/// The hook is an arbitrary shell command run with the user's privileges; it is
+/// never set by default.
+pub fn clear_hook(config: &Config) -> Option<ClearCallback> {
+    config.clipboard_clear_hook.clone().map(shell_hook)
+}
+
+/// Whether to restore the previous clipboard contents after a copy, rather than
+/// clear to empty (`clipboard_no_restore` / `--no-restore` turn this off).
+pub fn restore_previous(config: &Config) -> bool {
+    config.clipboard_no_restore != Some(true)
+}
+
+/// Wrap a shell command as a clear callback (`sh -c` on Unix, `cmd /C` on Windows).
+/// The command's output is discarded and failures are ignored.
+pub fn shell_hook(command: String) -> ClearCallback {
+    Box::new(move || {
+        #[cfg(windows)]
+        let mut cmd = Command::new("cmd");
+        #[cfg(windows)]
+        cmd.arg("/C");
+        #[cfg(not(windows))]
+        let mut cmd = Command::new("sh");
+        #[cfg(not(windows))]
+        cmd.arg("-c");
+        let _ = cmd
+            .arg(&command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status();
+    })
+}
+
 /// Resolve clipboard TTL seconds with precedence: override > KEVI_CLIP_TTL > config.clipboard_ttl > default (20)
 pub fn ttl_seconds(config: &Config, override_ttl: Option<u64>) -> u64 {
     override_ttl
         .or_else(|| {
             std::env::var("KEVI_CLIP_TTL")
                 .ok()
-                .and_then(|s| s.parse::<u64>().ok())
+                .and_then(|s| parse_duration_secs(&s).ok())
         })
         .or(config.clipboard_ttl)
         .unwrap_or(20)
@@ -92,3 +335,100 @@ pub fn environment_warning() -> Option<String> {
     }
     None
 }
+
+/// Clipboard managers known to keep a history: (process name, display name).
+const HISTORY_MANAGERS: &[(&str, &str)] = &[
+    ("copyq", "CopyQ"),
+    ("gpaste-daemon", "GPaste"),
+    ("klipper", "Klipper"),
+    ("xfce4-clipman", "Clipman"),
+    ("parcellite", "Parcellite"),
+    ("clipit", "ClipIt"),
+    ("diodon", "Diodon"),
+    ("greenclip", "greenclip"),
+    ("cliphist", "cliphist"),
+];
+
+/// Match process names (or command lines) against known history-keeping clipboard
+/// managers. On KDE, Klipper runs inside plasmashell, so the desktop name counts too.
+pub fn history_managers_in<I, S>(processes: I, desktop: Option<&str>) -> Vec<&'static str>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut found: Vec<&'static str> = Vec::new();
+    for proc_name in processes {
+        let proc_name = proc_name.as_ref().to_lowercase();
+        for (needle, name) in HISTORY_MANAGERS {
+            if proc_name.contains(needle) && !found.contains(name) {
+                found.push(name);
+            }
+        }
+    }
+    if desktop.is_some_and(|d| d.to_uppercase().contains("KDE")) && !found.contains(&"Klipper") {
+        found.push("Klipper");
+    }
+    found
+}
+
+/// Best-effort scan of running processes for clipboard managers with history.
+/// Linux only (reads `/proc`); elsewhere nothing is detected.
+pub fn detect_history_managers() -> Vec<&'static str> {
+    #[cfg(target_os = "linux")]
+    {
+        let procs = std::fs::read_dir("/proc")
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|e| {
+                e.file_name()
+                    .to_string_lossy()
+                    .bytes()
+                    .all(|b| b.is_ascii_digit())
+            })
+            .filter_map(|e| {
+                let comm = std::fs::read_to_string(e.path().join("comm")).ok()?;
+                let comm = comm.trim().to_string();
+                if comm != "wl-paste" {
+                    return Some(comm);
+                }
+                // Wayland history daemons run as `wl-paste --watch cliphist store`
+                let raw = std::fs::read(e.path().join("cmdline")).ok()?;
+                Some(String::from_utf8_lossy(&raw).replace('\0', " "))
+            });
+        let desktop = std::env::var("XDG_CURRENT_DESKTOP").ok();
+        history_managers_in(procs, desktop.as_deref())
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Advisory text for detected history managers, if any.
+pub fn history_warning(managers: &[&str]) -> Option<String> {
+    if managers.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "Clipboard manager with history detected ({}); copied secrets may stay in its \
+         history after the TTL clears the clipboard. Consider --no-copy --echo or disabling \
+         its history.",
+        managers.join(", ")
+    ))
+}
+
+/// `history_warning`, shown once per set of detected managers: the set is remembered
+/// in `marker` and the warning returns only when it changes.
+pub fn history_warning_once(marker: &Path) -> Option<String> {
+    let managers = detect_history_managers();
+    let seen = managers.join(",");
+    if std::fs::read_to_string(marker).is_ok_and(|prev| prev.trim() == seen) {
+        return None;
+    }
+    if let Some(parent) = marker.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    let _ = std::fs::write(marker, &seen);
+    history_warning(&managers)
+}
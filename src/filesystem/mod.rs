@@ -1,3 +1,5 @@
+#[cfg(feature = "autotype")]
+pub mod autotype;
 pub mod clipboard;
 pub mod secure;
 pub mod store;
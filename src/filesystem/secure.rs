@@ -18,9 +18,18 @@ pub fn ensure_parent_secure(path: &Path) -> Result<()> {
     Ok(())
 }
 
+/// `<path>.<suffix>` next to `path`. Unlike `with_extension`, the existing
+/// file name is kept whole, so `secrets` and `vault.prod.ron` both work.
+pub fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
 /// Atomically write `bytes` to `path` with secure permissions (0600 on Unix).
 pub fn atomic_write_secure(path: &Path, bytes: &[u8]) -> Result<()> {
-    let tmp_path: PathBuf = path.with_extension("tmp");
+    let tmp_path: PathBuf = sibling_path(path, "tmp");
     {
         let mut tmp = File::create(&tmp_path).context("Failed to create temporary vault file")?;
         tmp.write_all(bytes)?;
@@ -37,6 +46,30 @@ pub fn atomic_write_secure(path: &Path, bytes: &[u8]) -> Result<()> {
     Ok(())
 }
 
+/// Write `bytes` once to an existing named pipe, blocking until a reader opens it.
+#[cfg(target_family = "unix")]
+pub fn write_fifo_once(path: &Path, bytes: &[u8]) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    let meta = fs::metadata(path).with_context(|| format!("{} does not exist", path.display()))?;
+    if !meta.file_type().is_fifo() {
+        anyhow::bail!(
+            "{} is not a named pipe (create one with `mkfifo`)",
+            path.display()
+        );
+    }
+    let mut fifo = fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    fifo.write_all(bytes)?;
+    Ok(())
+}
+
+#[cfg(not(target_family = "unix"))]
+pub fn write_fifo_once(_path: &Path, _bytes: &[u8]) -> Result<()> {
+    anyhow::bail!("named pipes are only supported on Unix")
+}
+
 #[cfg(target_family = "unix")]
 fn set_perm_0600(path: &Path) {
     if let Ok(meta) = fs::metadata(path) {
@@ -46,9 +79,45 @@ fn set_perm_0600(path: &Path) {
     }
 }
 
-fn backup_path(path: &Path, n: usize) -> PathBuf {
-    // Append .n to the filename path
-    PathBuf::from(format!("{}.{n}", path.display()))
+/// How numbered backups are named next to the vault (config `backup_hidden`,
+/// `backup_suffix`). The default gives `vault.ron.1`, `vault.ron.2`, ...
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BackupNaming {
+    /// Prefix the file name with a dot (`.vault.ron.1`); Unix only
+    pub hidden: bool,
+    /// Inserted before the number (`bak` gives `vault.ron.bak.1`)
+    pub suffix: Option<String>,
+}
+
+impl BackupNaming {
+    /// Path of backup number `n` of `vault`.
+    pub fn path(&self, vault: &Path, n: usize) -> PathBuf {
+        let tail = match self
+            .suffix
+            .as_deref()
+            .map(|s| s.trim_matches('.'))
+            .filter(|s| !s.is_empty())
+        {
+            Some(suffix) => format!("{suffix}.{n}"),
+            None => n.to_string(),
+        };
+        let backup = sibling_path(vault, &tail);
+        #[cfg(target_family = "unix")]
+        if self.hidden {
+            if let Some(name) = backup.file_name() {
+                let name = name.to_string_lossy();
+                if !name.starts_with('.') {
+                    return backup.with_file_name(format!(".{name}"));
+                }
+            }
+        }
+        backup
+    }
+}
+
+/// Backup `n` of `path` under the default naming (`<file>.<n>`).
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    BackupNaming::default().path(path, n)
 }
 
 fn backup_count_from_env() -> usize {
@@ -61,17 +130,29 @@ fn backup_count_from_env() -> usize {
 /// Rotate backups and write atomically, keeping up to N backups.
 /// Backups are named `<file>.1`, `<file>.2`, ..., `<file>.N`.
 pub fn write_with_backups_n(path: &Path, bytes: &[u8], n: usize) -> Result<()> {
+    write_with_backups_named(path, bytes, n, &BackupNaming::default())
+}
+
+/// [`write_with_backups_n`] with backups named by `naming`.
+pub fn write_with_backups_named(
+    path: &Path,
+    bytes: &[u8],
+    n: usize,
+    naming: &BackupNaming,
+) -> Result<()> {
     ensure_parent_secure(path)?;
     if n > 0 {
+        log::debug!("rotating backups of {} (keeping {n})", path.display());
         // Remove the oldest if exists
-        let oldest = backup_path(path, n);
+        let oldest = naming.path(path, n);
         let _ = fs::remove_file(&oldest);
 
         // Shift backups: n-1 -> n, ..., 1 -> 2
         for i in (1..=n - 1).rev() {
-            let src = backup_path(path, i);
-            let dst = backup_path(path, i + 1);
+            let src = naming.path(path, i);
+            let dst = naming.path(path, i + 1);
             if src.exists() {
+                log::trace!("backup {} -> {}", src.display(), dst.display());
                 let _ = fs::rename(&src, &dst);
                 #[cfg(target_family = "unix")]
                 {
@@ -82,7 +163,8 @@ pub fn write_with_backups_n(path: &Path, bytes: &[u8], n: usize) -> Result<()> {
 
         // Move the current file to .1
         if path.exists() {
-            let first = backup_path(path, 1);
+            let first = naming.path(path, 1);
+            log::trace!("backup {} -> {}", path.display(), first.display());
             let _ = fs::rename(path, &first);
             #[cfg(target_family = "unix")]
             {
@@ -96,6 +178,104 @@ pub fn write_with_backups_n(path: &Path, bytes: &[u8], n: usize) -> Result<()> {
     Ok(())
 }
 
+/// A vault-related path whose Unix mode grants group or other access.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoosePermission {
+    pub path: PathBuf,
+    pub mode: u32,
+    /// The mode `fix_permissions` applies: 0600 for files, 0700 for the directory
+    pub wanted: u32,
+}
+
+/// The vault's numbered backups that exist, `.1` upwards, stopping at the first gap.
+pub fn existing_backups(path: &Path, naming: &BackupNaming) -> Vec<PathBuf> {
+    (1..)
+        .map(|n| naming.path(path, n))
+        .take_while(|p| p.exists())
+        .collect()
+}
+
+/// Overwrite a regular file with zeros, flush it to disk, then unlink it. Returns
+/// false if there was nothing to remove. Best-effort: on SSDs, copy-on-write and
+/// journaling filesystems the old blocks may survive elsewhere. A symlink is
+/// removed without touching its target.
+pub fn wipe_file(path: &Path) -> Result<bool> {
+    let meta = match fs::symlink_metadata(path) {
+        Ok(m) => m,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    if meta.is_file() {
+        let mut f = fs::OpenOptions::new().write(true).open(path)?;
+        let zeros = [0u8; 8192];
+        let mut left = meta.len();
+        while left > 0 {
+            let n = left.min(zeros.len() as u64) as usize;
+            f.write_all(&zeros[..n])?;
+            left -= n as u64;
+        }
+        f.sync_all()?;
+    }
+    fs::remove_file(path)?;
+    Ok(true)
+}
+
+/// Files that hold vault data or key material, plus the vault's directory:
+/// the vault, its numbered backups and the session/age sidecars that exist.
+#[cfg(target_family = "unix")]
+fn private_paths(path: &Path, naming: &BackupNaming) -> Vec<(PathBuf, u32)> {
+    let mut paths = vec![(path.to_path_buf(), 0o600)];
+    paths.extend(
+        existing_backups(path, naming)
+            .into_iter()
+            .map(|p| (p, 0o600)),
+    );
+    for suffix in ["dksession", "agekey"] {
+        paths.push((sibling_path(path, suffix), 0o600));
+    }
+    // A bare file name lives in the working directory, which is left alone
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        paths.push((parent.to_path_buf(), 0o700));
+    }
+    paths
+}
+
+/// Vault files and the vault directory that group/other can access (Unix only;
+/// always empty elsewhere). Missing files are skipped.
+pub fn audit_permissions(path: &Path, naming: &BackupNaming) -> Vec<LoosePermission> {
+    #[cfg(target_family = "unix")]
+    {
+        private_paths(path, naming)
+            .into_iter()
+            .filter_map(|(p, wanted)| {
+                let mode = fs::metadata(&p).ok()?.permissions().mode() & 0o777;
+                (mode & 0o077 != 0).then_some(LoosePermission {
+                    path: p,
+                    mode,
+                    wanted,
+                })
+            })
+            .collect()
+    }
+    #[cfg(not(target_family = "unix"))]
+    {
+        let _ = (path, naming);
+        Vec::new()
+    }
+}
+
+/// Re-apply 0600 to vault files and 0700 to the vault directory where they are
+/// looser, returning what was changed.
+pub fn fix_permissions(path: &Path, naming: &BackupNaming) -> Result<Vec<LoosePermission>> {
+    let loose = audit_permissions(path, naming);
+    #[cfg(target_family = "unix")]
+    for item in &loose {
+        fs::set_permissions(&item.path, fs::Permissions::from_mode(item.wanted))
+            .with_context(|| format!("Failed to restrict {}", item.path.display()))?;
+    }
+    Ok(loose)
+}
+
 /// Deprecated: env-coupled variant kept for compatibility. Prefer `write_with_backups_n`.
 pub fn write_with_backups(path: &Path, bytes: &[u8]) -> Result<()> {
     let n = backup_count_from_env();
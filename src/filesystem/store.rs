@@ -1,24 +1,43 @@
-use crate::filesystem::secure::write_with_backups_n;
+use crate::cli::output::status;
+use crate::filesystem::secure::{audit_permissions, write_with_backups_named, BackupNaming};
 use crate::vault::ports::ByteStore;
 use anyhow::{Context, Result};
 use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, Once};
+
+static PERMISSION_WARNING: Once = Once::new();
 
 pub struct FileByteStore {
     path: PathBuf,
     backups: usize,
+    naming: BackupNaming,
 }
 
 impl FileByteStore {
     /// Construct with backups count resolved from environment (KEVI_BACKUPS) or default 2.
     pub fn new(path: PathBuf) -> Self {
-        Self { path, backups: 2 }
+        Self {
+            path,
+            backups: 2,
+            naming: BackupNaming::default(),
+        }
     }
 
     /// Preferred: construct with explicit backups count to avoid env coupling.
     pub fn new_with_backups(path: PathBuf, backups: usize) -> Self {
-        Self { path, backups }
+        Self {
+            path,
+            backups,
+            naming: BackupNaming::default(),
+        }
+    }
+
+    /// Name backups with `naming` instead of the default `<file>.<n>`.
+    pub fn with_backup_naming(mut self, naming: BackupNaming) -> Self {
+        self.naming = naming;
+        self
     }
 }
 
@@ -28,6 +47,27 @@ impl ByteStore for FileByteStore {
         if !Path::new(path).exists() {
             return Ok(Vec::new());
         }
+        // Copies made by hand can lose the 0600 that writes apply; say so once per run
+        PERMISSION_WARNING.call_once(|| {
+            let loose = audit_permissions(path, &self.naming);
+            if let Some(first) = loose.first() {
+                let more = match loose.len() {
+                    1 => String::new(),
+                    n => format!(" (+{} more)", n - 1),
+                };
+                eprintln!(
+                    "{}",
+                    status(
+                        "⚠️",
+                        format_args!(
+                            "{} is accessible by other users (mode {:o}){more}; run `kevi fix-perms`",
+                            first.path.display(),
+                            first.mode
+                        )
+                    )
+                );
+            }
+        });
         let mut f = File::open(path).context("Failed to open vault file")?;
         let mut buf = Vec::new();
         f.read_to_end(&mut buf)?;
@@ -35,6 +75,55 @@ impl ByteStore for FileByteStore {
     }
 
     fn write(&self, bytes: &[u8]) -> Result<()> {
-        write_with_backups_n(&self.path, bytes, self.backups)
+        write_with_backups_named(&self.path, bytes, self.backups, &self.naming)
+    }
+
+    fn write_in_place(&self, bytes: &[u8]) -> Result<()> {
+        write_with_backups_named(&self.path, bytes, 0, &self.naming)
+    }
+}
+
+/// A vault "file" held in memory: nothing is read from or written to disk. For
+/// ephemeral vaults (`kevi tui --ephemeral`) and as a test fixture.
+#[derive(Default)]
+pub struct InMemoryByteStore {
+    bytes: Mutex<Vec<u8>>,
+}
+
+impl InMemoryByteStore {
+    /// An empty store, which reads as a vault that does not exist yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A store preloaded with `bytes`, e.g. an encrypted vault read elsewhere.
+    pub fn with_bytes(bytes: Vec<u8>) -> Self {
+        Self {
+            bytes: Mutex::new(bytes),
+        }
+    }
+
+    /// A copy of the current contents (the last write).
+    pub fn snapshot(&self) -> Vec<u8> {
+        self.bytes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+}
+
+impl ByteStore for InMemoryByteStore {
+    fn read(&self) -> Result<Vec<u8>> {
+        Ok(self.snapshot())
+    }
+
+    fn write(&self, bytes: &[u8]) -> Result<()> {
+        let mut stored = self
+            .bytes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        stored.clear();
+        stored.extend_from_slice(bytes);
+        Ok(())
     }
 }
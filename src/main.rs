@@ -1,9 +1,11 @@
+use kevi::cli::exit::exit_code;
+use kevi::cli::output::status;
 use kevi::cli::runner;
 
 #[tokio::main]
 async fn main() {
     if let Err(e) = runner::run().await {
-        eprintln!("❌ Error: {e}");
-        std::process::exit(1);
+        eprintln!("{}", status("❌", format_args!("Error: {e}")));
+        std::process::exit(exit_code(&e));
     }
 }
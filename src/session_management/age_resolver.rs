@@ -0,0 +1,128 @@
+use crate::cryptography::primitives::{
+    header_fingerprint_excluding_nonce, KeviHeader, AEAD_AES256GCM, HEADER_VERSION, KDF_ARGON2ID,
+    KEY_LEN, NONCE_LEN,
+};
+use crate::filesystem::secure::{atomic_write_secure, ensure_parent_secure, sibling_path};
+use crate::vault::ports::{DerivedKey, HeaderParams, KeyResolver};
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose, Engine as _};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use zeroize::Zeroize;
+
+/// Sidecar stored next to the vault: the data key wrapped to an age recipient,
+/// bound to the vault header (params + salt) via its fingerprint.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WrappedKeyStored {
+    pub header_fingerprint_hex: String,
+    pub wrapped_b64: String,
+}
+
+pub fn age_sidecar_file_for(vault_path: &Path) -> PathBuf {
+    let sidecar = sibling_path(vault_path, "agekey");
+    // Sidecars used to replace the vault's extension; keep finding those.
+    let legacy = vault_path.with_extension("agekey");
+    if !sidecar.exists() && legacy.exists() {
+        return legacy;
+    }
+    sidecar
+}
+
+/// Resolves the vault key by unwrapping a random data key with an age identity
+/// instead of deriving it from a passphrase. Argon2 params in the header are unused.
+pub struct AgeKeyResolver {
+    sidecar_path: PathBuf,
+    identity_path: PathBuf,
+    recipient: Option<String>,
+}
+
+impl AgeKeyResolver {
+    /// `recipient` defaults to the public key of the identity when not given.
+    pub fn new(vault_path: PathBuf, identity_path: PathBuf, recipient: Option<String>) -> Self {
+        Self {
+            sidecar_path: age_sidecar_file_for(&vault_path),
+            identity_path,
+            recipient,
+        }
+    }
+
+    fn load_identity(&self) -> Result<age::x25519::Identity> {
+        let content = fs::read_to_string(&self.identity_path).with_context(|| {
+            format!(
+                "Failed to read age identity {}",
+                self.identity_path.display()
+            )
+        })?;
+        let line = content
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with('#'))
+            .ok_or_else(|| anyhow!("age identity file is empty"))?;
+        age::x25519::Identity::from_str(line).map_err(|e| anyhow!("invalid age identity: {e}"))
+    }
+
+    fn resolve_recipient(&self) -> Result<age::x25519::Recipient> {
+        match &self.recipient {
+            Some(r) => age::x25519::Recipient::from_str(r)
+                .map_err(|e| anyhow!("invalid age recipient: {e}")),
+            None => Ok(self.load_identity()?.to_public()),
+        }
+    }
+}
+
+impl KeyResolver for AgeKeyResolver {
+    fn resolve_for_header(&self, hdr: &KeviHeader) -> Result<DerivedKey> {
+        let bytes = fs::read(&self.sidecar_path).with_context(|| {
+            format!("Failed to read wrapped key {}", self.sidecar_path.display())
+        })?;
+        let stored: WrappedKeyStored = ron::from_str(&String::from_utf8_lossy(&bytes))
+            .context("Failed to parse wrapped key sidecar")?;
+        if stored.header_fingerprint_hex != header_fingerprint_excluding_nonce(hdr) {
+            anyhow::bail!("wrapped key does not match this vault header");
+        }
+        let wrapped = general_purpose::STANDARD
+            .decode(&stored.wrapped_b64)
+            .map_err(|_| anyhow!("wrapped key is not valid base64"))?;
+        let identity = self.load_identity()?;
+        let mut key = age::decrypt(&identity, &wrapped)
+            .map_err(|e| anyhow!("failed to unwrap vault key with age identity: {e}"))?;
+        if key.len() != KEY_LEN {
+            key.zeroize();
+            anyhow::bail!("unwrapped key has unexpected length");
+        }
+        Ok(DerivedKey::new(key))
+    }
+
+    fn resolve_for_new_vault(&self, params: HeaderParams, salt: [u8; 16]) -> Result<DerivedKey> {
+        let recipient = self.resolve_recipient()?;
+        let mut key = vec![0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut key)
+            .map_err(|_| anyhow!("failed to generate data key"))?;
+        let wrapped = age::encrypt(&recipient, &key)
+            .map_err(|e| anyhow!("failed to wrap vault key to age recipient: {e}"))?;
+
+        let hdr = KeviHeader {
+            version: HEADER_VERSION,
+            kdf_id: KDF_ARGON2ID,
+            aead_id: AEAD_AES256GCM,
+            m_cost_kib: params.m_cost_kib,
+            t_cost: params.t_cost,
+            p_lanes: params.p_lanes,
+            salt,
+            nonce: [0u8; NONCE_LEN],
+        };
+        let stored = WrappedKeyStored {
+            header_fingerprint_hex: header_fingerprint_excluding_nonce(&hdr),
+            wrapped_b64: general_purpose::STANDARD.encode(wrapped),
+        };
+        let ron = ron::to_string(&stored).context("failed to serialize wrapped key")?;
+        ensure_parent_secure(&self.sidecar_path)?;
+        atomic_write_secure(&self.sidecar_path, ron.as_bytes())?;
+
+        Ok(DerivedKey::new(key))
+    }
+}
@@ -0,0 +1,34 @@
+use crate::filesystem::secure::{atomic_write_secure, ensure_parent_secure, sibling_path};
+use crate::vault::handlers::GetField;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Parameters of the most recent clipboard `get`, replayed by `kevi repeat`.
+/// Holds only the label, field and TTL — never the secret itself.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastGet {
+    pub label: String,
+    pub field: GetField,
+    pub ttl: Option<u64>,
+}
+
+pub fn last_get_file_for(vault_path: &Path) -> PathBuf {
+    sibling_path(vault_path, "lastget")
+}
+
+pub fn save_last_get(path: &Path, last: &LastGet) -> Result<()> {
+    let ron = ron::to_string(last).context("failed to serialize last get")?;
+    ensure_parent_secure(path)?;
+    atomic_write_secure(path, ron.as_bytes())
+}
+
+/// Load the recorded `get`; a missing or unreadable state file yields `None`.
+pub fn load_last_get(path: &Path) -> Result<Option<LastGet>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path).context("Failed to read last get state")?;
+    Ok(ron::from_str(&content).ok())
+}
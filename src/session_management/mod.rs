@@ -1,2 +1,5 @@
+#[cfg(feature = "age")]
+pub mod age_resolver;
+pub mod last_get;
 pub mod resolver;
 pub mod session;
@@ -1,24 +1,128 @@
+use crate::cli::prompt::{block_with_cmd_timeout, require_password_prompt};
+use crate::config::duration::parse_duration_secs;
 use crate::cryptography::primitives::{
     derive_key_argon2id, header_fingerprint_excluding_nonce, KeviHeader, KEY_LEN,
 };
-use crate::session_management::session::{load, save};
-use crate::vault::ports::{DerivedKey, HeaderParams, KeyResolver};
+use crate::filesystem::secure::sibling_path;
+use crate::session_management::session::{clear, load, load_with_expiry, save};
+use crate::vault::ports::{DerivedKey, HeaderParams, KeyResolver, Progress};
+use crate::vault::service::now_secs;
 use anyhow::Result;
 use base64::{engine::general_purpose, Engine as _};
+use ring::rand::{SecureRandom, SystemRandom};
 use secrecy::{ExposeSecret, SecretBox};
 use serde::{Deserialize, Serialize};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
+use zeroize::{Zeroize, Zeroizing};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DerivedKeyStored {
     pub header_fingerprint_hex: String,
     pub key_b64: String,
+    /// When the key was first cached and the TTL it was cached for; absent in
+    /// sessions written by older versions. Used by sliding renewal.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unlocked_at_unix: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ttl_secs: Option<u64>,
 }
 
-pub fn dk_session_file_for(vault_path: &std::path::Path) -> PathBuf {
-    vault_path.with_extension("dksession")
+/// Sliding expiry for the session cache: every cache hit pushes the session's
+/// expiry out by its TTL again, but never past `max_secs` after the unlock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SessionSliding {
+    pub max_secs: Option<u64>,
+}
+
+impl Drop for DerivedKeyStored {
+    fn drop(&mut self) {
+        self.key_b64.zeroize();
+    }
+}
+
+/// Pick the key resolver for a vault: age-wrapped keys when the `age` feature is
+/// enabled and `KEVI_AGE_IDENTITY` is set, otherwise the passphrase/session resolver
+/// (renewing its session on use when `sliding` is set, and reporting key derivation
+/// through `progress`).
+pub fn default_key_resolver(
+    vault_path: PathBuf,
+    sliding: Option<SessionSliding>,
+    progress: Option<Progress>,
+) -> Arc<dyn KeyResolver> {
+    #[cfg(feature = "age")]
+    if let (true, Ok(identity)) = (age_key_in_use(), env::var("KEVI_AGE_IDENTITY")) {
+        log::debug!("key resolver: age identity {identity}");
+        return Arc::new(
+            crate::session_management::age_resolver::AgeKeyResolver::new(
+                vault_path,
+                PathBuf::from(identity),
+                env::var("KEVI_AGE_RECIPIENT").ok(),
+            ),
+        );
+    }
+    log::debug!("key resolver: passphrase with session cache");
+    Arc::new(
+        CachedKeyResolver::new(vault_path)
+            .with_sliding(sliding)
+            .with_progress(progress),
+    )
+}
+
+/// Whether [`default_key_resolver`] takes the vault key from an age identity
+/// rather than a passphrase.
+pub fn age_key_in_use() -> bool {
+    cfg!(feature = "age") && env::var_os("KEVI_AGE_IDENTITY").is_some()
+}
+
+/// Resolve unlock (dk-session) TTL seconds with precedence: override > KEVI_UNLOCK_TTL > default (900)
+pub fn unlock_ttl_seconds(override_ttl: Option<u64>) -> u64 {
+    override_ttl
+        .or_else(|| {
+            env::var("KEVI_UNLOCK_TTL")
+                .ok()
+                .and_then(|s| parse_duration_secs(&s).ok())
+        })
+        .unwrap_or(900)
+}
+
+/// Session file for a vault: `<vault>.dksession` next to the vault's canonical path,
+/// so `./vault.ron` and `/abs/vault.ron` share one session while a backup such as
+/// `vault.ron.1` always gets its own (`vault.ron.1.dksession`).
+pub fn dk_session_file_for(vault_path: &Path) -> PathBuf {
+    sibling_path(&canonical_vault_path(vault_path), "dksession")
+}
+
+/// Every session file a vault may have: the current one, then `vault.dksession`
+/// for `vault.ron` as written by versions that replaced the extension. The legacy
+/// file is never read, only cleared by `lock`.
+pub fn dk_session_files_for(vault_path: &Path) -> Vec<PathBuf> {
+    let current = dk_session_file_for(vault_path);
+    let legacy = canonical_vault_path(vault_path).with_extension("dksession");
+    if legacy == current {
+        vec![current]
+    } else {
+        vec![current, legacy]
+    }
+}
+
+/// Resolve the vault's directory (symlinks, `..`, relative paths) while keeping the
+/// file name as given; the vault itself may not exist yet.
+fn canonical_vault_path(vault_path: &Path) -> PathBuf {
+    let (Some(parent), Some(name)) = (vault_path.parent(), vault_path.file_name()) else {
+        return vault_path.to_path_buf();
+    };
+    let parent = if parent.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        parent
+    };
+    match std::fs::canonicalize(parent) {
+        Ok(dir) => dir.join(name),
+        Err(_) => vault_path.to_path_buf(),
+    }
 }
 
 pub fn save_derived_key_session(
@@ -30,68 +134,234 @@ pub fn save_derived_key_session(
     let stored = DerivedKeyStored {
         header_fingerprint_hex: fingerprint.to_string(),
         key_b64: general_purpose::STANDARD.encode(key.expose_secret()),
+        unlocked_at_unix: Some(now_secs()),
+        ttl_secs: Some(ttl.as_secs()),
     };
     save(path, &stored, ttl)
 }
 
+/// The unexpired session for the vault described by `hdr`, with its remaining
+/// lifetime. A session for another header counts as none.
+pub fn read_dk_session(
+    dk_session_path: &Path,
+    hdr: &KeviHeader,
+) -> Result<Option<(DerivedKeyStored, Duration)>> {
+    let Some((sess, expires_at)) = load_with_expiry::<DerivedKeyStored>(dk_session_path)? else {
+        return Ok(None);
+    };
+    if sess.header_fingerprint_hex != header_fingerprint_excluding_nonce(hdr) {
+        return Ok(None);
+    }
+    let remaining = Duration::from_secs(expires_at.saturating_sub(now_secs()));
+    Ok(Some((sess, remaining)))
+}
+
+/// Restart `sess` with `ttl` from now, reusing its cached key (no KDF). The unlock
+/// time is kept, so the new expiry is capped at `max_secs` after it like a sliding
+/// renewal. Returns the TTL written; zero means the cap is reached and nothing was saved.
+pub fn extend_dk_session(
+    dk_session_path: &Path,
+    sess: &DerivedKeyStored,
+    ttl: Duration,
+    max_secs: Option<u64>,
+) -> Result<Duration> {
+    let now = now_secs();
+    let unlocked_at = sess.unlocked_at_unix.unwrap_or(now);
+    let ttl_secs = capped_ttl(ttl.as_secs(), unlocked_at, max_secs, now);
+    if ttl_secs == 0 {
+        return Ok(Duration::ZERO);
+    }
+    let extended = DerivedKeyStored {
+        header_fingerprint_hex: sess.header_fingerprint_hex.clone(),
+        key_b64: sess.key_b64.clone(),
+        unlocked_at_unix: Some(unlocked_at),
+        ttl_secs: Some(ttl.as_secs()),
+    };
+    save(dk_session_path, &extended, Duration::from_secs(ttl_secs))?;
+    Ok(Duration::from_secs(ttl_secs))
+}
+
+/// `ttl_secs`, shortened so the session ends no later than `max_secs` after `unlocked_at`.
+fn capped_ttl(ttl_secs: u64, unlocked_at: u64, max_secs: Option<u64>, now: u64) -> u64 {
+    match max_secs {
+        Some(max) => ttl_secs.min(unlocked_at.saturating_add(max).saturating_sub(now)),
+        None => ttl_secs,
+    }
+}
+
 pub trait PasswordResolver {
-    fn resolve_password(&self) -> String {
-        let pw = if let Ok(pw) = env::var("KEVI_PASSWORD") {
-            pw
-        } else {
-            inquire::Password::new("Master password")
-                .without_confirmation()
-                .prompt()
-                .unwrap()
-        };
-        pw
+    /// Whether `KEVI_PASSWORD` may stand in for the prompt.
+    fn env_password_allowed(&self) -> bool {
+        true
+    }
+
+    /// The passphrase is wiped from memory when the returned value is dropped.
+    fn resolve_password(&self) -> Result<Zeroizing<String>> {
+        if self.env_password_allowed() {
+            if let Ok(pw) = env::var("KEVI_PASSWORD") {
+                return Ok(Zeroizing::new(pw));
+            }
+        }
+        require_password_prompt()?;
+        Ok(Zeroizing::new(block_with_cmd_timeout(
+            "password prompt",
+            || {
+                inquire::Password::new("Master password")
+                    .without_confirmation()
+                    .prompt()
+            },
+        )?))
     }
 }
 
+/// Derive the vault key from `pw` into a locked buffer, wiping the intermediate copy.
+/// The derivation is reported through `progress` when one is set.
+fn derive_locked(
+    pw: &str,
+    salt: &[u8],
+    (m_cost_kib, t_cost, p): (u32, u32, u32),
+    progress: Option<Progress>,
+) -> Result<DerivedKey> {
+    log::debug!("deriving key with Argon2id (m={m_cost_kib} KiB, t={t_cost}, p={p})");
+    let mut derived = None;
+    let mut step = || derived = Some(derive_key_argon2id(pw, salt, m_cost_kib, t_cost, p));
+    match progress {
+        Some(progress) => progress("Deriving key…", &mut step),
+        None => step(),
+    }
+    let mut key_arr = derived.ok_or_else(|| anyhow::anyhow!("key derivation did not run"))??;
+    let dk = DerivedKey::new(key_arr.to_vec());
+    key_arr.zeroize();
+    Ok(dk)
+}
+
 pub struct CachedKeyResolver {
     dk_session_path: PathBuf,
+    sliding: Option<SessionSliding>,
+    progress: Option<Progress>,
 }
 
 impl PasswordResolver for CachedKeyResolver {}
 
 impl CachedKeyResolver {
     pub fn new(vault_path: PathBuf) -> Self {
-        let dk = vault_path.with_extension("dksession");
+        let dk = dk_session_file_for(&vault_path);
         Self {
             dk_session_path: dk,
+            sliding: None,
+            progress: None,
         }
     }
+
+    /// Renew the session on every cache hit (see [`SessionSliding`]).
+    pub fn with_sliding(mut self, sliding: Option<SessionSliding>) -> Self {
+        self.sliding = sliding;
+        self
+    }
+
+    /// Report key derivation through `progress`.
+    pub fn with_progress(mut self, progress: Option<Progress>) -> Self {
+        self.progress = progress;
+        self
+    }
+}
+
+/// The key cached in the session file at `dk_session_path`, if it is unexpired and
+/// belongs to the vault described by `hdr`. A cached key that is not valid base64
+/// or is shorter than `KEY_LEN` is treated as a miss and its session file removed,
+/// so the next unlock writes a fresh one. A hit renews the session when `sliding` is set.
+fn cached_key(
+    dk_session_path: &Path,
+    hdr: &KeviHeader,
+    sliding: Option<SessionSliding>,
+) -> Result<Option<DerivedKey>> {
+    let fp = header_fingerprint_excluding_nonce(hdr);
+    let Some(sess) = load::<DerivedKeyStored>(dk_session_path)? else {
+        log::debug!(
+            "session cache miss: no valid session at {}",
+            dk_session_path.display()
+        );
+        return Ok(None);
+    };
+    if sess.header_fingerprint_hex != fp {
+        log::debug!("session cache miss: session belongs to a different vault header");
+        return Ok(None);
+    }
+    match general_purpose::STANDARD.decode(&sess.key_b64) {
+        Ok(vec) if vec.len() >= KEY_LEN => {
+            log::debug!("session cache hit: header fingerprint matches");
+            if let Some(sliding) = sliding {
+                renew_session(dk_session_path, &sess, sliding);
+            }
+            let mut vec = Zeroizing::new(vec);
+            vec.truncate(KEY_LEN);
+            Ok(Some(DerivedKey::new(std::mem::take(&mut *vec))))
+        }
+        Ok(mut vec) => {
+            log::debug!("session cache miss: cached key is too short; removing session");
+            vec.zeroize();
+            clear(dk_session_path)?;
+            Ok(None)
+        }
+        Err(_) => {
+            log::debug!("session cache miss: cached key is not valid base64; removing session");
+            clear(dk_session_path)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Rewrite a session with a fresh expiry, reusing its cached key (no KDF). The new
+/// expiry is the session's TTL from now, capped at `max_secs` after the unlock;
+/// once the cap is reached the session is left to run out. Failures only log.
+fn renew_session(dk_session_path: &Path, sess: &DerivedKeyStored, sliding: SessionSliding) {
+    let now = now_secs();
+    // Sessions from older versions start their lifetime cap at the first renewal
+    let unlocked_at = sess.unlocked_at_unix.unwrap_or(now);
+    let ttl_secs = sess.ttl_secs.unwrap_or_else(|| unlock_ttl_seconds(None));
+    let ttl_secs = capped_ttl(ttl_secs, unlocked_at, sliding.max_secs, now);
+    if ttl_secs == 0 {
+        log::debug!("session reached its maximum lifetime; not renewing");
+        return;
+    }
+    let renewed = DerivedKeyStored {
+        header_fingerprint_hex: sess.header_fingerprint_hex.clone(),
+        key_b64: sess.key_b64.clone(),
+        unlocked_at_unix: Some(unlocked_at),
+        ttl_secs: sess.ttl_secs,
+    };
+    match save(dk_session_path, &renewed, Duration::from_secs(ttl_secs)) {
+        Ok(()) => log::debug!("session renewed for {ttl_secs}s"),
+        Err(e) => log::debug!("session renewal failed: {e}"),
+    }
 }
 
 impl KeyResolver for CachedKeyResolver {
     fn resolve_for_header(&self, hdr: &KeviHeader) -> Result<DerivedKey> {
-        let fp = header_fingerprint_excluding_nonce(hdr);
-        if let Some(sess) = load::<DerivedKeyStored>(&self.dk_session_path)? {
-            if sess.header_fingerprint_hex == fp {
-                if let Ok(vec) = general_purpose::STANDARD.decode(&sess.key_b64) {
-                    let mut arr = [0u8; KEY_LEN];
-                    if vec.len() >= KEY_LEN {
-                        arr.copy_from_slice(&vec[..KEY_LEN]);
-                        return Ok(DerivedKey {
-                            key: SecretBox::new(Box::new(arr.to_vec())),
-                        });
-                    }
-                }
-            }
+        if let Some(dk) = cached_key(&self.dk_session_path, hdr, self.sliding)? {
+            return Ok(dk);
         }
+        let fp = header_fingerprint_excluding_nonce(hdr);
         // Cache miss: derive from passphrase
-        let pw = self.resolve_password();
-        let key_arr = derive_key_argon2id(&pw, &hdr.salt, hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes)?;
-        let key_vec = SecretBox::new(Box::new(key_arr.to_vec()));
-        // Default TTL: 900s unless KEVI_UNLOCK_TTL provided
-        let ttl_secs = env::var("KEVI_UNLOCK_TTL")
-            .ok()
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(900);
+        let pw = self.resolve_password()?;
+        let dk = derive_locked(
+            &pw,
+            &hdr.salt,
+            (hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes),
+            self.progress,
+        )?;
+        drop(pw);
+        let ttl_secs = unlock_ttl_seconds(None);
+        log::debug!(
+            "caching derived key in {} for {ttl_secs}s",
+            self.dk_session_path.display()
+        );
 
         let stored = DerivedKeyStored {
             header_fingerprint_hex: fp,
-            key_b64: general_purpose::STANDARD.encode(key_vec.expose_secret()),
+            key_b64: general_purpose::STANDARD.encode(dk.key.expose_secret()),
+            unlocked_at_unix: Some(now_secs()),
+            ttl_secs: Some(ttl_secs),
         };
         save(
             &self.dk_session_path,
@@ -99,14 +369,18 @@ impl KeyResolver for CachedKeyResolver {
             Duration::from_secs(ttl_secs),
         )?;
 
-        Ok(DerivedKey { key: key_vec })
+        Ok(dk)
     }
 
     fn resolve_for_new_vault(&self, params: HeaderParams, salt: [u8; 16]) -> Result<DerivedKey> {
-        let pw = self.resolve_password();
-        let key_arr =
-            derive_key_argon2id(&pw, &salt, params.m_cost_kib, params.t_cost, params.p_lanes)?;
-        let key_vec = SecretBox::new(Box::new(key_arr.to_vec()));
+        let pw = self.resolve_password()?;
+        let dk = derive_locked(
+            &pw,
+            &salt,
+            (params.m_cost_kib, params.t_cost, params.p_lanes),
+            self.progress,
+        )?;
+        drop(pw);
 
         // Also cache it
         let hdr = KeviHeader {
@@ -120,14 +394,17 @@ impl KeyResolver for CachedKeyResolver {
             nonce: [0u8; crate::cryptography::primitives::NONCE_LEN],
         };
         let fp = header_fingerprint_excluding_nonce(&hdr);
-        let ttl_secs = env::var("KEVI_UNLOCK_TTL")
-            .ok()
-            .and_then(|s| s.parse::<u64>().ok())
-            .unwrap_or(900);
+        let ttl_secs = unlock_ttl_seconds(None);
+        log::debug!(
+            "caching new vault key in {} for {ttl_secs}s",
+            self.dk_session_path.display()
+        );
 
         let stored = DerivedKeyStored {
             header_fingerprint_hex: fp,
-            key_b64: general_purpose::STANDARD.encode(key_vec.expose_secret()),
+            key_b64: general_purpose::STANDARD.encode(dk.key.expose_secret()),
+            unlocked_at_unix: Some(now_secs()),
+            ttl_secs: Some(ttl_secs),
         };
         save(
             &self.dk_session_path,
@@ -135,47 +412,118 @@ impl KeyResolver for CachedKeyResolver {
             Duration::from_secs(ttl_secs),
         )?;
 
-        Ok(DerivedKey { key: key_vec })
+        Ok(dk)
     }
 }
 
-pub struct BypassKeyResolver;
+/// Resolves only from an unlocked session: never prompts, never reads
+/// `KEVI_PASSWORD` and never creates a vault. Used by shell completion.
+pub struct SessionOnlyKeyResolver {
+    dk_session_path: PathBuf,
+}
+
+impl SessionOnlyKeyResolver {
+    pub fn new(vault_path: PathBuf) -> Self {
+        Self {
+            dk_session_path: dk_session_file_for(&vault_path),
+        }
+    }
+}
+
+impl KeyResolver for SessionOnlyKeyResolver {
+    fn resolve_for_header(&self, hdr: &KeviHeader) -> Result<DerivedKey> {
+        cached_key(&self.dk_session_path, hdr, None)?
+            .ok_or_else(|| anyhow::anyhow!("vault is locked; run `kevi unlock`"))
+    }
+
+    fn resolve_for_new_vault(&self, _params: HeaderParams, _salt: [u8; 16]) -> Result<DerivedKey> {
+        anyhow::bail!("vault is locked; run `kevi unlock`")
+    }
+}
 
-impl PasswordResolver for BypassKeyResolver {}
+pub struct BypassKeyResolver {
+    progress: Option<Progress>,
+    env_password: bool,
+}
 
 impl Default for BypassKeyResolver {
     fn default() -> Self {
-        Self::new()
+        Self {
+            progress: None,
+            env_password: true,
+        }
+    }
+}
+
+impl PasswordResolver for BypassKeyResolver {
+    fn env_password_allowed(&self) -> bool {
+        self.env_password
     }
 }
 
 impl BypassKeyResolver {
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Whether `KEVI_PASSWORD` is used instead of prompting (default: true).
+    pub fn with_env_password(mut self, allowed: bool) -> Self {
+        self.env_password = allowed;
+        self
+    }
+
+    /// Report key derivation through `progress`.
+    pub fn with_progress(mut self, progress: Option<Progress>) -> Self {
+        self.progress = progress;
+        self
     }
 }
 
 impl KeyResolver for BypassKeyResolver {
     fn resolve_for_header(&self, hdr: &KeviHeader) -> Result<DerivedKey> {
-        let pw = self.resolve_password();
-        let key_arr = derive_key_argon2id(&pw, &hdr.salt, hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes)?;
-        Ok(DerivedKey {
-            key: SecretBox::new(Box::new(key_arr.to_vec())),
-        })
+        log::debug!("session cache bypassed: deriving from the passphrase only");
+        let pw = self.resolve_password()?;
+        derive_locked(
+            &pw,
+            &hdr.salt,
+            (hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes),
+            self.progress,
+        )
     }
 
     fn resolve_for_new_vault(&self, params: HeaderParams, salt: [u8; 16]) -> Result<DerivedKey> {
-        let pw = if let Ok(pw) = env::var("KEVI_PASSWORD") {
-            pw
-        } else {
-            inquire::Password::new("Master password")
-                .without_confirmation()
-                .prompt()?
-        };
-        let key_arr =
-            derive_key_argon2id(&pw, &salt, params.m_cost_kib, params.t_cost, params.p_lanes)?;
-        Ok(DerivedKey {
-            key: SecretBox::new(Box::new(key_arr.to_vec())),
-        })
+        let pw = self.resolve_password()?;
+        derive_locked(
+            &pw,
+            &salt,
+            (params.m_cost_kib, params.t_cost, params.p_lanes),
+            self.progress,
+        )
+    }
+}
+
+/// Holds a random data key in memory for the life of the process; no password,
+/// no session file. Pairs with `InMemoryByteStore` for vaults that never touch disk.
+pub struct EphemeralKeyResolver {
+    key: Zeroizing<Vec<u8>>,
+}
+
+impl EphemeralKeyResolver {
+    pub fn new() -> Result<Self> {
+        let mut key = Zeroizing::new(vec![0u8; KEY_LEN]);
+        SystemRandom::new()
+            .fill(&mut key)
+            .map_err(|_| anyhow::anyhow!("failed to generate ephemeral key"))?;
+        Ok(Self { key })
+    }
+}
+
+impl KeyResolver for EphemeralKeyResolver {
+    fn resolve_for_header(&self, _hdr: &KeviHeader) -> Result<DerivedKey> {
+        Ok(DerivedKey::new(self.key.to_vec()))
+    }
+
+    fn resolve_for_new_vault(&self, _params: HeaderParams, _salt: [u8; 16]) -> Result<DerivedKey> {
+        Ok(DerivedKey::new(self.key.to_vec()))
     }
 }
@@ -30,6 +30,11 @@ pub fn save<T: Serialize>(path: &Path, data: &T, ttl: Duration) -> Result<()> {
 }
 
 pub fn load<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
+    Ok(load_with_expiry(path)?.map(|(data, _)| data))
+}
+
+/// Like [`load`], also returning the session's expiry as Unix seconds.
+pub fn load_with_expiry<T: DeserializeOwned>(path: &Path) -> Result<Option<(T, u64)>> {
     if !path.exists() {
         return Ok(None);
     }
@@ -40,17 +45,19 @@ pub fn load<T: DeserializeOwned>(path: &Path) -> Result<Option<T>> {
         Ok(v) => v,
         Err(_) => {
             // Corrupt or invalid format; clear it
+            log::debug!("session {} is unreadable; removed", path.display());
             let _ = fs::remove_file(path);
             return Ok(None);
         }
     };
 
     if now_unix() >= envelope.expires_at_unix {
+        log::debug!("session {} expired; removed", path.display());
         let _ = fs::remove_file(path);
         return Ok(None);
     }
 
-    Ok(Some(envelope.data))
+    Ok(Some((envelope.data, envelope.expires_at_unix)))
 }
 
 pub fn clear(path: &Path) -> Result<()> {
@@ -1,6 +1,12 @@
+use crate::tui::theme::Theme;
 use crate::vault::handlers::GetField;
 use crate::vault::models::VaultEntry;
 use secrecy::ExposeSecret;
+use std::time::Duration;
+use zeroize::Zeroize;
+
+/// How long a toast message stays on screen.
+pub const TOAST_DURATION: Duration = Duration::from_secs(2);
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum Mode {
@@ -15,6 +21,8 @@ pub enum View {
     AddModal,
     EditModal,
     ConfirmDelete,
+    /// Confirm copying the whole entry, password included, as JSON
+    ConfirmShare,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -25,6 +33,56 @@ pub enum FormField {
     Notes,
 }
 
+/// Commands accepted by the `:` palette.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PaletteCommand {
+    Add,
+    Edit,
+    Delete,
+    Open,
+    Search(String),
+    Copy(GetField),
+    Theme(String),
+    /// `sort recent` (most recently used first) or `sort vault` (vault order)
+    SortRecent(bool),
+    Lock,
+    Quit,
+}
+
+impl PaletteCommand {
+    pub const HELP: &'static str =
+        "add | edit | delete | open | search <q> | copy password|user|notes|custom:<name> | theme <name> | sort recent|vault | lock | quit";
+
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut parts = input.split_whitespace();
+        let cmd = parts.next().unwrap_or("");
+        let arg = parts.collect::<Vec<_>>().join(" ");
+        match (cmd, arg.as_str()) {
+            ("add", "") => Ok(Self::Add),
+            ("edit", "") => Ok(Self::Edit),
+            ("delete" | "rm", "") => Ok(Self::Delete),
+            ("open" | "details", "") => Ok(Self::Open),
+            ("search", q) => Ok(Self::Search(q.to_string())),
+            ("copy", "" | "password") => Ok(Self::Copy(GetField::Password)),
+            ("copy", "user" | "username") => Ok(Self::Copy(GetField::User)),
+            ("copy", "notes") => Ok(Self::Copy(GetField::Notes)),
+            ("copy", other) => match other.strip_prefix("custom:") {
+                Some(name) if !name.is_empty() => {
+                    Ok(Self::Copy(GetField::Custom(name.to_string())))
+                }
+                _ => Err(format!("cannot copy '{other}'")),
+            },
+            ("theme", name) if !name.is_empty() => Ok(Self::Theme(name.to_string())),
+            ("sort", "recent") => Ok(Self::SortRecent(true)),
+            ("sort", "vault") => Ok(Self::SortRecent(false)),
+            ("lock", "") => Ok(Self::Lock),
+            ("quit" | "q", "") => Ok(Self::Quit),
+            ("", _) => Err(Self::HELP.to_string()),
+            _ => Err(format!("unknown command '{}'", input.trim())),
+        }
+    }
+}
+
 pub struct App {
     entries: Vec<VaultEntry>,
     filtered: Vec<usize>,
@@ -33,6 +91,10 @@ pub struct App {
     pub filter: String,
     toast: Option<String>,
     toast_ticks: u16,
+    // Ticks a toast stays visible (about `TOAST_DURATION` at the current tick rate)
+    toast_len: u16,
+    // Set when something visible changed; the event loop redraws only then
+    dirty: bool,
     pub view: View,
     // Form state (Add/Edit)
     pub form_field: FormField,
@@ -43,6 +105,16 @@ pub struct App {
     pub form_original_label: String,
     // Toggle for revealing password in Details view
     pub reveal_password: bool,
+    // Active color theme used by all views
+    pub theme: Theme,
+    // Render notes as basic markdown in the Details view (`tui_render_markdown`)
+    pub render_markdown: bool,
+    // Command palette input while open (overlay on any view)
+    palette: Option<String>,
+    // List most recently used entries first instead of in vault order
+    recent: bool,
+    // Last entry deleted in this session, re-added by `U`
+    undo: Option<VaultEntry>,
 }
 
 impl App {
@@ -55,6 +127,8 @@ impl App {
             filter: String::new(),
             toast: None,
             toast_ticks: 0,
+            toast_len: 10,
+            dirty: true,
             view: View::List,
             form_field: FormField::Label,
             form_label: String::new(),
@@ -63,6 +137,11 @@ impl App {
             form_notes: String::new(),
             form_original_label: String::new(),
             reveal_password: false,
+            theme: Theme::default(),
+            render_markdown: false,
+            palette: None,
+            recent: false,
+            undo: None,
         };
         app.recompute();
         app
@@ -104,7 +183,26 @@ impl App {
 
     pub fn toast(&mut self, msg: String) {
         self.toast = Some(msg);
-        self.toast_ticks = 10; // ~2s at 200ms tick
+        self.toast_ticks = self.toast_len;
+        self.dirty = true;
+    }
+
+    /// Scale toast lifetime to the event loop's tick so it lasts about `TOAST_DURATION`.
+    pub fn set_tick_rate(&mut self, tick: Duration) {
+        let ticks = TOAST_DURATION.as_millis() / tick.as_millis().max(1);
+        self.toast_len = ticks.clamp(1, u16::MAX as u128) as u16;
+    }
+
+    /// Flag the screen for a redraw. State changed through `App` methods flags
+    /// itself; the event loop calls this for input, since key handlers also
+    /// update public fields directly.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether a redraw is due, clearing the flag.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
     }
 
     pub fn toast_message(&self) -> Option<&str> {
@@ -116,6 +214,7 @@ impl App {
             self.toast_ticks -= 1;
             if self.toast_ticks == 0 {
                 self.toast = None;
+                self.dirty = true;
             }
         }
     }
@@ -133,6 +232,7 @@ impl App {
     }
 
     fn recompute(&mut self) {
+        self.dirty = true;
         self.filtered.clear();
         if self.filter.is_empty() {
             self.filtered.extend(0..self.entries.len());
@@ -144,21 +244,65 @@ impl App {
                 }
             }
         }
+        if self.recent {
+            let entries = &self.entries;
+            self.filtered
+                .sort_by_key(|&i| std::cmp::Reverse(entries[i].recency()));
+        }
         if self.selected >= self.filtered.len() {
             self.selected = self.filtered.len().saturating_sub(1);
         }
     }
 
+    pub fn sort_recent(&self) -> bool {
+        self.recent
+    }
+
+    /// Switch between vault order and most-recently-used first.
+    pub fn set_sort_recent(&mut self, recent: bool) {
+        self.recent = recent;
+        self.selected = 0;
+        self.recompute();
+    }
+
+    /// Mirror a recorded access on the selected entry, keeping it selected
+    /// even when the recency sort moves it.
+    pub fn touch_selected(&mut self, now: u64) {
+        let Some(&idx) = self.filtered.get(self.selected) else {
+            return;
+        };
+        self.entries[idx].last_accessed_at = Some(now);
+        self.recompute();
+        if let Some(pos) = self.filtered.iter().position(|&i| i == idx) {
+            self.selected = pos;
+        }
+    }
+
     pub fn selected_field(&self, field: GetField) -> Option<String> {
         if self.filtered.is_empty() {
             return None;
         }
         let idx = self.filtered[self.selected];
-        let e = &self.entries[idx];
-        match field {
-            GetField::Password => Some(e.password.expose_secret().to_string()),
-            GetField::User => e.username.as_ref().map(|u| u.expose_secret().to_string()),
-            GetField::Notes => e.notes.clone(),
+        field.value_of(&self.entries[idx])
+    }
+
+    /// Select the entry labelled exactly `label`, clearing any filter that
+    /// hides it. Returns false (and changes nothing) if there is no such entry.
+    pub fn select_label(&mut self, label: &str) -> bool {
+        let Some(idx) = self.entries.iter().position(|e| e.label == label) else {
+            return false;
+        };
+        if !self.filtered.contains(&idx) {
+            self.filter.clear();
+            self.recompute();
+        }
+        match self.filtered.iter().position(|&i| i == idx) {
+            Some(pos) => {
+                self.selected = pos;
+                self.dirty = true;
+                true
+            }
+            None => false,
         }
     }
 
@@ -169,6 +313,24 @@ impl App {
         Some(self.entries[self.filtered[self.selected]].label.clone())
     }
 
+    pub fn selected_entry(&self) -> Option<&VaultEntry> {
+        self.filtered.get(self.selected).map(|&i| &self.entries[i])
+    }
+
+    /// Keep `entry` so `U` can bring it back; replaces any earlier deletion.
+    pub fn remember_deleted(&mut self, entry: VaultEntry) {
+        self.undo = Some(entry);
+    }
+
+    /// The entry `U` would restore, if any.
+    pub fn undo_entry(&self) -> Option<&VaultEntry> {
+        self.undo.as_ref()
+    }
+
+    pub fn clear_undo(&mut self) {
+        self.undo = None;
+    }
+
     // View navigation
     pub fn enter_details(&mut self) {
         self.view = View::Details;
@@ -212,6 +374,12 @@ impl App {
     pub fn cancel_confirm_delete(&mut self) {
         self.view = View::Details;
     }
+    pub fn enter_confirm_share(&mut self) {
+        self.view = View::ConfirmShare;
+    }
+    pub fn cancel_confirm_share(&mut self) {
+        self.view = View::Details;
+    }
 
     // Form editing
     pub fn next_field(&mut self) {
@@ -257,20 +425,99 @@ impl App {
     pub fn cancel_modal(&mut self) {
         self.view = View::List;
     }
+
+    // Command palette
+    pub fn open_palette(&mut self) {
+        self.palette = Some(String::new());
+    }
+    pub fn palette_input(&self) -> Option<&str> {
+        self.palette.as_deref()
+    }
+    pub fn push_palette(&mut self, c: char) {
+        if let Some(p) = self.palette.as_mut() {
+            p.push(c);
+        }
+    }
+    pub fn pop_palette(&mut self) {
+        if let Some(p) = self.palette.as_mut() {
+            p.pop();
+        }
+    }
+    /// Close the palette, returning what was typed.
+    pub fn close_palette(&mut self) -> String {
+        self.palette.take().unwrap_or_default()
+    }
+
+    /// Apply palette commands that only touch UI state. Clipboard, lock and quit
+    /// are handled by the event loop.
+    pub fn apply_palette(&mut self, cmd: PaletteCommand) -> Result<(), String> {
+        let needs_selection = matches!(
+            cmd,
+            PaletteCommand::Edit | PaletteCommand::Delete | PaletteCommand::Open
+        );
+        if needs_selection && self.selected_label().is_none() {
+            return Err("No entry selected".to_string());
+        }
+        match cmd {
+            PaletteCommand::Add => self.enter_add(),
+            PaletteCommand::Edit => self.enter_edit(),
+            PaletteCommand::Delete => self.enter_confirm_delete(),
+            PaletteCommand::Open => self.enter_details(),
+            PaletteCommand::Search(q) => {
+                self.view = View::List;
+                self.filter = q;
+                self.recompute();
+            }
+            PaletteCommand::Theme(name) => {
+                self.theme = Theme::resolve(Some(&name)).map_err(|e| e.to_string())?;
+            }
+            PaletteCommand::SortRecent(recent) => self.set_sort_recent(recent),
+            PaletteCommand::Copy(_) | PaletteCommand::Lock | PaletteCommand::Quit => {}
+        }
+        Ok(())
+    }
+
+    /// Wipe plaintext held by the TUI: form buffers, the filter, and the entries
+    /// (secret fields zeroize themselves on drop; labels and notes are wiped here).
+    pub fn wipe(&mut self) {
+        for buf in [
+            &mut self.form_label,
+            &mut self.form_user,
+            &mut self.form_password,
+            &mut self.form_notes,
+            &mut self.form_original_label,
+            &mut self.filter,
+        ] {
+            buf.zeroize();
+        }
+        if let Some(p) = self.palette.as_mut() {
+            p.zeroize();
+        }
+        for e in self.entries.iter_mut().chain(self.undo.as_mut()) {
+            e.label.zeroize();
+            if let Some(n) = e.notes.as_mut() {
+                n.zeroize();
+            }
+        }
+        self.entries.clear();
+        self.undo = None;
+        self.filtered.clear();
+        self.selected = 0;
+    }
+}
+
+impl Drop for App {
+    fn drop(&mut self) {
+        self.wipe();
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use secrecy::SecretString;
 
     fn make(label: &str) -> VaultEntry {
-        VaultEntry {
-            label: label.into(),
-            username: None,
-            password: SecretString::new("x".into()),
-            notes: None,
-        }
+        VaultEntry::new(label, "x")
     }
 
     #[test]
@@ -287,4 +534,126 @@ mod tests {
         app.pop_filter();
         assert_eq!(app.visible_labels(), vec!["alpha", "beta", "gamma"]);
     }
+
+    #[test]
+    fn palette_parses_and_dispatches_commands() {
+        assert_eq!(PaletteCommand::parse("add"), Ok(PaletteCommand::Add));
+        assert_eq!(
+            PaletteCommand::parse(" copy  user "),
+            Ok(PaletteCommand::Copy(GetField::User))
+        );
+        assert_eq!(
+            PaletteCommand::parse("theme light"),
+            Ok(PaletteCommand::Theme("light".into()))
+        );
+        assert!(PaletteCommand::parse("copy url").is_err());
+        assert!(PaletteCommand::parse("bogus").is_err());
+
+        let mut app = App::new(vec![make("alpha"), make("beta")]);
+        app.open_palette();
+        for c in "search be".chars() {
+            app.push_palette(c);
+        }
+        let input = app.close_palette();
+        assert!(app.palette_input().is_none());
+        app.apply_palette(PaletteCommand::parse(&input).unwrap())
+            .unwrap();
+        assert_eq!(app.visible_labels(), vec!["beta"]);
+
+        app.apply_palette(PaletteCommand::Delete).unwrap();
+        assert_eq!(app.view, View::ConfirmDelete);
+        assert!(app
+            .apply_palette(PaletteCommand::Theme("neon".into()))
+            .is_err());
+    }
+
+    #[test]
+    fn recency_sort_orders_by_access_then_update_and_follows_touches() {
+        let mut alpha = make("alpha");
+        alpha.updated_at = Some(300);
+        let mut beta = make("beta");
+        beta.updated_at = Some(100);
+        beta.last_accessed_at = Some(500);
+        let mut app = App::new(vec![make("none"), alpha, beta]);
+        assert_eq!(
+            PaletteCommand::parse("sort recent"),
+            Ok(PaletteCommand::SortRecent(true))
+        );
+
+        app.apply_palette(PaletteCommand::SortRecent(true)).unwrap();
+        assert_eq!(app.visible_labels(), vec!["beta", "alpha", "none"]);
+
+        app.selected = 2;
+        app.touch_selected(900);
+        assert_eq!(app.visible_labels(), vec!["none", "beta", "alpha"]);
+        assert_eq!(app.selected_label().as_deref(), Some("none"));
+
+        app.set_sort_recent(false);
+        assert_eq!(app.visible_labels(), vec!["none", "alpha", "beta"]);
+    }
+
+    #[test]
+    fn select_label_preselects_exact_matches_only() {
+        let mut app = App::new(vec![make("alpha"), make("beta"), make("gamma")]);
+        assert!(app.select_label("gamma"));
+        assert_eq!(app.selected_label().as_deref(), Some("gamma"));
+
+        assert!(!app.select_label("gam"));
+        assert_eq!(app.selected_label().as_deref(), Some("gamma"));
+
+        app.push_filter('b');
+        assert!(app.select_label("alpha"));
+        assert!(app.filter.is_empty());
+        assert_eq!(app.selected_label().as_deref(), Some("alpha"));
+    }
+
+    #[test]
+    fn wipe_clears_entries_and_form_buffers() {
+        let mut app = App::new(vec![make("alpha"), make("beta")]);
+        app.form_password = "typed-secret".into();
+        app.filter = "al".into();
+        app.wipe();
+        assert!(app.visible_labels().is_empty());
+        assert!(app.form_password.is_empty());
+        assert!(app.filter.is_empty());
+    }
+
+    #[test]
+    fn undo_buffer_keeps_only_the_last_deletion() {
+        let mut app = App::new(vec![make("alpha"), make("beta")]);
+        assert!(app.undo_entry().is_none());
+        let first = app.selected_entry().cloned().unwrap();
+        assert_eq!(first.label, "alpha");
+        app.remember_deleted(first);
+        app.remember_deleted(make("beta"));
+        assert_eq!(app.undo_entry().map(|e| e.label.as_str()), Some("beta"));
+        app.clear_undo();
+        assert!(app.undo_entry().is_none());
+
+        app.remember_deleted(make("gamma"));
+        app.wipe();
+        assert!(app.undo_entry().is_none());
+    }
+
+    #[test]
+    fn dirty_flag_tracks_changes_and_toast_scales_with_tick() {
+        let mut app = App::new(vec![make("alpha"), make("beta")]);
+        assert!(app.take_dirty(), "first frame is always drawn");
+        assert!(!app.take_dirty());
+
+        app.push_filter('b');
+        assert!(app.take_dirty());
+
+        app.set_tick_rate(Duration::from_millis(500));
+        app.toast("copied".into());
+        assert!(app.take_dirty());
+        app.tick();
+        assert!(!app.take_dirty(), "a live toast needs no redraw");
+        assert!(app.toast_message().is_some());
+        app.tick();
+        app.tick();
+        app.tick();
+        assert!(app.toast_message().is_none(), "2s at 500ms is 4 ticks");
+        assert!(app.take_dirty());
+    }
 }
@@ -0,0 +1,143 @@
+use ratatui::prelude::*;
+
+use crate::tui::theme::Theme;
+
+/// Render notes with a small markdown subset: `#` headings, `-`/`*`/`+` and
+/// numbered list items, `**bold**`, `*italic*` and `` `code` ``. Anything else,
+/// including unclosed markers, is shown as written.
+pub fn render_markdown(text: &str, theme: &Theme) -> Vec<Line<'static>> {
+    text.lines().map(|line| render_line(line, theme)).collect()
+}
+
+fn render_line(line: &str, theme: &Theme) -> Line<'static> {
+    let indent_len = line.len() - line.trim_start().len();
+    let (indent, rest) = line.split_at(indent_len);
+
+    if let Some(heading) = heading_text(rest) {
+        return Line::from(Span::styled(heading.to_string(), theme.title_style()));
+    }
+    let mut spans = vec![Span::raw(indent.to_string())];
+    let body = if let Some(item) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| rest.strip_prefix(bullet))
+    {
+        spans.push(Span::styled("• ", theme.muted_style()));
+        item
+    } else if let Some((number, item)) = numbered_item(rest) {
+        spans.push(Span::styled(format!("{number}. "), theme.muted_style()));
+        item
+    } else {
+        rest
+    };
+    spans.extend(inline_spans(body, theme));
+    Line::from(spans)
+}
+
+/// The text of an ATX heading (`# Title` up to `###### Title`).
+fn heading_text(line: &str) -> Option<&str> {
+    let level = line.bytes().take_while(|&b| b == b'#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    line[level..]
+        .strip_prefix(' ')
+        .map(|t| t.trim_end_matches(['#', ' ']))
+}
+
+fn numbered_item(line: &str) -> Option<(&str, &str)> {
+    let digits = line.bytes().take_while(u8::is_ascii_digit).count();
+    if digits == 0 {
+        return None;
+    }
+    let item = line[digits..].strip_prefix(". ")?;
+    Some((&line[..digits], item))
+}
+
+fn inline_spans(text: &str, theme: &Theme) -> Vec<Span<'static>> {
+    const MARKERS: [(&str, Modifier); 5] = [
+        ("**", Modifier::BOLD),
+        ("__", Modifier::BOLD),
+        ("*", Modifier::ITALIC),
+        ("_", Modifier::ITALIC),
+        ("`", Modifier::empty()),
+    ];
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+    let mut prev = None;
+    'scan: while let Some(c) = rest.chars().next() {
+        for (marker, modifier) in MARKERS {
+            let Some(after) = rest.strip_prefix(marker) else {
+                continue;
+            };
+            let Some(end) = after.find(marker).filter(|&end| end > 0) else {
+                continue;
+            };
+            // Underscores inside words (snake_case) are not emphasis
+            let next = after[end + marker.len()..].chars().next();
+            if marker.starts_with('_')
+                && (prev.is_some_and(char::is_alphanumeric)
+                    || next.is_some_and(char::is_alphanumeric))
+            {
+                continue;
+            }
+            if !plain.is_empty() {
+                spans.push(Span::raw(std::mem::take(&mut plain)));
+            }
+            let style = if marker == "`" {
+                theme.muted_style()
+            } else {
+                Style::default().add_modifier(modifier)
+            };
+            spans.push(Span::styled(after[..end].to_string(), style));
+            rest = &after[end + marker.len()..];
+            prev = marker.chars().last();
+            continue 'scan;
+        }
+        plain.push(c);
+        prev = Some(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(line: &Line) -> Vec<String> {
+        line.spans.iter().map(|s| s.content.to_string()).collect()
+    }
+
+    #[test]
+    fn renders_headings_lists_and_emphasis() {
+        let theme = Theme::default();
+        let lines = render_markdown("## Recovery ##\n- **pin** 1234\n2. call `bank`", &theme);
+        assert_eq!(texts(&lines[0]), ["Recovery"]);
+        assert_eq!(lines[0].spans[0].style, theme.title_style());
+        assert_eq!(texts(&lines[1]), ["", "• ", "pin", " 1234"]);
+        assert!(lines[1].spans[2]
+            .style
+            .add_modifier
+            .contains(Modifier::BOLD));
+        assert_eq!(texts(&lines[2]), ["", "2. ", "call ", "bank"]);
+    }
+
+    #[test]
+    fn leaves_unsupported_and_unclosed_markup_alone() {
+        let theme = Theme::default();
+        for raw in [
+            "#hashtag",
+            "a ** b",
+            "snake_case_name",
+            "> quote",
+            "| a | b |",
+        ] {
+            let lines = render_markdown(raw, &theme);
+            assert_eq!(texts(&lines[0]).concat(), raw, "{raw}");
+        }
+    }
+}
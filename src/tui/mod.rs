@@ -1,7 +1,9 @@
 pub mod app;
+pub mod markdown;
 pub mod theme;
 pub mod views;
 
+use crate::cli::output::spinner_progress;
 use crate::config::app_config::Config;
 use anyhow::{anyhow, Result};
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
@@ -9,33 +11,86 @@ use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
 use std::io;
+use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::task::spawn_blocking;
 
-use crate::filesystem::clipboard::{copy_with_ttl, ttl_seconds, SystemClipboardEngine};
-use crate::filesystem::store::FileByteStore;
-use crate::session_management::resolver::CachedKeyResolver;
-use crate::vault::codec::RonCodec;
-use crate::vault::handlers::GetField;
+use crate::filesystem::clipboard::{
+    clear_hook, copy_until_paste_then, copy_with_ttl_then, restore_previous, ttl_seconds,
+    SystemClipboardEngine,
+};
+use crate::filesystem::store::InMemoryByteStore;
+use crate::session_management::resolver::{
+    default_key_resolver, dk_session_files_for, EphemeralKeyResolver,
+};
+use crate::session_management::session::clear;
+use crate::vault::codec::codec_for;
+use crate::vault::format::{format_entry, EntryFormat};
+use crate::vault::handlers::{
+    reauthenticate_for_reveal, validate_field_sizes, vault_store, GetField,
+};
+use crate::vault::models::validate_label;
 use crate::vault::ports::PasswordGenerator;
-use crate::vault::ports::{ByteStore, KeyResolver, VaultCodec};
-use crate::vault::service::VaultService;
+use crate::vault::ports::{ByteStore, KeyResolver};
+use crate::vault::service::{now_secs, VaultService};
 use secrecy::SecretString;
 
-use self::app::{App, Mode, View};
-use self::views::confirm::render_confirm;
+use self::app::{App, Mode, PaletteCommand, View};
+use self::theme::Theme;
+use self::views::confirm::{render_confirm, render_confirm_share};
 use self::views::details::render_details;
 use self::views::form::render_form;
 use self::views::list::render_list;
+use self::views::palette::render_palette;
+
+/// Default event-loop tick (`tui_tick_ms`).
+const DEFAULT_TICK_MS: u64 = 200;
+
+/// Run the TUI. `select` preselects the entry with that exact label, opening its
+/// details view when `details` is set; an unknown label is ignored. With
+/// `ephemeral` the vault starts empty, lives only in memory and is gone on exit.
+pub async fn launch(
+    config: &Config,
+    theme_override: Option<&str>,
+    select: Option<&str>,
+    details: bool,
+    ephemeral: bool,
+) -> Result<()> {
+    let theme = Theme::resolve(theme_override.or(config.tui_theme.as_deref()))?;
+
+    // There is no vault file to re-authenticate against
+    let mut config = config.clone();
+    if ephemeral {
+        config.require_reveal_reauth = None;
+    }
+    let config = &config;
 
-pub async fn launch(config: &Config) -> Result<()> {
     // Compose service (same defaults as CLI flows)
-    let store: Arc<dyn ByteStore> = Arc::new(FileByteStore::new(config.vault_path.clone()));
-    let codec: Arc<dyn VaultCodec> = Arc::new(RonCodec);
-    let resolver: Arc<dyn KeyResolver> =
-        Arc::new(CachedKeyResolver::new(config.vault_path.clone()));
-    let service = Arc::new(VaultService::new(store, codec, resolver));
+    let (store, resolver): (Arc<dyn ByteStore>, Arc<dyn KeyResolver>) = if ephemeral {
+        (
+            Arc::new(InMemoryByteStore::new()),
+            Arc::new(EphemeralKeyResolver::new()?),
+        )
+    } else {
+        (
+            Arc::new(vault_store(config)),
+            default_key_resolver(
+                config.vault_path.clone(),
+                config.session_sliding(),
+                Some(spinner_progress),
+            ),
+        )
+    };
+    let codec = codec_for(config.compact_storage == Some(true));
+    let mut service = VaultService::new(store, codec, resolver);
+    if let Some(days) = config.trash_retention_days {
+        service = service.with_trash_retention_days(days);
+    }
+    if config.stream_encryption == Some(true) {
+        service = service.with_stream_encryption(true);
+    }
+    let service = Arc::new(service);
 
     // Load entries (may prompt for password if no session cache) without blocking the async runtime
     let svc = service.clone();
@@ -44,33 +99,89 @@ pub async fn launch(config: &Config) -> Result<()> {
         .map_err(|_| anyhow!("task join error"))?
         .map_err(|e| anyhow!("failed to load vault for TUI: {}", e))?;
 
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    crossterm::execute!(stdout, crossterm::terminal::EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
+    // Setup terminal; the guard restores it on every exit path, including panics
+    let mut guard = TerminalGuard::enter()?;
+    let terminated = watch_termination()?;
+    let backend = CrosstermBackend::new(io::stdout());
     let mut terminal = Terminal::new(backend)?;
 
     let ttl_secs = ttl_seconds(config, None);
     let mut app = App::new(entries);
+    app.theme = theme;
+    app.render_markdown = config.tui_render_markdown.unwrap_or(false);
+    if let Some(label) = select {
+        if app.select_label(label) && details {
+            app.enter_details();
+        }
+    }
     let mut last_tick = Instant::now();
-    let tick_rate = Duration::from_millis(200);
+    let tick_rate = Duration::from_millis(config.tui_tick_ms.unwrap_or(DEFAULT_TICK_MS));
+    app.set_tick_rate(tick_rate);
 
     let res = loop {
-        terminal.draw(|f| match app.view {
-            View::List => render_list(f, &app),
-            View::Details => render_details(f, &app),
-            View::AddModal | View::EditModal => render_form(f, &app),
-            View::ConfirmDelete => render_confirm(f, &app),
-        })?;
+        if terminated.load(Ordering::SeqCst) != 0 {
+            break Ok(());
+        }
+        // Redraw only when something changed; an idle TUI just waits for input
+        if app.take_dirty() {
+            terminal.draw(|f| {
+                match app.view {
+                    View::List => render_list(f, &app),
+                    View::Details => render_details(f, &app),
+                    View::AddModal | View::EditModal => render_form(f, &app),
+                    View::ConfirmDelete => render_confirm(f, &app),
+                    View::ConfirmShare => render_confirm_share(f, &app),
+                }
+                render_palette(f, &app);
+            })?;
+        }
 
         let timeout = tick_rate
             .checked_sub(last_tick.elapsed())
             .unwrap_or(Duration::from_millis(0));
 
         if event::poll(timeout)? {
-            if let Event::Key(k) = event::read()? {
-                if k.kind == KeyEventKind::Press {
+            let ev = event::read()?;
+            if matches!(ev, Event::Key(_) | Event::Resize(..)) {
+                app.mark_dirty();
+            }
+            if let Event::Key(k) = ev {
+                if k.kind == KeyEventKind::Press && app.palette_input().is_some() {
+                    // Command palette overlay takes all keys while open
+                    match k.code {
+                        KeyCode::Esc => {
+                            app.close_palette();
+                        }
+                        KeyCode::Backspace => app.pop_palette(),
+                        KeyCode::Enter => {
+                            let input = app.close_palette();
+                            match PaletteCommand::parse(&input) {
+                                Ok(PaletteCommand::Quit) => break Ok(()),
+                                Ok(PaletteCommand::Lock) => {
+                                    // Drop the cached key and leave the TUI
+                                    if ephemeral {
+                                        break Ok(());
+                                    }
+                                    break dk_session_files_for(&config.vault_path)
+                                        .iter()
+                                        .try_for_each(|p| clear(p));
+                                }
+                                Ok(PaletteCommand::Copy(field)) => {
+                                    copy_and_record(&mut app, config, &service, field, ttl_secs)
+                                        .await
+                                }
+                                Ok(cmd) => {
+                                    if let Err(e) = app.apply_palette(cmd) {
+                                        app.toast(e);
+                                    }
+                                }
+                                Err(e) => app.toast(e),
+                            }
+                        }
+                        KeyCode::Char(c) if !c.is_control() => app.push_palette(c),
+                        _ => {}
+                    }
+                } else if k.kind == KeyEventKind::Press {
                     // Global per-view key handling
                     match app.view {
                         View::List => {
@@ -80,39 +191,32 @@ pub async fn launch(config: &Config) -> Result<()> {
                                     KeyCode::Down | KeyCode::Char('j') => app.next(),
                                     KeyCode::Up | KeyCode::Char('k') => app.prev(),
                                     KeyCode::Char('/') => app.enter_search(),
+                                    KeyCode::Char(':') => app.open_palette(),
                                     KeyCode::Right | KeyCode::Char('l') => app.enter_details(),
                                     KeyCode::Char('a') => app.enter_add(),
+                                    KeyCode::Char('s') => app.set_sort_recent(!app.sort_recent()),
                                     KeyCode::Enter => {
                                         // Copy password (legacy behavior from list)
-                                        if let Some(val) = app.selected_field(GetField::Password) {
-                                            if let Ok(engine) = SystemClipboardEngine::new() {
-                                                let secret = SecretString::new(val.into());
-                                                let _ = copy_with_ttl(
-                                                    Arc::new(engine),
-                                                    &secret,
-                                                    Duration::from_secs(ttl_secs),
-                                                );
-                                                app.toast(format!("Password copied ({ttl_secs}s)"));
-                                            } else {
-                                                app.toast("Clipboard unavailable".to_string());
-                                            }
-                                        }
+                                        copy_and_record(
+                                            &mut app,
+                                            config,
+                                            &service,
+                                            GetField::Password,
+                                            ttl_secs,
+                                        )
+                                        .await;
                                     }
                                     KeyCode::Char('u') => {
-                                        if let Some(val) = app.selected_field(GetField::User) {
-                                            if let Ok(engine) = SystemClipboardEngine::new() {
-                                                let secret = SecretString::new(val.into());
-                                                let _ = copy_with_ttl(
-                                                    Arc::new(engine),
-                                                    &secret,
-                                                    Duration::from_secs(ttl_secs),
-                                                );
-                                                app.toast(format!("Username copied ({ttl_secs}s)"));
-                                            } else {
-                                                app.toast("Clipboard unavailable".to_string());
-                                            }
-                                        }
+                                        copy_and_record(
+                                            &mut app,
+                                            config,
+                                            &service,
+                                            GetField::User,
+                                            ttl_secs,
+                                        )
+                                        .await;
                                     }
+                                    KeyCode::Char('U') => undo_delete(&mut app, &service).await,
                                     _ => {}
                                 },
                                 Mode::Search => match k.code {
@@ -129,43 +233,42 @@ pub async fn launch(config: &Config) -> Result<()> {
                                 app.back_to_list()
                             }
                             KeyCode::Enter => {
-                                if let Some(val) = app.selected_field(GetField::Password) {
-                                    if let Ok(engine) = SystemClipboardEngine::new() {
-                                        let secret = SecretString::new(val.into());
-                                        let _ = copy_with_ttl(
-                                            Arc::new(engine),
-                                            &secret,
-                                            Duration::from_secs(ttl_secs),
-                                        );
-                                        app.toast(format!("Password copied ({ttl_secs}s)"));
-                                    } else {
-                                        app.toast("Clipboard unavailable".to_string());
-                                    }
-                                }
+                                copy_and_record(
+                                    &mut app,
+                                    config,
+                                    &service,
+                                    GetField::Password,
+                                    ttl_secs,
+                                )
+                                .await
                             }
                             KeyCode::Char('u') => {
-                                if let Some(val) = app.selected_field(GetField::User) {
-                                    if let Ok(engine) = SystemClipboardEngine::new() {
-                                        let secret = SecretString::new(val.into());
-                                        let _ = copy_with_ttl(
-                                            Arc::new(engine),
-                                            &secret,
-                                            Duration::from_secs(ttl_secs),
-                                        );
-                                        app.toast(format!("Username copied ({ttl_secs}s)"));
-                                    } else {
-                                        app.toast("Clipboard unavailable".to_string());
-                                    }
-                                } else {
-                                    app.toast("No username".to_string());
-                                }
+                                copy_and_record(
+                                    &mut app,
+                                    config,
+                                    &service,
+                                    GetField::User,
+                                    ttl_secs,
+                                )
+                                .await
                             }
                             KeyCode::Char('v') => {
-                                app.reveal_password = !app.reveal_password;
+                                if app.reveal_password {
+                                    app.reveal_password = false;
+                                } else {
+                                    match suspended(&mut terminal, || {
+                                        reauthenticate_for_reveal(config)
+                                    }) {
+                                        Ok(Ok(())) => app.reveal_password = true,
+                                        Ok(Err(e)) | Err(e) => app.toast(e.to_string()),
+                                    }
+                                }
                             }
                             KeyCode::Char('e') => app.enter_edit(),
                             KeyCode::Char('a') => app.enter_add(),
                             KeyCode::Char('d') => app.enter_confirm_delete(),
+                            KeyCode::Char('C') => app.enter_confirm_share(),
+                            KeyCode::Char(':') => app.open_palette(),
                             _ => {}
                         },
                         View::AddModal | View::EditModal => {
@@ -179,6 +282,14 @@ pub async fn launch(config: &Config) -> Result<()> {
                                     let label = app.form_label.trim().to_string();
                                     if label.is_empty() {
                                         app.toast("Label required".to_string());
+                                    } else if let Err(e) = validate_label(&label).and_then(|_| {
+                                        validate_field_sizes(
+                                            config,
+                                            &app.form_user,
+                                            &app.form_notes,
+                                        )
+                                    }) {
+                                        app.toast(e.to_string());
                                     } else {
                                         // Build entry; for Add we generate a strong password by default
                                         let is_add = matches!(app.view, View::AddModal);
@@ -205,7 +316,7 @@ pub async fn launch(config: &Config) -> Result<()> {
                                                 let _ = spawn_blocking(move || {
                                                     let pw_final = if form_pw.is_empty() {
                                                         // Generate password via default generator
-                                                        let gen2 = crate::cryptography::generator::DefaultPasswordGenerator::new(Arc::new(crate::cryptography::generator::SystemRng));
+                                                        let gen2 = crate::cryptography::generator::DefaultPasswordGenerator::new(crate::cryptography::generator::generator_rng());
                                                         gen2.generate(&crate::vault::ports::GenPolicy::default())?
                                                     } else {
                                                         form_pw
@@ -216,26 +327,31 @@ pub async fn launch(config: &Config) -> Result<()> {
                                                         username: user_opt.map(|u| SecretString::new(u.into())),
                                                         password: SecretString::new(pw_final.into()),
                                                         notes: notes_opt,
+                                                        custom: Default::default(),
+                                                        created_at: None,
+                                                        updated_at: None,
+                                                        last_accessed_at: None,
                                                     };
                                                     svc.add_entry(entry_real)
                                                 }).await.map_err(|_| anyhow!("task join error"))?;
                                             } else {
                                                 spawn_blocking(move || {
-                                                    let mut vault_entries = svc.load()?;
-                                                    if let Some(pos) = vault_entries
-                                                        .iter()
-                                                        .position(|e| e.label == original_label)
-                                                    {
-                                                        vault_entries[pos].label = label_for_save;
-                                                        vault_entries[pos].username = user_opt
-                                                            .map(|u| SecretString::new(u.into()));
-                                                        vault_entries[pos].password =
-                                                            SecretString::new(form_pw.into());
-                                                        vault_entries[pos].notes = notes_opt;
-                                                        svc.save(&vault_entries)
-                                                    } else {
+                                                    svc.batch(|vault_entries| {
+                                                        if let Some(e) = vault_entries
+                                                            .iter_mut()
+                                                            .find(|e| e.label == original_label)
+                                                        {
+                                                            e.label = label_for_save;
+                                                            e.username = user_opt.map(|u| {
+                                                                SecretString::new(u.into())
+                                                            });
+                                                            e.password =
+                                                                SecretString::new(form_pw.into());
+                                                            e.notes = notes_opt;
+                                                            e.updated_at = Some(now_secs());
+                                                        }
                                                         Ok(())
-                                                    }
+                                                    })
                                                 })
                                                 .await
                                                 .map_err(|_| anyhow!("task join error"))??;
@@ -252,22 +368,37 @@ pub async fn launch(config: &Config) -> Result<()> {
                                         }
                                     }
                                 }
-                                KeyCode::Char(c) => {
-                                    if !c.is_control() {
-                                        app.update_form_char(c);
-                                    }
+                                KeyCode::Char(c) if !c.is_control() => {
+                                    app.update_form_char(c);
                                 }
                                 _ => {}
                             }
                         }
+                        View::ConfirmShare => match k.code {
+                            KeyCode::Char('y') => {
+                                match suspended(&mut terminal, || reauthenticate_for_reveal(config))
+                                {
+                                    Ok(Ok(())) => copy_entry_json(&mut app, config, ttl_secs),
+                                    Ok(Err(e)) | Err(e) => app.toast(e.to_string()),
+                                }
+                                app.cancel_confirm_share();
+                            }
+                            KeyCode::Esc | KeyCode::Char('n') => app.cancel_confirm_share(),
+                            _ => {}
+                        },
                         View::ConfirmDelete => {
                             match k.code {
                                 KeyCode::Esc | KeyCode::Char('n') => app.cancel_confirm_delete(),
                                 KeyCode::Char('y') => {
-                                    if let Some(label) = app.selected_label() {
+                                    if let Some(entry) = app.selected_entry().cloned() {
+                                        let label = entry.label.clone();
                                         let svc_rm = service.clone();
-                                        let _ = spawn_blocking(move || svc_rm.remove_entry(&label))
-                                            .await;
+                                        if let Ok(Ok(true)) =
+                                            spawn_blocking(move || svc_rm.remove_entry(&label))
+                                                .await
+                                        {
+                                            app.remember_deleted(entry);
+                                        }
                                         // Reload
                                         let svc_reload = service.clone();
                                         if let Ok(Ok(ents)) =
@@ -297,14 +428,236 @@ pub async fn launch(config: &Config) -> Result<()> {
         }
     };
 
-    // Restore terminal
+    // Wipe decrypted entries before the terminal is handed back
+    drop(app);
+    guard.restore()?;
+
+    let signal = terminated.load(Ordering::SeqCst);
+    if signal != 0 {
+        std::process::exit(128 + signal);
+    }
+    res
+}
+
+/// Hand the terminal back to the shell while `f` runs (e.g. a password prompt),
+/// then return to the TUI and force a full redraw.
+fn suspended<T>(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    f: impl FnOnce() -> T,
+) -> Result<T> {
     disable_raw_mode()?;
     crossterm::execute!(
-        terminal.backend_mut(),
+        io::stdout(),
         crossterm::terminal::LeaveAlternateScreen,
         crossterm::cursor::Show
     )?;
-    terminal.show_cursor()?;
+    let out = f();
+    crossterm::execute!(io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    terminal.clear()?;
+    Ok(out)
+}
 
-    res
+/// Raw mode and the alternate screen for the TUI's lifetime. Dropping the guard
+/// (early `?` returns, panics) restores the terminal; `restore` does it explicitly.
+struct TerminalGuard {
+    active: bool,
+}
+
+impl TerminalGuard {
+    fn enter() -> Result<Self> {
+        enable_raw_mode()?;
+        let guard = Self { active: true };
+        crossterm::execute!(io::stdout(), crossterm::terminal::EnterAlternateScreen)?;
+        Ok(guard)
+    }
+
+    fn restore(&mut self) -> Result<()> {
+        if !std::mem::take(&mut self.active) {
+            return Ok(());
+        }
+        disable_raw_mode()?;
+        crossterm::execute!(
+            io::stdout(),
+            crossterm::terminal::LeaveAlternateScreen,
+            crossterm::cursor::Show
+        )?;
+        Ok(())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = self.restore();
+    }
+}
+
+/// Record SIGTERM/SIGHUP (e.g. from a session manager) instead of dying mid-frame,
+/// so the event loop can wipe entries and restore the terminal before exiting.
+/// Holds the signal number once one arrives, 0 until then.
+fn watch_termination() -> Result<Arc<AtomicI32>> {
+    let received = Arc::new(AtomicI32::new(0));
+    #[cfg(target_family = "unix")]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        const SIGHUP: i32 = 1;
+        const SIGTERM: i32 = 15;
+        let mut term = signal(SignalKind::terminate())?;
+        let mut hup = signal(SignalKind::hangup())?;
+        let flag = received.clone();
+        tokio::spawn(async move {
+            let signo = tokio::select! {
+                _ = term.recv() => SIGTERM,
+                _ = hup.recv() => SIGHUP,
+            };
+            flag.store(signo, Ordering::SeqCst);
+        });
+    }
+    Ok(received)
+}
+
+/// `copy_selected`, then with `track_access` record the access. The save is
+/// awaited so it cannot race a later edit of the same vault.
+async fn copy_and_record(
+    app: &mut App,
+    config: &Config,
+    service: &Arc<VaultService>,
+    field: GetField,
+    ttl_secs: u64,
+) {
+    if !copy_selected(app, config, field, ttl_secs) || config.track_access != Some(true) {
+        return;
+    }
+    let Some(label) = app.selected_label() else {
+        return;
+    };
+    let svc = service.clone();
+    match spawn_blocking(move || svc.record_access(&label)).await {
+        Ok(Ok(())) => app.touch_selected(now_secs()),
+        _ => app.toast("Copied, but could not record access".to_string()),
+    }
+}
+
+/// Move the last entry deleted in this session back out of the trash (or re-add
+/// it, if the trash does not keep entries) and reload the list.
+async fn undo_delete(app: &mut App, service: &Arc<VaultService>) {
+    let Some(entry) = app.undo_entry().cloned() else {
+        app.toast("Nothing to undo".to_string());
+        return;
+    };
+    let label = entry.label.clone();
+    let svc = service.clone();
+    let restored = spawn_blocking(move || {
+        if !svc.restore_entry(&entry.label)? {
+            svc.add_entry(entry)?;
+        }
+        svc.load()
+    })
+    .await
+    .map_err(|_| anyhow!("task join error"))
+    .and_then(|r| r);
+    match restored {
+        Ok(ents) => {
+            app.replace_entries(ents);
+            app.select_label(&label);
+            app.clear_undo();
+            app.toast(format!("Restored '{label}'"));
+        }
+        Err(e) => app.toast(e.to_string()),
+    }
+}
+
+/// Copy a field of the selected entry to the clipboard with TTL, reporting via toast.
+/// Returns whether anything was copied.
+fn copy_selected(app: &mut App, config: &Config, field: GetField, ttl_secs: u64) -> bool {
+    let name = match &field {
+        GetField::Password => "Password".to_string(),
+        GetField::User => "Username".to_string(),
+        GetField::Notes => "Notes".to_string(),
+        GetField::Custom(n) => format!("Field '{n}'"),
+        GetField::All => return false,
+    };
+    let Some(val) = app.selected_field(field) else {
+        app.toast(format!("No {}", name.to_lowercase()));
+        return false;
+    };
+    if copy_secret(config, SecretString::new(val.into()), ttl_secs) {
+        app.toast(format!("{name} copied ({ttl_secs}s)"));
+        true
+    } else {
+        app.toast("Clipboard unavailable".to_string());
+        false
+    }
+}
+
+/// Copy the selected entry, password and custom fields included, as JSON.
+fn copy_entry_json(app: &mut App, config: &Config, ttl_secs: u64) {
+    let Some(entry) = app.selected_entry() else {
+        return;
+    };
+    let json = match format_entry(entry, EntryFormat::Json, true) {
+        Ok(json) => json,
+        Err(e) => return app.toast(e.to_string()),
+    };
+    if copy_secret(config, SecretString::new(json.into()), ttl_secs) {
+        app.toast(format!(
+            "PLAINTEXT entry with password copied ({ttl_secs}s) - share it carefully"
+        ));
+    } else {
+        app.toast("Clipboard unavailable".to_string());
+    }
+}
+
+/// Put `secret` on the clipboard with the configured TTL behavior; false if
+/// no clipboard is available.
+fn copy_secret(config: &Config, secret: SecretString, ttl_secs: u64) -> bool {
+    if let Ok(engine) = SystemClipboardEngine::new() {
+        let ttl = Duration::from_secs(ttl_secs);
+        let _ = if config.clipboard_clear_on_paste == Some(true) {
+            copy_until_paste_then(
+                Arc::new(engine),
+                &secret,
+                ttl,
+                restore_previous(config),
+                clear_hook(config),
+            )
+        } else {
+            copy_with_ttl_then(
+                Arc::new(engine),
+                &secret,
+                ttl,
+                restore_previous(config),
+                clear_hook(config),
+            )
+        };
+        true
+    } else {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session_management::resolver::EphemeralKeyResolver;
+    use crate::vault::codec::RonCodec;
+    use crate::vault::models::VaultEntry;
+
+    #[tokio::test]
+    async fn undo_moves_the_deleted_entry_back_out_of_the_trash() {
+        let service = Arc::new(VaultService::new(
+            Arc::new(InMemoryByteStore::new()),
+            Arc::new(RonCodec),
+            Arc::new(EphemeralKeyResolver::new().unwrap()),
+        ));
+        service.add_entry(VaultEntry::new("alpha", "pw")).unwrap();
+        let mut app = App::new(service.load().unwrap());
+        app.remember_deleted(app.selected_entry().cloned().unwrap());
+        assert!(service.remove_entry("alpha").unwrap());
+
+        undo_delete(&mut app, &service).await;
+        let doc = service.load_document().unwrap();
+        assert_eq!(doc.entries.len(), 1);
+        assert!(doc.trash.is_empty(), "no copy is left in the trash");
+    }
 }
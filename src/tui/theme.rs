@@ -1,3 +1,5 @@
+use crate::cli::output::no_color;
+use anyhow::{anyhow, Result};
 use ratatui::style::{Color, Modifier, Style};
 
 #[derive(Clone, Debug)]
@@ -12,6 +14,34 @@ pub struct Theme {
 
 impl Default for Theme {
     fn default() -> Self {
+        if no_color() {
+            return Self::monochrome();
+        }
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// Names accepted by `--theme` and the `tui_theme` config key.
+    pub const NAMES: [&'static str; 3] = ["dark", "light", "high-contrast"];
+
+    /// Resolve a named theme; `None` keeps the default. NO_COLOR always wins.
+    pub fn resolve(name: Option<&str>) -> Result<Self> {
+        if no_color() {
+            return Ok(Self::monochrome());
+        }
+        match name {
+            None | Some("dark") => Ok(Self::dark()),
+            Some("light") => Ok(Self::light()),
+            Some("high-contrast") => Ok(Self::high_contrast()),
+            Some(other) => Err(anyhow!(
+                "unknown theme \"{other}\"; expected one of: {}",
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+
+    pub fn dark() -> Self {
         // NES/SEGA inspired palette
         Self {
             bg: Color::Black,
@@ -22,9 +52,41 @@ impl Default for Theme {
             selection: Color::Cyan,
         }
     }
-}
 
-impl Theme {
+    pub fn light() -> Self {
+        Self {
+            bg: Color::White,
+            fg: Color::Black,
+            primary: Color::Blue,
+            accent: Color::Red,
+            muted: Color::Gray,
+            selection: Color::Magenta,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            bg: Color::Black,
+            fg: Color::White,
+            primary: Color::Yellow,
+            accent: Color::LightRed,
+            muted: Color::White,
+            selection: Color::LightCyan,
+        }
+    }
+
+    /// Terminal default colors only; emphasis via modifiers (for NO_COLOR / --no-color).
+    pub fn monochrome() -> Self {
+        Self {
+            bg: Color::Reset,
+            fg: Color::Reset,
+            primary: Color::Reset,
+            accent: Color::Reset,
+            muted: Color::Reset,
+            selection: Color::Reset,
+        }
+    }
+
     pub fn title_style(&self) -> Style {
         Style::default()
             .fg(self.primary)
@@ -2,13 +2,22 @@ use ratatui::prelude::*;
 use ratatui::widgets::Paragraph;
 
 use crate::tui::app::App;
-use crate::tui::theme::Theme;
 
 pub fn render_confirm(f: &mut Frame, app: &App) {
-    let theme = Theme::default();
+    let theme = &app.theme;
     let area = f.area();
     let label = app.selected_label().unwrap_or_else(|| "(none)".to_string());
     let text = format!("Delete '{label}'? (y/N)");
     let para = Paragraph::new(text).style(theme.toast_style());
     f.render_widget(para, area);
 }
+
+pub fn render_confirm_share(f: &mut Frame, app: &App) {
+    let theme = &app.theme;
+    let area = f.area();
+    let label = app.selected_label().unwrap_or_else(|| "(none)".to_string());
+    let text =
+        format!("Copy '{label}' as PLAINTEXT JSON, password included, to the clipboard? (y/N)");
+    let para = Paragraph::new(text).style(theme.toast_style());
+    f.render_widget(para, area);
+}
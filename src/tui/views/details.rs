@@ -2,11 +2,11 @@ use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, Paragraph};
 
 use crate::tui::app::{App, View};
-use crate::tui::theme::Theme;
+use crate::tui::markdown::render_markdown;
 use crate::vault::handlers::GetField;
 
 pub fn render_details(f: &mut Frame, app: &App) {
-    let theme = Theme::default();
+    let theme = &app.theme;
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -33,20 +33,31 @@ pub fn render_details(f: &mut Frame, app: &App) {
         "********".to_string()
     };
 
-    let notes = app
-        .selected_field(GetField::Notes)
-        .unwrap_or_else(|| "(none)".to_string());
+    let notes = app.selected_field(GetField::Notes);
 
-    let body =
-        format!("Label: {label}\nUsername: {user}\nPassword: {pass_display}\nNotes: {notes}");
-    let para = Paragraph::new(body)
+    let mut lines = vec![
+        Line::from(format!("Label: {label}")),
+        Line::from(format!("Username: {user}")),
+        Line::from(format!("Password: {pass_display}")),
+    ];
+    match notes {
+        Some(notes) if app.render_markdown => {
+            lines.push(Line::from("Notes:"));
+            lines.extend(render_markdown(&notes, theme));
+        }
+        notes => {
+            let notes = notes.unwrap_or_else(|| "(none)".to_string());
+            lines.extend(Text::from(format!("Notes: {notes}")).lines);
+        }
+    }
+    let para = Paragraph::new(Text::from(lines))
         .block(Block::default().borders(Borders::ALL).title("Entry"))
         .style(theme.normal_style());
     f.render_widget(para, chunks[1]);
 
     let footer = match app.view {
         View::Details => {
-            "q=back  Enter=copy password  u=copy user  v=toggle password  e=edit  d=delete"
+            "q=back  Enter=copy password  u=copy user  v=toggle password  C=copy entry as JSON  e=edit  d=delete  :=commands"
         }
         _ => "",
     };
@@ -21,7 +21,7 @@ fn field_line<'a>(
 }
 
 pub fn render_form(f: &mut Frame, app: &App) {
-    let theme = Theme::default();
+    let theme = &app.theme;
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
@@ -57,25 +57,25 @@ pub fn render_form(f: &mut Frame, app: &App) {
         "Label",
         &app.form_label,
         matches!(app.form_field, FormField::Label),
-        &theme,
+        theme,
     );
     let user_para = field_line(
         "Username",
         &app.form_user,
         matches!(app.form_field, FormField::User),
-        &theme,
+        theme,
     );
     let password_para = field_line(
         "Password",
         &app.form_password,
         matches!(app.form_field, FormField::Password),
-        &theme,
+        theme,
     );
     let notes_para = field_line(
         "Notes",
         &app.form_notes,
         matches!(app.form_field, FormField::Notes),
-        &theme,
+        theme,
     );
 
     f.render_widget(label_para, inner[0]);
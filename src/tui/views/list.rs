@@ -2,10 +2,9 @@ use ratatui::prelude::*;
 use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
 
 use crate::tui::app::{App, Mode};
-use crate::tui::theme::Theme;
 
 pub fn render_list(f: &mut Frame, app: &App) {
-    let theme = Theme::default();
+    let theme = &app.theme;
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -45,11 +44,16 @@ pub fn render_list(f: &mut Frame, app: &App) {
         })
         .collect();
 
-    let list = List::new(items).block(Block::default().borders(Borders::ALL).title("Entries"));
+    let title = if app.sort_recent() {
+        "Entries (recent first)"
+    } else {
+        "Entries"
+    };
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
     f.render_widget(list, chunks[2]);
 
     let footer_text = app.toast_message().unwrap_or(
-        "q=quit  j/k or arrows=move  (> marks selection)  Enter=copy password  u=copy user",
+        "q=quit  j/k or arrows=move  (> marks selection)  Enter=copy password  u=copy user  s=sort  U=undo delete  :=commands",
     );
     let footer = Paragraph::new(footer_text).style(theme.toast_style());
     f.render_widget(footer, chunks[3]);
@@ -2,3 +2,4 @@ pub mod confirm;
 pub mod details;
 pub mod form;
 pub mod list;
+pub mod palette;
@@ -0,0 +1,29 @@
+use ratatui::prelude::*;
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+
+use crate::tui::app::{App, PaletteCommand};
+
+/// Overlay the `:` command palette at the bottom of the screen.
+pub fn render_palette(f: &mut Frame, app: &App) {
+    let Some(input) = app.palette_input() else {
+        return;
+    };
+    let theme = &app.theme;
+    let full = f.area();
+    let height = 3.min(full.height);
+    let area = Rect {
+        x: full.x,
+        y: full.y + full.height - height,
+        width: full.width,
+        height,
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(PaletteCommand::HELP)
+        .style(theme.muted_style());
+    let para = Paragraph::new(format!(":{input}"))
+        .style(theme.normal_style())
+        .block(block);
+    f.render_widget(Clear, area);
+    f.render_widget(para, area);
+}
@@ -1,24 +1,266 @@
-use crate::vault::models::VaultEntry;
-use crate::vault::ports::VaultCodec;
-use anyhow::{anyhow, Context, Result};
+use crate::vault::models::{TrashedEntry, VaultDocument, VaultEntry, SCHEMA_VERSION};
+use crate::vault::ports::{DecodeIssue, VaultCodec};
+use anyhow::{anyhow, Result};
+use ron::error::SpannedError;
 use ron::ser::PrettyConfig;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::sync::Arc;
 
 pub struct RonCodec;
 
+/// Writes RON without indentation or array index comments (config
+/// `compact_storage`); decoding is shared with [`RonCodec`], which reads both.
+pub struct CompactRonCodec;
+
+/// The codec for new writes: compact when `compact` is set, pretty otherwise.
+pub fn codec_for(compact: bool) -> Arc<dyn VaultCodec> {
+    if compact {
+        Arc::new(CompactRonCodec)
+    } else {
+        Arc::new(RonCodec)
+    }
+}
+
+/// What gets written: the document stamped with this build's schema version.
+#[derive(Serialize)]
+struct StoredDocument<'a> {
+    schema_version: u32,
+    entries: &'a [VaultEntry],
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    trash: &'a [TrashedEntry],
+}
+
+fn encode_with(doc: &VaultDocument, pretty: Option<PrettyConfig>) -> Result<Vec<u8>> {
+    let stored = StoredDocument {
+        schema_version: SCHEMA_VERSION,
+        entries: &doc.entries,
+        trash: &doc.trash,
+    };
+    let s = match pretty {
+        Some(p) => ron::ser::to_string_pretty(&stored, p)?,
+        None => ron::ser::to_string(&stored)?,
+    };
+    Ok(s.into_bytes())
+}
+
 impl VaultCodec for RonCodec {
-    fn encode(&self, entries: &[VaultEntry]) -> Result<Vec<u8>> {
+    fn encode(&self, doc: &VaultDocument) -> Result<Vec<u8>> {
         let pretty = PrettyConfig::new()
-            .depth_limit(3)
+            .depth_limit(4)
             .separate_tuple_members(true)
             .enumerate_arrays(true);
-        let s = ron::ser::to_string_pretty(entries, pretty)?;
-        Ok(s.into_bytes())
+        encode_with(doc, Some(pretty))
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<VaultDocument> {
+        let s = utf8(data)?;
+        // Bare entry lists predate the document layout (schema 0)
+        let list_err = match ron::from_str::<Vec<VaultEntry>>(s) {
+            Ok(entries) => {
+                return Ok(VaultDocument {
+                    entries,
+                    ..Default::default()
+                })
+            }
+            Err(e) => e,
+        };
+        match ron::from_str::<VaultDocument>(s) {
+            Ok(doc) => Ok(doc),
+            Err(doc_err) => {
+                // Report the error for the layout the content actually uses
+                let err = if s.trim_start().starts_with('[') {
+                    list_err
+                } else {
+                    doc_err
+                };
+                Err(anyhow!(
+                    "Failed to parse vault content at {}; run `kevi repair` to skip damaged entries",
+                    describe(&err)
+                ))
+            }
+        }
     }
 
-    fn decode(&self, data: &[u8]) -> Result<Vec<VaultEntry>> {
-        let s = String::from_utf8(data.to_vec())
-            .map_err(|_| anyhow!("vault content not valid UTF-8 RON"))?;
-        let vault: Vec<VaultEntry> = ron::from_str(&s).context("Failed to parse vault content")?;
-        Ok(vault)
+    fn decode_lenient(&self, data: &[u8]) -> Result<(VaultDocument, Vec<DecodeIssue>)> {
+        if let Ok(doc) = self.decode(data) {
+            return Ok((doc, Vec::new()));
+        }
+        let s = utf8(data)?;
+        let start = s.len() - s.trim_start().len();
+        let mut doc = VaultDocument::default();
+        let mut issues = Vec::new();
+        match s[start..].chars().next() {
+            Some('[') => {
+                doc.entries = parse_items::<VaultEntry>(s, start, "entries", &mut issues);
+            }
+            Some('(') => {
+                // Document layout: `(schema_version: N, entries: [...], trash: [...])`
+                for (off, field) in split_items(s, start) {
+                    let Some((key, value)) = field.split_once(':') else {
+                        continue;
+                    };
+                    if key.trim() == "schema_version" {
+                        doc.schema_version = value.trim().parse().unwrap_or_default();
+                        continue;
+                    }
+                    let Some(open) = value.find('[') else {
+                        continue;
+                    };
+                    let list_at = off + key.len() + 1 + open;
+                    match key.trim() {
+                        "entries" => {
+                            doc.entries =
+                                parse_items::<VaultEntry>(s, list_at, "entries", &mut issues)
+                        }
+                        "trash" => {
+                            doc.trash =
+                                parse_items::<TrashedEntry>(s, list_at, "trash", &mut issues)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => return Err(anyhow!("vault content is not a RON list or document")),
+        }
+        Ok((doc, issues))
     }
 }
+
+impl VaultCodec for CompactRonCodec {
+    fn encode(&self, doc: &VaultDocument) -> Result<Vec<u8>> {
+        encode_with(doc, None)
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<VaultDocument> {
+        RonCodec.decode(data)
+    }
+
+    fn decode_lenient(&self, data: &[u8]) -> Result<(VaultDocument, Vec<DecodeIssue>)> {
+        RonCodec.decode_lenient(data)
+    }
+}
+
+fn utf8(data: &[u8]) -> Result<&str> {
+    std::str::from_utf8(data).map_err(|_| anyhow!("vault content not valid UTF-8 RON"))
+}
+
+/// `line L, column C: message`, with quoted values redacted so secrets from a
+/// malformed entry never reach the terminal.
+fn describe(err: &SpannedError) -> String {
+    format!(
+        "line {}, column {}: {}",
+        err.span.start.line,
+        err.span.start.col,
+        redact_quoted(&err.code.to_string())
+    )
+}
+
+fn redact_quoted(msg: &str) -> String {
+    let mut out = String::with_capacity(msg.len());
+    let mut in_quotes = false;
+    for c in msg.chars() {
+        if c == '"' {
+            if in_quotes {
+                out.push_str("…\"");
+            } else {
+                out.push('"');
+            }
+            in_quotes = !in_quotes;
+        } else if !in_quotes {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Parse each item of the list opening at `s[open]` on its own, collecting
+/// the ones that fail into `issues` instead of failing the whole list.
+fn parse_items<T: DeserializeOwned>(
+    s: &str,
+    open: usize,
+    section: &'static str,
+    issues: &mut Vec<DecodeIssue>,
+) -> Vec<T> {
+    let mut out = Vec::new();
+    for (index, (off, item)) in split_items(s, open).into_iter().enumerate() {
+        match ron::from_str::<T>(item) {
+            Ok(v) => out.push(v),
+            Err(e) => {
+                let lead = item.len() - item.trim_start().len();
+                issues.push(DecodeIssue {
+                    section,
+                    index,
+                    line: s[..off + lead].matches('\n').count() + 1,
+                    label: guess_label(item),
+                    error: describe(&e),
+                });
+            }
+        }
+    }
+    out
+}
+
+/// Split the contents of the list/tuple opening at `s[open]` into top-level
+/// items as `(byte offset, text)`, honouring nesting, strings and comments.
+/// An unterminated list yields everything up to the end of input.
+fn split_items(s: &str, open: usize) -> Vec<(usize, &str)> {
+    let bytes = s.as_bytes();
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut item_start = open + 1;
+    let mut i = open + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i + 1 < bytes.len() && !(bytes[i] == b'*' && bytes[i + 1] == b'/') {
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' if depth == 0 => {
+                push_item(s, item_start, i, &mut items);
+                return items;
+            }
+            b')' | b']' | b'}' => depth -= 1,
+            b',' if depth == 0 => {
+                push_item(s, item_start, i, &mut items);
+                item_start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    push_item(s, item_start, s.len(), &mut items);
+    items
+}
+
+fn push_item<'a>(s: &'a str, from: usize, to: usize, items: &mut Vec<(usize, &'a str)>) {
+    if !s[from..to].trim().is_empty() {
+        items.push((from, &s[from..to]));
+    }
+}
+
+/// Best-effort `label: "..."` lookup so a skipped entry can be identified.
+fn guess_label(item: &str) -> Option<String> {
+    let rest = &item[item.find("label")? + "label".len()..];
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+    let rest = rest.strip_prefix('"')?;
+    Some(rest[..rest.find('"')?].to_string())
+}
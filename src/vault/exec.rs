@@ -0,0 +1,180 @@
+use crate::config::app_config::Config;
+use crate::vault::format::{format_entry, EntryFormat};
+use crate::vault::handlers::{resolve_entry, validate_field_sizes, GetField};
+use crate::vault::models::{validate_custom_name, validate_label, VaultEntry};
+use crate::vault::service::{now_secs, VaultService};
+use anyhow::Result;
+use secrecy::SecretString;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+/// One operation of a `kevi exec` request, e.g. `{"op":"get","key":"gh"}`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum ExecOp {
+    Get {
+        key: String,
+        #[serde(default = "default_field")]
+        field: String,
+        /// Include the secret value in the result
+        #[serde(default)]
+        reveal: bool,
+        #[serde(default)]
+        exact: bool,
+    },
+    List {
+        #[serde(default)]
+        query: Option<String>,
+    },
+    Add {
+        label: String,
+        #[serde(default)]
+        username: Option<String>,
+        password: String,
+        #[serde(default)]
+        notes: Option<String>,
+        #[serde(default)]
+        custom: BTreeMap<String, String>,
+    },
+}
+
+fn default_field() -> String {
+    "password".to_string()
+}
+
+impl ExecOp {
+    fn mutates(&self) -> bool {
+        matches!(self, ExecOp::Add { .. })
+    }
+}
+
+/// Results of a request, one JSON object per executed op. Execution stops at
+/// the first failing op (`failed_at`), and then nothing is saved.
+#[derive(Debug)]
+pub struct ExecOutcome {
+    pub results: Vec<Value>,
+    pub failed_at: Option<usize>,
+}
+
+/// Run `ops` in order against a single load of the vault. Requests with
+/// mutations go through `VaultService::batch`, so they are saved once or not at all.
+pub fn execute(service: &VaultService, config: &Config, ops: Vec<ExecOp>) -> Result<ExecOutcome> {
+    if !ops.iter().any(ExecOp::mutates) {
+        let mut entries = service.load()?;
+        return Ok(apply_all(&mut entries, config, ops));
+    }
+    let mut outcome = None;
+    let saved = service.batch(|entries| {
+        let o = apply_all(entries, config, ops);
+        let failed_at = o.failed_at;
+        outcome = Some(o);
+        match failed_at {
+            Some(i) => anyhow::bail!("operation {i} failed"),
+            None => Ok(()),
+        }
+    });
+    match (saved, outcome) {
+        // A failing op aborts the batch on purpose; its results are the answer
+        (_, Some(o)) if o.failed_at.is_some() => Ok(o),
+        (Ok(()), Some(o)) => Ok(o),
+        (Err(e), _) => Err(e),
+        (Ok(()), None) => anyhow::bail!("the vault was saved without running the operations"),
+    }
+}
+
+fn apply_all(entries: &mut Vec<VaultEntry>, config: &Config, ops: Vec<ExecOp>) -> ExecOutcome {
+    let mut results = Vec::with_capacity(ops.len());
+    for (i, op) in ops.into_iter().enumerate() {
+        match apply(entries, config, op) {
+            Ok(v) => results.push(v),
+            Err(e) => {
+                results.push(json!({ "ok": false, "error": e.to_string() }));
+                return ExecOutcome {
+                    results,
+                    failed_at: Some(i),
+                };
+            }
+        }
+    }
+    ExecOutcome {
+        results,
+        failed_at: None,
+    }
+}
+
+fn apply(entries: &mut Vec<VaultEntry>, config: &Config, op: ExecOp) -> Result<Value> {
+    match op {
+        ExecOp::Get {
+            key,
+            field,
+            reveal,
+            exact,
+        } => {
+            let field: GetField = field.parse()?;
+            let entry = resolve_entry(entries, &key, exact)?
+                .ok_or_else(|| anyhow::anyhow!("no entry found with key '{key}'"))?;
+            let mut out = json!({ "ok": true, "label": entry.label, "field": field.to_string() });
+            if let GetField::All = field {
+                let rendered = format_entry(entry, EntryFormat::Json, reveal)?;
+                out["entry"] = serde_json::from_str(&rendered)?;
+                return Ok(out);
+            }
+            let value = field.value_of(entry);
+            out["present"] = json!(value.is_some());
+            if reveal {
+                out["value"] = json!(value);
+            }
+            Ok(out)
+        }
+        ExecOp::List { query } => {
+            let ql = query.map(|q| q.to_lowercase());
+            let labels: Vec<&str> = entries
+                .iter()
+                .filter(|e| {
+                    ql.as_ref()
+                        .is_none_or(|q| e.label.to_lowercase().contains(q))
+                })
+                .map(|e| e.label.as_str())
+                .collect();
+            Ok(json!({ "ok": true, "labels": labels }))
+        }
+        ExecOp::Add {
+            label,
+            username,
+            password,
+            notes,
+            custom,
+        } => {
+            validate_label(&label)?;
+            if entries.iter().any(|e| e.label == label) {
+                anyhow::bail!("entry with label '{label}' already exists");
+            }
+            validate_field_sizes(
+                config,
+                username.as_deref().unwrap_or(""),
+                notes.as_deref().unwrap_or(""),
+            )?;
+            for name in custom.keys() {
+                validate_custom_name(name)?;
+            }
+            let now = now_secs();
+            entries.push(VaultEntry {
+                label: label.clone(),
+                username: username
+                    .filter(|u| !u.is_empty())
+                    .map(|u| SecretString::new(u.into())),
+                password: SecretString::new(password.into()),
+                notes: notes.filter(|n| !n.is_empty()),
+                custom: custom
+                    .into_iter()
+                    .map(|(k, v)| (k, SecretString::new(v.into())))
+                    .collect(),
+                created_at: Some(now),
+                updated_at: Some(now),
+                last_accessed_at: None,
+            });
+            Ok(json!({ "ok": true, "label": label }))
+        }
+    }
+}
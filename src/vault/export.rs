@@ -0,0 +1,71 @@
+use crate::cryptography::primitives::{decrypt_vault, encrypt_vault};
+use crate::vault::models::VaultEntry;
+use crate::vault::service::{ensure_not_truncated, now_secs};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+/// Marker stored in every export payload; a raw vault never carries it.
+pub const EXPORT_FORMAT: &str = "kevi-export";
+/// Highest export payload version this build reads (and the one it writes).
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Payload of an encrypted export: a standard KEVI container whose plaintext
+/// names its format and version instead of being a bare vault document.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: u64,
+    pub entries: Vec<VaultEntry>,
+}
+
+impl ExportBundle {
+    pub fn new(entries: Vec<VaultEntry>) -> Self {
+        Self {
+            format: EXPORT_FORMAT.to_string(),
+            version: EXPORT_VERSION,
+            exported_at: now_secs(),
+            entries,
+        }
+    }
+
+    /// Serialized payload, wiped when dropped.
+    pub fn encode(&self) -> Result<Zeroizing<Vec<u8>>> {
+        Ok(Zeroizing::new(ron::ser::to_string(self)?.into_bytes()))
+    }
+
+    /// Parse a decrypted payload, rejecting anything without the export marker
+    /// (such as a vault file) and versions newer than this build understands.
+    pub fn decode(plain: &[u8]) -> Result<Self> {
+        let bundle = std::str::from_utf8(plain)
+            .ok()
+            .and_then(|s| ron::from_str::<Self>(s).ok())
+            .filter(|b| b.format == EXPORT_FORMAT)
+            .ok_or_else(|| anyhow!("not a kevi export (missing the '{EXPORT_FORMAT}' marker)"))?;
+        if bundle.version > EXPORT_VERSION {
+            anyhow::bail!(
+                "export version {} is newer than this kevi supports ({EXPORT_VERSION})",
+                bundle.version
+            );
+        }
+        Ok(bundle)
+    }
+
+    /// Encrypt under `password` with default KDF params and a fresh salt.
+    pub fn seal(&self, password: &str) -> Result<Vec<u8>> {
+        encrypt_vault(&self.encode()?, password)
+    }
+
+    /// Decrypt and parse an export file's bytes.
+    pub fn open(bytes: &[u8], password: &str) -> Result<Self> {
+        ensure_not_truncated(bytes)?;
+        if !bytes.starts_with(b"KEVI") {
+            anyhow::bail!("not a kevi export: missing KEVI header");
+        }
+        let plain = Zeroizing::new(
+            decrypt_vault(bytes, password).context("Failed to decrypt export (wrong password?)")?,
+        );
+        Self::decode(&plain)
+    }
+}
@@ -0,0 +1,106 @@
+use crate::vault::models::VaultEntry;
+use anyhow::Result;
+use secrecy::ExposeSecret;
+use serde_json::json;
+use std::collections::HashMap;
+
+/// Output formats for dumping a whole entry (`get --field all`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EntryFormat {
+    Dotenv,
+    Json,
+}
+
+/// Render an entry; the password and custom fields are included only when `reveal` is set.
+pub fn format_entry(entry: &VaultEntry, format: EntryFormat, reveal: bool) -> Result<String> {
+    let user = entry
+        .username
+        .as_ref()
+        .map(|u| u.expose_secret().to_string());
+    let password = reveal.then(|| entry.password.expose_secret().to_string());
+    match format {
+        EntryFormat::Dotenv => {
+            let mut lines = vec![format!("KEVI_LABEL={}", dotenv_quote(&entry.label))];
+            if let Some(u) = &user {
+                lines.push(format!("KEVI_USER={}", dotenv_quote(u)));
+            }
+            if let Some(p) = &password {
+                lines.push(format!("KEVI_PASSWORD={}", dotenv_quote(p)));
+            }
+            if let Some(n) = &entry.notes {
+                lines.push(format!("KEVI_NOTES={}", dotenv_quote(n)));
+            }
+            if reveal {
+                let mut seen = HashMap::new();
+                for (name, value) in &entry.custom {
+                    let var = dotenv_name(name);
+                    if let Some(other) = seen.insert(var.clone(), name) {
+                        anyhow::bail!(
+                            "custom fields '{other}' and '{name}' both map to KEVI_CUSTOM_{var}; use --format json"
+                        );
+                    }
+                    lines.push(format!(
+                        "KEVI_CUSTOM_{var}={}",
+                        dotenv_quote(value.expose_secret())
+                    ));
+                }
+            }
+            Ok(lines.join("\n"))
+        }
+        EntryFormat::Json => {
+            let mut obj = json!({ "label": entry.label });
+            if let Some(u) = user {
+                obj["username"] = json!(u);
+            }
+            if let Some(p) = password {
+                obj["password"] = json!(p);
+            }
+            if let Some(n) = &entry.notes {
+                obj["notes"] = json!(n);
+            }
+            if reveal && !entry.custom.is_empty() {
+                let custom: serde_json::Map<String, serde_json::Value> = entry
+                    .custom
+                    .iter()
+                    .map(|(k, v)| (k.clone(), json!(v.expose_secret())))
+                    .collect();
+                obj["custom"] = serde_json::Value::Object(custom);
+            }
+            Ok(serde_json::to_string_pretty(&obj)?)
+        }
+    }
+}
+
+/// Double-quote a value so it stays on one line and is safe both for dotenv
+/// parsers and for `source`-ing in a POSIX shell.
+pub fn dotenv_quote(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '$' => out.push_str("\\$"),
+            '`' => out.push_str("\\`"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Upper-case a custom field name into a shell-safe variable suffix. Distinct
+/// names can collide (`a-b`, `a_b`, `A_B`); callers must check.
+fn dotenv_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
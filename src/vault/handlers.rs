@@ -1,40 +1,322 @@
-use crate::config::app_config::Config;
+use crate::cli::exit::KeviError;
+use crate::cli::output::{spinner_progress, status, stderr_notice, with_spinner};
+use crate::cli::prompt::{
+    block_with_cmd_timeout, is_interactive, preview_generated, require_interactive,
+    require_password_prompt, with_cmd_timeout,
+};
+use crate::config::app_config::{kevi_config_dir, use_backup, Config};
+use crate::cryptography::compare::secrets_equal;
 use crate::cryptography::generator::{
-    estimate_bits_char_mode, estimate_bits_passphrase, strength_label, DefaultPasswordGenerator,
-    SystemRng,
+    estimate_bits_char_mode, estimate_bits_passphrase, estimate_bits_password, generator_rng,
+    infer_policy, strength_label, DefaultPasswordGenerator,
 };
+use crate::cryptography::memlock::{lock_slice, unlock_slice};
 use crate::cryptography::primitives::{
-    derive_key_argon2id, header_fingerprint_excluding_nonce, parse_kevi_header, AEAD_AES256GCM,
+    calibrate_params, decrypt_vault_with_key, default_params, derive_key_argon2id, encrypt_vault,
+    header_fingerprint_excluding_nonce, parse_kevi_header, KdfPreset, KeviHeader, AEAD_AES256GCM,
     KDF_ARGON2ID,
 };
+use crate::cryptography::wordlist::duplicate_words;
 use crate::filesystem::clipboard::{
-    copy_with_ttl, environment_warning, ttl_seconds, SystemClipboardEngine,
+    clear_hook, copy_and_wait, copy_and_wait_for_paste, copy_with_ttl_then,
+    detect_history_managers, environment_warning, history_warning, history_warning_once,
+    restore_previous, ttl_seconds, HoldOutcome, SystemClipboardEngine,
+};
+use crate::filesystem::secure::{
+    atomic_write_secure, audit_permissions, ensure_parent_secure, existing_backups,
+    fix_permissions, wipe_file, write_fifo_once, write_with_backups_n,
 };
 use crate::filesystem::store::FileByteStore;
+use crate::session_management::last_get::{
+    last_get_file_for, load_last_get, save_last_get, LastGet,
+};
 use crate::session_management::resolver::{
-    dk_session_file_for, save_derived_key_session, BypassKeyResolver, CachedKeyResolver,
+    age_key_in_use, default_key_resolver, dk_session_file_for, dk_session_files_for,
+    extend_dk_session, read_dk_session, save_derived_key_session, unlock_ttl_seconds,
+    BypassKeyResolver, SessionOnlyKeyResolver,
 };
 use crate::session_management::session::clear;
-use crate::vault::codec::RonCodec;
-use crate::vault::models::VaultEntry;
-use crate::vault::persistence::save_vault_file;
+use crate::vault::codec::{codec_for, RonCodec};
+use crate::vault::exec::{execute, ExecOp};
+use crate::vault::export::ExportBundle;
+use crate::vault::format::{format_entry, EntryFormat};
+use crate::vault::label_template::LabelTemplate;
+use crate::vault::models::{validate_custom_name, validate_label, VaultDocument, VaultEntry};
+use crate::vault::persistence::save_vault_file_with_params;
 use crate::vault::ports::{ByteStore, GenPolicy, KeyResolver, PasswordGenerator, Rng, VaultCodec};
-use crate::vault::service::VaultService;
+use crate::vault::search::{search_entries, SearchField};
+use crate::vault::service::{now_secs, VaultService};
 use anyhow::{anyhow, Result};
 use inquire::{Confirm, Password, Text};
 use secrecy::{ExposeSecret, SecretBox, SecretString};
+use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashSet;
 use std::env;
 use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::task::spawn_blocking;
+use zeroize::{Zeroize, Zeroizing};
+
+/// Find the entry addressed by `key`. An exact label match always wins; unless
+/// `exact` is set, a unique case-insensitive prefix (or, failing that, substring)
+/// match is accepted. Several candidates are reported as an error.
+pub fn resolve_entry<'e>(
+    entries: &'e [VaultEntry],
+    key: &str,
+    exact: bool,
+) -> Result<Option<&'e VaultEntry>> {
+    if let Some(e) = entries.iter().find(|e| e.label == key) {
+        return Ok(Some(e));
+    }
+    if exact {
+        return Ok(None);
+    }
+    let kl = key.to_lowercase();
+    let mut candidates: Vec<&VaultEntry> = entries
+        .iter()
+        .filter(|e| e.label.to_lowercase().starts_with(&kl))
+        .collect();
+    if candidates.is_empty() {
+        candidates = entries
+            .iter()
+            .filter(|e| e.label.to_lowercase().contains(&kl))
+            .collect();
+    }
+    match candidates.as_slice() {
+        [] => Ok(None),
+        [one] => Ok(Some(one)),
+        many => {
+            let labels: Vec<&str> = many.iter().map(|e| e.label.as_str()).collect();
+            anyhow::bail!(
+                "key '{key}' matches several entries: {} (use a longer key or --exact)",
+                labels.join(", ")
+            )
+        }
+    }
+}
+
+pub const DEFAULT_MAX_FIELD_BYTES: usize = 64 * 1024;
 
-#[derive(Copy, Clone, Debug)]
+/// Enforce the configured byte limits on username and notes so an accidental
+/// paste cannot bloat the vault.
+pub fn validate_field_sizes(config: &Config, username: &str, notes: &str) -> Result<()> {
+    let max_user = config.max_username_bytes.unwrap_or(DEFAULT_MAX_FIELD_BYTES);
+    let max_notes = config.max_notes_bytes.unwrap_or(DEFAULT_MAX_FIELD_BYTES);
+    if username.len() > max_user {
+        anyhow::bail!(
+            "username is {} bytes, over the {max_user}-byte limit (max_username_bytes)",
+            username.len()
+        );
+    }
+    if notes.len() > max_notes {
+        anyhow::bail!(
+            "notes are {} bytes, over the {max_notes}-byte limit (max_notes_bytes)",
+            notes.len()
+        );
+    }
+    Ok(())
+}
+
+/// The vault file store with the configured backup count and naming, so every
+/// writer rotates the same `<vault>.N` files.
+pub fn vault_store(config: &Config) -> FileByteStore {
+    FileByteStore::new_with_backups(config.vault_path.clone(), config.backups.unwrap_or(2))
+        .with_backup_naming(config.backup_naming())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum GetField {
     Password,
     User,
     Notes,
+    All,
+    /// A named entry of `VaultEntry::custom`
+    Custom(String),
+}
+
+impl GetField {
+    /// The field's value in `entry`; `None` when unset, and always for `All`.
+    pub fn value_of(&self, entry: &VaultEntry) -> Option<String> {
+        match self {
+            GetField::Password => Some(entry.password.expose_secret().to_string()),
+            GetField::User => entry
+                .username
+                .as_ref()
+                .map(|u| u.expose_secret().to_string()),
+            GetField::Notes => entry.notes.clone(),
+            GetField::All => None,
+            GetField::Custom(name) => entry
+                .custom
+                .get(name)
+                .map(|v| v.expose_secret().to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for GetField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GetField::Password => f.write_str("password"),
+            GetField::User => f.write_str("user"),
+            GetField::Notes => f.write_str("notes"),
+            GetField::All => f.write_str("all"),
+            GetField::Custom(name) => write!(f, "custom:{name}"),
+        }
+    }
+}
+
+impl std::str::FromStr for GetField {
+    type Err = anyhow::Error;
+
+    /// Inverse of `Display`: `password`, `user`, `notes`, `all` or `custom:<name>`.
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "password" => Ok(GetField::Password),
+            "user" => Ok(GetField::User),
+            "notes" => Ok(GetField::Notes),
+            "all" => Ok(GetField::All),
+            other => match other.strip_prefix("custom:") {
+                Some(name) => {
+                    validate_custom_name(name)?;
+                    Ok(GetField::Custom(name.to_string()))
+                }
+                None => anyhow::bail!(
+                    "expected password, user, notes, all or custom:<name>, got '{other}'"
+                ),
+            },
+        }
+    }
+}
+
+/// Output styles for `list`. JSON styles carry the `--json-fields` selection;
+/// empty means the label (plus username with `--show-users`).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListOutput {
+    Text,
+    /// Pretty-printed JSON array
+    Json {
+        fields: Vec<ListJsonField>,
+    },
+    /// Newline-delimited compact JSON objects (NDJSON)
+    JsonLines {
+        fields: Vec<ListJsonField>,
+    },
+    /// Bare labels, newline- or NUL-terminated; stable for scripts
+    Porcelain {
+        nul: bool,
+    },
+}
+
+/// Non-secret entry fields `list --json-fields` can emit. Passwords and custom
+/// field values are never listed; custom fields appear by name only.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ListJsonField {
+    Label,
+    Username,
+    Notes,
+    CreatedAt,
+    UpdatedAt,
+    LastAccessedAt,
+    CustomFields,
+}
+
+impl ListJsonField {
+    fn key(self) -> &'static str {
+        match self {
+            Self::Label => "label",
+            Self::Username => "username",
+            Self::Notes => "notes",
+            Self::CreatedAt => "created_at",
+            Self::UpdatedAt => "updated_at",
+            Self::LastAccessedAt => "last_accessed_at",
+            Self::CustomFields => "custom_fields",
+        }
+    }
+
+    /// This field of `entry` as JSON; null when the entry doesn't have it.
+    fn value_of(self, entry: &VaultEntry) -> serde_json::Value {
+        match self {
+            Self::Label => json!(entry.label),
+            Self::Username => json!(entry.username.as_ref().map(|u| u.expose_secret())),
+            Self::Notes => json!(entry.notes),
+            Self::CreatedAt => json!(entry.created_at),
+            Self::UpdatedAt => json!(entry.updated_at),
+            Self::LastAccessedAt => json!(entry.last_accessed_at),
+            Self::CustomFields => json!(entry.custom.keys().collect::<Vec<_>>()),
+        }
+    }
+}
+
+/// How `init` picks the Argon2 params written to the header.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InitKdf {
+    Default,
+    /// Benchmark this machine and stay under the target derivation time
+    Calibrate(Duration),
+    Preset(KdfPreset),
+}
+
+/// Sort keys for `list --sort`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ListSort {
+    Label,
+    User,
+    Created,
+    Updated,
+    /// Most recently used first (see [`VaultEntry::recency`])
+    Recent,
+}
+
+/// Sort entries in place (stable, so ties keep vault order); label and user
+/// compare case-insensitively, and entries without timestamps sort first
+/// (last for `Recent`, which puts the newest first).
+pub fn sort_entries(entries: &mut [VaultEntry], sort: Option<ListSort>, reverse: bool) {
+    match sort {
+        Some(ListSort::Label) => entries.sort_by_cached_key(|e| e.label.to_lowercase()),
+        Some(ListSort::User) => entries.sort_by_cached_key(|e| {
+            e.username
+                .as_ref()
+                .map(|u| u.expose_secret().to_lowercase())
+                .unwrap_or_default()
+        }),
+        Some(ListSort::Created) => entries.sort_by_key(|e| e.created_at.unwrap_or(0)),
+        Some(ListSort::Updated) => {
+            entries.sort_by_key(|e| e.updated_at.or(e.created_at).unwrap_or(0))
+        }
+        Some(ListSort::Recent) => entries.sort_by_key(|e| std::cmp::Reverse(e.recency())),
+        None => {}
+    }
+    if reverse {
+        entries.reverse();
+    }
+}
+
+/// Fields `list --missing` and `info --incomplete` check for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MissingField {
+    User,
+    Notes,
+    Custom,
+}
+
+impl MissingField {
+    pub const ALL: [MissingField; 3] = [Self::User, Self::Notes, Self::Custom];
+
+    /// Whether `entry` lacks this field; an empty username or note counts as missing.
+    pub fn is_missing(self, entry: &VaultEntry) -> bool {
+        match self {
+            Self::User => entry
+                .username
+                .as_ref()
+                .is_none_or(|u| u.expose_secret().is_empty()),
+            Self::Notes => entry.notes.as_ref().is_none_or(|n| n.is_empty()),
+            Self::Custom => entry.custom.is_empty(),
+        }
+    }
 }
 
 pub struct Vault<'a> {
@@ -46,104 +328,447 @@ impl<'a> Vault<'a> {
     pub fn create(config: &'a Config) -> Self {
         // Compose default adapters
         let backups = config.backups.unwrap_or(2);
-        let store: Arc<dyn ByteStore> = Arc::new(FileByteStore::new_with_backups(
+        let store: Arc<dyn ByteStore> = Arc::new(vault_store(config));
+        let codec = codec_for(config.compact_storage == Some(true));
+        log::debug!(
+            "vault {} (backups: {backups}, compact: {})",
+            config.vault_path.display(),
+            config.compact_storage == Some(true)
+        );
+        let key_resolver: Arc<dyn KeyResolver> = default_key_resolver(
             config.vault_path.clone(),
-            backups,
-        ));
-        let codec: Arc<dyn VaultCodec> = Arc::new(RonCodec);
-        let key_resolver: Arc<dyn KeyResolver> =
-            Arc::new(CachedKeyResolver::new(config.vault_path.clone()));
-        let service = Arc::new(VaultService::new(store, codec, key_resolver));
+            config.session_sliding(),
+            Some(spinner_progress),
+        );
+        let mut service = VaultService::new(store, codec, key_resolver);
+        if let Some(days) = config.trash_retention_days {
+            service = service.with_trash_retention_days(days);
+        }
+        if config.stream_encryption == Some(true) {
+            service = service.with_stream_encryption(true);
+        }
+        let fallbacks: Vec<(String, Arc<dyn ByteStore>)> =
+            existing_backups(&config.vault_path, &config.backup_naming())
+                .into_iter()
+                .map(|p| {
+                    let store: Arc<dyn ByteStore> =
+                        Arc::new(FileByteStore::new_with_backups(p.clone(), 0));
+                    (p.display().to_string(), store)
+                })
+                .collect();
+        service = service
+            .with_backups(fallbacks, use_backup())
+            .with_notice(stderr_notice);
 
-        Vault { config, service }
+        Vault {
+            config,
+            service: Arc::new(service),
+        }
     }
 
-    pub async fn handle_header(&self) -> Result<()> {
+    /// Print the plaintext header. With `verify`, also resolve the key (may
+    /// prompt) and report whether the vault decrypts with it; on failure the
+    /// cached session is dropped.
+    pub async fn handle_header(&self, verify: bool) -> Result<()> {
         let path = self.config.vault_path.clone();
         let bytes = spawn_blocking(move || fs::read(&path))
             .await
             .map_err(|_| anyhow!("task join error"))??;
-        match parse_kevi_header(&bytes) {
-            Ok((hdr, _off)) => {
-                let kdf = match hdr.kdf_id {
-                    KDF_ARGON2ID => "Argon2id",
-                    other => {
-                        let _ = other;
-                        "Unknown"
-                    }
-                };
-                let aead = match hdr.aead_id {
-                    AEAD_AES256GCM => "AES-256-GCM",
-                    other => {
-                        let _ = other;
-                        "Unknown"
-                    }
-                };
-                let salt_hex: String = hdr.salt.iter().map(|b| format!("{b:02x}")).collect();
-                let nonce_hex: String = hdr.nonce.iter().map(|b| format!("{b:02x}")).collect();
-                println!("KEVI header:");
-                println!("  version: {}", hdr.version);
-                println!("  kdf: {} ({})", kdf, hdr.kdf_id);
-                println!("  aead: {} ({})", aead, hdr.aead_id);
-                println!("  argon2 m_cost_kib: {}", hdr.m_cost_kib);
-                println!("  argon2 t_cost: {}", hdr.t_cost);
-                println!("  argon2 p_lanes: {}", hdr.p_lanes);
-                println!("  salt: {salt_hex}");
-                println!("  nonce: {nonce_hex}");
+        let hdr = match parse_kevi_header(&bytes) {
+            Ok((hdr, _off)) => hdr,
+            Err(e) => return Err(anyhow!("Failed to parse header: {}", e)),
+        };
+        print_header(&hdr);
+        if !verify {
+            return Ok(());
+        }
+        let svc = self.service.clone();
+        let checked = spawn_blocking(move || svc.verify_decrypt())
+            .await
+            .map_err(|_| anyhow!("task join error"))?;
+        match checked {
+            Ok(()) => {
+                println!("decrypt: OK");
                 Ok(())
             }
-            Err(e) => Err(anyhow!("Failed to parse header: {}", e)),
+            Err(e) => {
+                println!("decrypt: FAILED");
+                // Don't keep a cached key that doesn't open this vault
+                clear(&dk_session_file_for(&self.config.vault_path))?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Write all entries (not the trash) to `out` as an encrypted export. With
+    /// `new_password` the export is sealed under a password asked for now;
+    /// otherwise under the vault's key and salt, so the master password opens it.
+    /// An age-wrapped vault has no master password, so it needs `new_password`.
+    pub async fn handle_export_encrypted(&self, out: PathBuf, new_password: bool) -> Result<()> {
+        if same_file(&out, &self.config.vault_path) {
+            anyhow::bail!("refusing to overwrite the vault file with an export");
+        }
+        if !new_password && age_key_in_use() {
+            anyhow::bail!(
+                "this vault's key comes from an age identity, which import cannot use; pass --new-password to give the export its own password"
+            );
+        }
+        let password = if new_password {
+            Some(
+                spawn_blocking(new_export_password)
+                    .await
+                    .map_err(|_| anyhow!("task join error"))??,
+            )
+        } else {
+            None
+        };
+        let svc = self.service.clone();
+        let path = out.clone();
+        let count = spawn_blocking(move || -> Result<usize> {
+            let bundle = ExportBundle::new(svc.load()?);
+            let sealed = match &password {
+                Some(pw) => bundle.seal(pw)?,
+                None => svc.seal_with_vault_key(&bundle.encode()?)?,
+            };
+            atomic_write_secure(&path, &sealed)?;
+            Ok(bundle.entries.len())
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+        println!(
+            "{}",
+            status(
+                "📦",
+                format_args!("Exported {count} entries to {}", out.display())
+            )
+        );
+        Ok(())
+    }
+
+    /// Add the entries of an encrypted export. Labels already in the vault are
+    /// skipped and reported; nothing is written when every label exists.
+    pub async fn handle_import_encrypted(&self, file: PathBuf) -> Result<()> {
+        let password = spawn_blocking(export_password)
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        let path = file.clone();
+        let bundle = spawn_blocking(move || -> Result<ExportBundle> {
+            let bytes = fs::read(&path)?;
+            with_spinner("Deriving key…", || ExportBundle::open(&bytes, &password))
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+
+        let svc = self.service.clone();
+        let (added, skipped) = spawn_blocking(move || -> Result<(usize, Vec<String>)> {
+            let existing: Vec<String> = svc.load()?.into_iter().map(|e| e.label).collect();
+            let (skipped, new): (Vec<_>, Vec<_>) = bundle
+                .entries
+                .into_iter()
+                .partition(|e| existing.contains(&e.label));
+            let added = new.len();
+            if added > 0 {
+                svc.batch(|entries| {
+                    entries.extend(new);
+                    Ok(())
+                })?;
+            }
+            Ok((added, skipped.into_iter().map(|e| e.label).collect()))
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+
+        println!(
+            "{}",
+            status(
+                "📥",
+                format_args!("Imported {added} entries from {}", file.display())
+            )
+        );
+        if !skipped.is_empty() {
+            eprintln!(
+                "{}",
+                status(
+                    "⚠️",
+                    format_args!("Skipped existing labels: {}", skipped.join(", "))
+                )
+            );
+        }
+        Ok(())
+    }
+
+    /// `reauthenticate_for_reveal` off the async runtime.
+    async fn reauthenticate(&self) -> Result<()> {
+        let config = self.config.clone();
+        spawn_blocking(move || reauthenticate_for_reveal(&config))
+            .await
+            .map_err(|_| anyhow!("task join error"))?
+    }
+
+    /// Advisory on stderr when the stored password is longer than `warn_password_len`,
+    /// since some sites silently truncate long passwords.
+    fn warn_password_length(&self, entry: &VaultEntry) {
+        let Some(limit) = self.config.warn_password_len else {
+            return;
+        };
+        let len = entry.password.expose_secret().chars().count();
+        if len > limit {
+            eprintln!(
+                "{}",
+                status(
+                    "⚠️",
+                    format_args!(
+                        "Password of '{}' is {len} characters, over warn_password_len ({limit}); check the site does not truncate it",
+                        entry.label
+                    )
+                )
+            );
+        }
+    }
+
+    /// With `track_access`, stamp `label` as just used. This costs a save, so a
+    /// failure only warns instead of failing the read that triggered it.
+    async fn record_access(&self, label: &str) {
+        if self.config.track_access != Some(true) {
+            return;
+        }
+        let svc = self.service.clone();
+        let label = label.to_string();
+        let res = spawn_blocking(move || svc.record_access(&label))
+            .await
+            .map_err(|_| anyhow!("task join error"))
+            .and_then(|r| r);
+        if let Err(e) = res {
+            eprintln!(
+                "{}",
+                status("⚠️", format_args!("Could not record access: {e}"))
+            );
+        }
+    }
+
+    /// Load entries for `get`, optionally bypassing the session cache via a temp resolver.
+    async fn load_for_get(&self, once: bool) -> Result<Vec<VaultEntry>> {
+        let svc = if once {
+            let store: Arc<dyn ByteStore> = Arc::new(vault_store(self.config));
+            let codec: Arc<dyn VaultCodec> = Arc::new(RonCodec);
+            let resolver: Arc<dyn KeyResolver> =
+                Arc::new(BypassKeyResolver::new().with_progress(Some(spinner_progress)));
+            Arc::new(VaultService::new(store, codec, resolver))
+        } else {
+            self.service.clone()
+        };
+        spawn_blocking(move || svc.load())
+            .await
+            .map_err(|_| anyhow!("task join error"))?
+    }
+
+    /// Shell completion: print labels starting with `prefix`, one per line, only
+    /// when an unlocked session exists. A locked or unreadable vault prints nothing
+    /// and still succeeds, so completion never prompts or reports errors.
+    pub async fn handle_complete_labels(&self, prefix: &str) -> Result<()> {
+        let store: Arc<dyn ByteStore> = Arc::new(vault_store(self.config));
+        let codec: Arc<dyn VaultCodec> = Arc::new(RonCodec);
+        let resolver: Arc<dyn KeyResolver> =
+            Arc::new(SessionOnlyKeyResolver::new(self.config.vault_path.clone()));
+        let svc = VaultService::new(store, codec, resolver);
+        let Ok(Ok(entries)) = spawn_blocking(move || svc.load()).await else {
+            return Ok(());
+        };
+        for entry in entries.iter().filter(|e| e.label.starts_with(prefix)) {
+            println!("{}", entry.label);
         }
+        Ok(())
+    }
+
+    /// `get --keys-file`: print `field` of each label listed in `path` (one per
+    /// line, `-` for stdin) in order, from a single unlock. Labels that do not
+    /// resolve are reported on stderr and make the command fail after the rest print.
+    pub async fn handle_get_keys_file(
+        &self,
+        path: &Path,
+        field: GetField,
+        once: bool,
+        exact: bool,
+        print0: bool,
+    ) -> Result<()> {
+        if let GetField::All = field {
+            anyhow::bail!("--keys-file needs a single field, not --field all");
+        }
+        let listing = if path == Path::new("-") {
+            std::io::read_to_string(std::io::stdin())?
+        } else {
+            fs::read_to_string(path)
+                .map_err(|e| anyhow!("cannot read keys file {}: {e}", path.display()))?
+        };
+        let keys: Vec<&str> = listing
+            .lines()
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .collect();
+        let vault = self.load_for_get(once).await?;
+
+        let mut out = std::io::stdout().lock();
+        let mut failed = 0usize;
+        for key in &keys {
+            let problem = match resolve_entry(&vault, key, exact) {
+                Ok(Some(entry)) => match field.value_of(entry) {
+                    Some(value) => {
+                        let value = Zeroizing::new(value);
+                        out.write_all(value.as_bytes())?;
+                        out.write_all(if print0 { b"\0" } else { b"\n" })?;
+                        continue;
+                    }
+                    None => format!("field {field} is empty for '{}'", entry.label),
+                },
+                Ok(None) => format!("no entry found with key '{key}'"),
+                Err(e) => e.to_string(),
+            };
+            failed += 1;
+            eprintln!("{}", status("❌", problem));
+        }
+        out.flush()?;
+        if failed > 0 {
+            anyhow::bail!("{failed} of {} keys could not be printed", keys.len());
+        }
+        Ok(())
     }
 
-    pub async fn handle_get(
+    /// `get --measure`: print the estimated strength of the stored password,
+    /// never the password. A missing entry or password is an error.
+    pub async fn handle_measure(
         &self,
         key: &str,
         field: GetField,
-        no_copy: bool,
-        ttl_override: Option<u64>,
-        echo: bool,
         once: bool,
+        exact: bool,
     ) -> Result<()> {
-        // Load entries, optionally bypassing session cache for this call using a temp resolver
-        let vault = if once {
-            let store: Arc<dyn ByteStore> =
-                Arc::new(FileByteStore::new(self.config.vault_path.clone()));
-            let codec: Arc<dyn VaultCodec> = Arc::new(RonCodec);
-            let resolver: Arc<dyn KeyResolver> = Arc::new(BypassKeyResolver::new());
-            let svc = Arc::new(VaultService::new(store, codec, resolver));
-            spawn_blocking(move || svc.load())
-                .await
-                .map_err(|_| anyhow!("task join error"))??
+        if field != GetField::Password {
+            anyhow::bail!("--measure only works with --field password");
+        }
+        let vault = self.load_for_get(once).await?;
+        let Some(entry) = resolve_entry(&vault, key, exact)? else {
+            return Err(KeviError::NotFound(key.to_string()).into());
+        };
+        let password = entry.password.expose_secret();
+        if password.is_empty() {
+            anyhow::bail!("'{}' has no password", entry.label);
+        }
+        let bits = estimate_bits_password(password);
+        println!("{bits:.0} bits ({})", strength_label(bits));
+        Ok(())
+    }
+
+    pub async fn handle_get(&self, key: &str, opts: GetOptions) -> Result<()> {
+        let GetOptions {
+            field,
+            field_fallback,
+            no_copy,
+            ttl: ttl_override,
+            echo,
+            once,
+            exact,
+            format,
+            reveal,
+            raw,
+            print0,
+            wait,
+            verbose,
+            to_file,
+            to_fifo,
+            clear_on_paste,
+            autotype,
+        } = opts;
+        if reveal {
+            self.reauthenticate().await?;
+        }
+        let vault = self.load_for_get(once).await?;
+        let Some(entry) = resolve_entry(&vault, key, exact)? else {
+            return Err(KeviError::NotFound(key.to_string()).into());
+        };
+        // `--once` must not leave a cached key behind, which the extra save would
+        if !once {
+            self.record_access(&entry.label).await;
+        }
+
+        // Fallback chain: the first field that is set and non-empty stands in for `field`
+        let field = if field_fallback.is_empty() {
+            field
         } else {
-            let svc = self.service.clone();
-            spawn_blocking(move || svc.load())
-                .await
-                .map_err(|_| anyhow!("task join error"))??
+            let tried = field_fallback
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            let Some(found) = field_fallback
+                .into_iter()
+                .find(|f| f.value_of(entry).is_some_and(|v| !v.is_empty()))
+            else {
+                return Err(KeviError::FieldNotSet {
+                    field: format!("value among {tried}"),
+                    label: entry.label.clone(),
+                }
+                .into());
+            };
+            eprintln!(
+                "{}",
+                status("↪️", format_args!("using {found} of {}", entry.label))
+            );
+            found
         };
-        let entry = match vault.iter().find(|e| e.label == key) {
-            Some(e) => e,
-            None => {
-                println!("❌ No entry found with key '{key}'");
-                return Ok(());
+
+        if let GetField::Password = field {
+            self.warn_password_length(entry);
+        }
+
+        // Whole-entry dump: printed (or written out), never copied
+        if let GetField::All = field {
+            if autotype {
+                anyhow::bail!("--type needs a single field, not --field all");
             }
-        };
+            let rendered = format_entry(entry, format, reveal)?;
+            if let Some(target) = to_file.or(to_fifo.clone()) {
+                let what = format!("entry {}", entry.label);
+                return self
+                    .write_out(rendered, target, to_fifo.is_some(), what)
+                    .await;
+            }
+            println!("{rendered}");
+            return Ok(());
+        }
 
         // Extract selected field as string (without leaking by default)
-        let selected: Option<String> = match field {
-            GetField::Password => Some(entry.password.expose_secret().to_string()),
-            GetField::User => entry
-                .username
-                .as_ref()
-                .map(|u| u.expose_secret().to_string()),
-            GetField::Notes => entry.notes.clone(),
-        };
+        let selected = field.value_of(entry);
 
         let Some(value) = selected else {
-            println!("❌ Field is empty for '{key}'");
-            return Ok(());
+            return Err(KeviError::FieldNotSet {
+                field: field.to_string(),
+                label: entry.label.clone(),
+            }
+            .into());
         };
 
+        // Plaintext file or pipe for tools that read credentials from a path
+        if let Some(target) = to_file.or(to_fifo.clone()) {
+            let what = format!("{field} of {}", entry.label);
+            return self.write_out(value, target, to_fifo.is_some(), what).await;
+        }
+
+        // Exact bytes for scripts: no trailing newline (or a NUL terminator), never copied
+        if raw || print0 {
+            let mut out = std::io::stdout().lock();
+            out.write_all(value.as_bytes())?;
+            if print0 {
+                out.write_all(b"\0")?;
+            }
+            out.flush()?;
+            return Ok(());
+        }
+
+        // Typed into the focused window; never falls back to the clipboard
+        if autotype {
+            return auto_type(value).await;
+        }
+
         // Echo to stdout if requested
         if echo {
             println!("{value}");
@@ -161,63 +786,343 @@ impl<'a> Vault<'a> {
         let ttl_secs = ttl_seconds(self.config, ttl_override);
         let ttl = Duration::from_secs(ttl_secs);
 
+        // Remember what was copied (parameters only) so `kevi repeat` can replay it
+        let last = LastGet {
+            label: entry.label.clone(),
+            field: field.clone(),
+            ttl: ttl_override,
+        };
+        let last_path = last_get_file_for(&self.config.vault_path);
+        if let Err(e) = save_last_get(&last_path, &last) {
+            eprintln!(
+                "{}",
+                status("⚠️", format_args!("Could not record last get: {e}"))
+            );
+        }
+
+        // Watching for a paste needs a live process, so clear-on-paste always holds
+        let clear_on_paste = clear_on_paste || self.config.clipboard_clear_on_paste == Some(true);
+        if wait || clear_on_paste {
+            return copy_and_hold(self.config, value, ttl, clear_on_paste).await;
+        }
+
         // Copy to clipboard with TTL
-        if let Some(warn) = environment_warning() {
-            eprintln!("⚠️ {warn}");
+        // Successful copy: do not print secrets or confirmations to stdout by default.
+        let copied = copy_to_clipboard(self.config, value, ttl).await?;
+        if copied && (verbose || self.config.get_verbose == Some(true)) {
+            eprintln!(
+                "{}",
+                status(
+                    "📋",
+                    format_args!("copied {field} of {} (clears in {ttl_secs}s)", entry.label)
+                )
+            );
         }
-        match SystemClipboardEngine::new() {
-            Ok(engine_impl) => {
-                let engine =
-                    Arc::new(engine_impl) as Arc<dyn crate::filesystem::clipboard::ClipboardEngine>;
-                let secret = SecretString::new(value.into());
-                if let Err(e) = copy_with_ttl(engine, &secret, ttl) {
-                    eprintln!("⚠️ Failed to copy to clipboard: {e}");
-                } else {
-                    // Successful copy: do not print secrets or confirmations to stdout by default.
-                }
-            }
-            Err(e) => {
-                eprintln!("⚠️ Clipboard not available: {e}");
+
+        Ok(())
+    }
+
+    /// Write `value` exactly (no trailing newline) to a 0600 file, or once to a named pipe.
+    async fn write_out(
+        &self,
+        value: String,
+        target: PathBuf,
+        fifo: bool,
+        what: String,
+    ) -> Result<()> {
+        if !fifo && same_file(&target, &self.config.vault_path) {
+            anyhow::bail!("refusing to overwrite the vault file with a secret");
+        }
+        let value = Zeroizing::new(value);
+        let path = target.clone();
+        spawn_blocking(move || {
+            if fifo {
+                write_fifo_once(&path, value.as_bytes())
+            } else {
+                atomic_write_secure(&path, value.as_bytes())
             }
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+        eprintln!(
+            "{}",
+            status("📝", format_args!("Wrote {what} to {}", target.display()))
+        );
+        Ok(())
+    }
+
+    /// Re-run the last clipboard `get` recorded for this vault.
+    pub async fn handle_repeat(&self) -> Result<()> {
+        let path = last_get_file_for(&self.config.vault_path);
+        let last = spawn_blocking(move || load_last_get(&path))
+            .await
+            .map_err(|_| anyhow!("task join error"))??
+            .ok_or_else(|| anyhow!("nothing to repeat; run `kevi get <label>` first"))?;
+        let opts = GetOptions {
+            field: last.field,
+            field_fallback: Vec::new(),
+            no_copy: false,
+            ttl: last.ttl,
+            echo: false,
+            once: false,
+            exact: true,
+            format: EntryFormat::Dotenv,
+            reveal: false,
+            raw: false,
+            print0: false,
+            wait: false,
+            verbose: false,
+            to_file: None,
+            to_fifo: None,
+            clear_on_paste: false,
+            autotype: false,
+        };
+        self.handle_get(&last.label, opts).await
+    }
+
+    /// Advisory checks of the local environment; needs no password and reads no secrets.
+    pub async fn handle_doctor(&self) -> Result<()> {
+        let path = self.config.vault_path.clone();
+        let exists = path.exists();
+        println!(
+            "Vault: {} ({})",
+            path.display(),
+            if exists { "exists" } else { "not found" }
+        );
+        match environment_warning() {
+            Some(warn) => println!("{}", status("⚠️", warn)),
+            None => println!("{}", status("✅", "Clipboard environment looks usable.")),
+        }
+        let managers = spawn_blocking(detect_history_managers)
+            .await
+            .map_err(|_| anyhow!("task join error"))?;
+        match history_warning(&managers) {
+            Some(warn) => println!("{}", status("⚠️", warn)),
+            None => println!(
+                "{}",
+                status("✅", "No history-keeping clipboard manager detected.")
+            ),
+        }
+        let loose = audit_permissions(&self.config.vault_path, &self.config.backup_naming());
+        if loose.is_empty() {
+            println!("{}", status("✅", "Vault files are private to your user."));
+        }
+        for item in &loose {
+            println!(
+                "{}",
+                status(
+                    "⚠️",
+                    format_args!(
+                        "{} has mode {:o}, expected {:o}; run `kevi fix-perms`",
+                        item.path.display(),
+                        item.mode,
+                        item.wanted
+                    )
+                )
+            );
+        }
+        #[cfg(feature = "autotype")]
+        match crate::filesystem::autotype::detect_tool() {
+            Ok(tool) => println!(
+                "{}",
+                status(
+                    "✅",
+                    format_args!("Auto-type (get --type) available via {}.", tool.program())
+                )
+            ),
+            Err(e) => println!(
+                "{}",
+                status("⚠️", format_args!("Auto-type unavailable: {e}"))
+            ),
         }
+        #[cfg(not(feature = "autotype"))]
+        println!(
+            "{}",
+            status(
+                "ℹ️",
+                "Auto-type (get --type) not built in; enable the `autotype` feature."
+            )
+        );
+        Ok(())
+    }
+
+    /// Restrict the vault, its backups and sidecars to 0600 and its directory to 0700.
+    pub async fn handle_fix_perms(&self) -> Result<()> {
+        let path = self.config.vault_path.clone();
+        let naming = self.config.backup_naming();
+        let fixed = spawn_blocking(move || fix_permissions(&path, &naming))
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        if fixed.is_empty() {
+            println!(
+                "{}",
+                status("✅", "Permissions already private; nothing to fix.")
+            );
+        }
+        for item in &fixed {
+            println!(
+                "{}",
+                status(
+                    "🔒",
+                    format_args!(
+                        "{}: {:o} -> {:o}",
+                        item.path.display(),
+                        item.mode,
+                        item.wanted
+                    )
+                )
+            );
+        }
+        Ok(())
+    }
+
+    /// Vault summary without secrets. With `incomplete`, also how many entries
+    /// lack each optional field (see `list --missing`).
+    pub async fn handle_info(&self, incomplete: bool) -> Result<()> {
+        let path = self.config.vault_path.clone();
+        let bytes = spawn_blocking(move || fs::read(&path))
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        let (hdr, _off) =
+            parse_kevi_header(&bytes).map_err(|e| anyhow!("Failed to parse header: {}", e))?;
+        let svc = self.service.clone();
+        let doc = spawn_blocking(move || svc.load_document())
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+
+        let kdf = if hdr.kdf_id == KDF_ARGON2ID {
+            "Argon2id"
+        } else {
+            "Unknown"
+        };
+        let aead = if hdr.aead_id == AEAD_AES256GCM {
+            "AES-256-GCM"
+        } else {
+            "Unknown"
+        };
+        let with_user = doc
+            .entries
+            .iter()
+            .filter(|e| {
+                e.username
+                    .as_ref()
+                    .is_some_and(|u| !u.expose_secret().is_empty())
+            })
+            .count();
+        let with_notes = doc
+            .entries
+            .iter()
+            .filter(|e| e.notes.as_ref().is_some_and(|n| !n.is_empty()))
+            .count();
+        let bits: Vec<f64> = doc
+            .entries
+            .iter()
+            .map(|e| estimate_bits_password(e.password.expose_secret()))
+            .collect();
 
+        println!("Vault: {}", self.config.vault_path.display());
+        println!(
+            "  format: v{}, {kdf} (m_cost_kib={}, t_cost={}, p_lanes={}), {aead}",
+            hdr.version, hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes
+        );
+        println!("Entries: {}", doc.entries.len());
+        println!("  with username: {with_user}");
+        println!("  with notes:    {with_notes}");
+        println!("  in trash:      {}", doc.trash.len());
+        // Entries from before timestamps were recorded have no updated_at
+        let updated = doc.entries.iter().filter_map(|e| e.updated_at);
+        if let (Some(oldest), Some(newest)) = (updated.clone().min(), updated.max()) {
+            let now = now_secs();
+            let ago = |t: u64| format_age(now.saturating_sub(t));
+            println!("Last updated:");
+            println!("  oldest:        {oldest} ({} ago)", ago(oldest));
+            println!("  newest:        {newest} ({} ago)", ago(newest));
+        }
+        if incomplete {
+            let lacking = |f: MissingField| doc.entries.iter().filter(|e| f.is_missing(e)).count();
+            let any = doc
+                .entries
+                .iter()
+                .filter(|e| MissingField::ALL.iter().any(|f| f.is_missing(e)))
+                .count();
+            println!("Incomplete: {any}");
+            println!("  no username:   {}", lacking(MissingField::User));
+            println!("  no notes:      {}", lacking(MissingField::Notes));
+            println!("  no custom:     {}", lacking(MissingField::Custom));
+        }
+        if !bits.is_empty() {
+            let avg = bits.iter().sum::<f64>() / bits.len() as f64;
+            let min = bits.iter().cloned().fold(f64::INFINITY, f64::min);
+            println!("Password strength (estimated):");
+            println!("  average: {avg:.0} bits ({})", strength_label(avg));
+            println!("  weakest: {min:.0} bits ({})", strength_label(min));
+        }
         Ok(())
     }
 
-    pub async fn handle_show(&self, key: &str, reveal_password: bool) -> Result<()> {
+    /// Print an entry's details. `copy` puts one field (password or username) on the
+    /// clipboard; with `json` the details are a JSON object and status goes to stderr.
+    pub async fn handle_show(
+        &self,
+        key: &str,
+        reveal_password: bool,
+        copy: Option<GetField>,
+        exact: bool,
+        json: bool,
+    ) -> Result<()> {
+        if reveal_password {
+            self.reauthenticate().await?;
+        }
         let svc = self.service.clone();
         let entries = spawn_blocking(move || svc.load())
             .await
             .map_err(|_| anyhow!("task join error"))??;
 
-        if let Some(entry) = entries.iter().find(|e| e.label == key) {
-            println!("Label:    {}", entry.label);
-            if let Some(user) = &entry.username {
-                println!("Username: {}", user.expose_secret());
-            } else {
-                println!("Username: (none)");
-            }
-            if let Some(notes) = &entry.notes {
-                println!("Notes:    {notes}");
-            } else {
-                println!("Notes:    (none)");
-            }
+        let Some(entry) = resolve_entry(&entries, key, exact)? else {
+            return Err(KeviError::NotFound(key.to_string()).into());
+        };
+        self.record_access(&entry.label).await;
+        self.warn_password_length(entry);
+        if json {
+            println!(
+                "{}",
+                format_entry(entry, EntryFormat::Json, reveal_password)?
+            );
+        } else {
+            print_entry_details(entry, reveal_password, " (use --reveal-password to show)");
+        }
 
-            if reveal_password {
-                println!("Password: {}", entry.password.expose_secret());
-            } else {
-                println!("Password: ******** (use --reveal-password to show)");
+        if let Some(field) = copy {
+            let what = match field {
+                GetField::User => "Username",
+                _ => "Password",
+            };
+            let Some(value) = field.value_of(entry) else {
+                anyhow::bail!("{} is empty for '{}'", what.to_lowercase(), entry.label);
+            };
+            let ttl_secs = ttl_seconds(self.config, None);
+            if copy_to_clipboard(self.config, value, Duration::from_secs(ttl_secs)).await? {
+                let msg = status(
+                    "📋",
+                    format_args!("{what} copied to clipboard ({ttl_secs}s)."),
+                );
+                if json {
+                    eprintln!("{msg}");
+                } else {
+                    println!("{msg}");
+                }
             }
-        } else {
-            anyhow::bail!("entry '{}' not found", key);
         }
         Ok(())
     }
 
     pub async fn handle_add(&self, opts: AddOptions) -> Result<()> {
+        if let Some(template) = opts.label_template.clone() {
+            return self.handle_add_many(opts, &template).await;
+        }
         // Load existing entries first
         let svc_load = self.service.clone();
-        let mut vault = spawn_blocking(move || svc_load.load())
+        let vault = spawn_blocking(move || svc_load.load())
             .await
             .map_err(|_| anyhow!("task join error"))??;
 
@@ -225,62 +1130,50 @@ impl<'a> Vault<'a> {
         let label = if let Some(l) = opts.label.clone() {
             l
         } else {
-            Text::new("Label (key)").prompt()?
+            require_interactive("--label")?;
+            with_cmd_timeout("label prompt", || Text::new("Label (key)").prompt()).await?
         };
+        validate_label(&label)?;
         if vault.iter().any(|e| e.label == label) {
-            println!("❌ Entry with label '{label}' already exists.");
+            println!(
+                "{}",
+                status(
+                    "❌",
+                    format_args!("Entry with label '{label}' already exists.")
+                )
+            );
             return Ok(());
         }
         let username = if let Some(u) = opts.user.clone() {
             u
+        } else if !is_interactive() {
+            String::new()
         } else {
-            Text::new("Username (optional)").with_default("").prompt()?
+            with_cmd_timeout("username prompt", || {
+                Text::new("Username (optional)").with_default("").prompt()
+            })
+            .await?
         };
         let notes = if let Some(n) = opts.notes.clone() {
             n
+        } else if !is_interactive() {
+            String::new()
         } else {
-            Text::new("Notes (optional)").with_default("").prompt()?
+            with_cmd_timeout("notes prompt", || {
+                Text::new("Notes (optional)").with_default("").prompt()
+            })
+            .await?
         };
+        validate_field_sizes(self.config, &username, &notes)?;
 
         // Determine password
-        let password = if opts.generate {
-            // Build policy
-            let mut policy = GenPolicy {
-                passphrase: opts.passphrase,
-                ..GenPolicy::default()
-            };
-            if policy.passphrase {
-                policy.words = opts
-                    .words
-                    .or(self.config.generator_words)
-                    .unwrap_or(GenPolicy::default().words);
-                policy.sep = opts
-                    .sep
-                    .clone()
-                    .or(self.config.generator_sep.clone())
-                    .unwrap_or_else(|| GenPolicy::default().sep.clone());
-            } else {
-                policy.length = opts
-                    .length
-                    .or(self.config.generator_length)
-                    .unwrap_or(GenPolicy::default().length);
-                policy.lower = !opts.no_lower;
-                policy.upper = !opts.no_upper;
-                policy.digits = !opts.no_digits;
-                policy.symbols = !opts.no_symbols;
-                let avoid_from_cfg = self
-                    .config
-                    .avoid_ambiguous
-                    .unwrap_or(GenPolicy::default().avoid_ambiguous);
-                policy.avoid_ambiguous = if opts.allow_ambiguous {
-                    false
-                } else {
-                    avoid_from_cfg
-                };
-            }
-            let rng: Arc<dyn Rng> = Arc::new(SystemRng);
+        let password = if let Some(pw) = &opts.password {
+            pw.expose_secret().to_string()
+        } else if opts.generate {
+            let policy = add_policy(self.config, &opts)?;
+            let rng: Arc<dyn Rng> = generator_rng();
             let gen = DefaultPasswordGenerator::new(rng);
-            let generated = gen.generate(&policy)?;
+            let mut generated = gen.generate(&policy)?;
             // Show a basic strength hint (interactive UX), without echoing the secret
             let bits = if policy.passphrase {
                 estimate_bits_passphrase(policy.words, crate::cryptography::wordlist::WORDS.len())
@@ -288,13 +1181,23 @@ impl<'a> Vault<'a> {
                 estimate_bits_char_mode(&policy)
             };
             println!(
-                "🔒 Generated secret strength: {} (~{:.1} bits)",
-                strength_label(bits),
-                bits
+                "{}",
+                status(
+                    "🔒",
+                    format_args!(
+                        "Generated secret strength: {} (~{:.1} bits)",
+                        strength_label(bits),
+                        bits
+                    )
+                )
             );
+            if is_interactive() && !opts.yes {
+                generated = preview_generated(generated, || gen.generate(&policy))?;
+            }
             generated
         } else {
-            Password::new("Password").prompt()?
+            require_interactive("password (use --generate)")?;
+            with_cmd_timeout("password prompt", || Password::new("Password").prompt()).await?
         };
 
         let entry = VaultEntry {
@@ -306,48 +1209,478 @@ impl<'a> Vault<'a> {
             },
             password: SecretString::new(password.into()),
             notes: if notes.is_empty() { None } else { Some(notes) },
+            custom: opts
+                .custom
+                .into_iter()
+                .map(|(k, v)| (k, SecretString::new(v.into())))
+                .collect(),
+            created_at: Some(now_secs()),
+            updated_at: Some(now_secs()),
+            last_accessed_at: None,
         };
 
-        vault.push(entry);
         let svc_save = self.service.clone();
-        spawn_blocking(move || svc_save.save(&vault))
+        spawn_blocking(move || {
+            svc_save.batch(|entries| {
+                entries.push(entry);
+                Ok(())
+            })
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+        println!("{}", status("✅", "Entry saved."));
+
+        Ok(())
+    }
+
+    /// `add --generate --label-template T --count N`: add N generated entries in
+    /// one save. A label that is already taken is re-rolled when the template
+    /// has a `{randK}` token and skipped otherwise.
+    async fn handle_add_many(&self, opts: AddOptions, template: &str) -> Result<()> {
+        const MAX_ATTEMPTS: usize = 16;
+        let template = LabelTemplate::parse(template)?;
+        let username = opts.user.clone().unwrap_or_default();
+        let notes = opts.notes.clone().unwrap_or_default();
+        validate_field_sizes(self.config, &username, &notes)?;
+        let policy = add_policy(self.config, &opts)?;
+        let rng = generator_rng();
+        let gen = DefaultPasswordGenerator::new(rng.clone());
+
+        let svc_load = self.service.clone();
+        let existing = spawn_blocking(move || svc_load.load())
             .await
             .map_err(|_| anyhow!("task join error"))??;
-        println!("✅ Entry saved.");
+        let mut taken: HashSet<String> = existing.iter().map(|e| e.label.clone()).collect();
+
+        let mut new_entries = Vec::new();
+        for n in 1..=opts.count.unwrap_or(1) as usize {
+            let mut label = template.render(n, rng.as_ref())?;
+            let mut attempts = 1;
+            while taken.contains(&label) && template.is_random() && attempts < MAX_ATTEMPTS {
+                label = template.render(n, rng.as_ref())?;
+                attempts += 1;
+            }
+            if taken.contains(&label) {
+                println!(
+                    "{}",
+                    status(
+                        "⚠️",
+                        format_args!("Skipping '{label}': label already exists")
+                    )
+                );
+                continue;
+            }
+            validate_label(&label)?;
+            taken.insert(label.clone());
+            let now = now_secs();
+            new_entries.push(VaultEntry {
+                label,
+                username: (!username.is_empty())
+                    .then(|| SecretString::new(username.clone().into())),
+                password: SecretString::new(gen.generate(&policy)?.into()),
+                notes: (!notes.is_empty()).then(|| notes.clone()),
+                custom: opts
+                    .custom
+                    .iter()
+                    .map(|(k, v)| (k.clone(), SecretString::new(v.clone().into())))
+                    .collect(),
+                created_at: Some(now),
+                updated_at: Some(now),
+                last_accessed_at: None,
+            });
+        }
+        if new_entries.is_empty() {
+            println!("{}", status("ℹ️", "No entries added."));
+            return Ok(());
+        }
 
+        let labels: Vec<String> = new_entries.iter().map(|e| e.label.clone()).collect();
+        let svc_save = self.service.clone();
+        spawn_blocking(move || {
+            svc_save.batch(|entries| {
+                entries.extend(new_entries);
+                Ok(())
+            })
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+        println!(
+            "{}",
+            status("✅", format_args!("Added {} entries.", labels.len()))
+        );
+        for label in labels {
+            println!("  {label}");
+        }
+        Ok(())
+    }
+
+    /// Edit an entry in place: rotate its password and/or add a line before or
+    /// after its notes. Other fields are edited in the TUI. Notes are joined with
+    /// the entry as saved, so a concurrent notes edit is kept.
+    pub async fn handle_edit(&self, key: &str, opts: EditOptions) -> Result<()> {
+        let EditOptions {
+            rotate_like_current,
+            append_note,
+            prepend_note,
+            exact,
+            yes,
+        } = opts;
+        if !rotate_like_current && append_note.is_none() && prepend_note.is_none() {
+            anyhow::bail!(
+                "nothing to change; pass --rotate-like-current, --append-note or --prepend-note \
+                 (edit other fields in `kevi tui`)"
+            );
+        }
+        let svc_load = self.service.clone();
+        let entries = spawn_blocking(move || svc_load.load())
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        let Some(entry) = resolve_entry(&entries, key, exact)? else {
+            return Err(KeviError::NotFound(key.to_string()).into());
+        };
+        let label = entry.label.clone();
+
+        let rotation = if rotate_like_current {
+            let mut policy = infer_policy(entry.password.expose_secret())?;
+            if let Some(max) = self.config.generator_max_length {
+                policy.max_length = max;
+            }
+            let gen = DefaultPasswordGenerator::new(generator_rng());
+            let mut generated = gen.generate(&policy)?;
+            if is_interactive() && !yes {
+                generated = preview_generated(generated, || gen.generate(&policy))?;
+            }
+            let password = SecretString::new(generated.into());
+            let bits = estimate_bits_char_mode(&policy);
+            Some((password, policy.length, bits))
+        } else {
+            None
+        };
+        let svc_save = self.service.clone();
+        let target = label.clone();
+        let new_password = rotation.as_ref().map(|(p, _, _)| p.clone());
+        let notes_changed = append_note.is_some() || prepend_note.is_some();
+        let config = self.config.clone();
+        spawn_blocking(move || {
+            svc_save.batch(|entries| {
+                let entry = entries
+                    .iter_mut()
+                    .find(|e| e.label == target)
+                    .ok_or_else(|| KeviError::NotFound(target.clone()))?;
+                if let Some(password) = new_password {
+                    entry.password = password;
+                }
+                if notes_changed {
+                    let notes = join_notes(
+                        prepend_note.as_deref(),
+                        entry.notes.as_deref(),
+                        append_note.as_deref(),
+                    );
+                    let username = entry
+                        .username
+                        .as_ref()
+                        .map(|u| u.expose_secret().to_string())
+                        .unwrap_or_default();
+                    validate_field_sizes(&config, &username, &notes)?;
+                    entry.notes = Some(notes);
+                }
+                entry.updated_at = Some(now_secs());
+                Ok(())
+            })
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+        if let Some((_, length, bits)) = rotation {
+            println!(
+                "{}",
+                status(
+                    "🔄",
+                    format_args!(
+                        "Rotated password for '{label}' ({length} chars, same classes; {} ~{bits:.1} bits).",
+                        strength_label(bits)
+                    )
+                )
+            );
+        }
+        if notes_changed {
+            println!(
+                "{}",
+                status("📝", format_args!("Updated notes for '{label}'."))
+            );
+        }
+        Ok(())
+    }
+
+    /// Move one or more entries to the trash with a single load and save. Keys that
+    /// match nothing are reported and skipped; an ambiguous key aborts before anything
+    /// is removed.
+    pub async fn handle_rm(&self, keys: &[String], yes: bool, exact: bool) -> Result<()> {
+        // Load to check existence and optionally confirm
+        let svc_load = self.service.clone();
+        let entries = spawn_blocking(move || svc_load.load())
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        let mut targets: Vec<&VaultEntry> = Vec::new();
+        let mut missing: Vec<&str> = Vec::new();
+        for key in keys {
+            match resolve_entry(&entries, key, exact)? {
+                Some(entry) => {
+                    if !targets.iter().any(|t| t.label == entry.label) {
+                        targets.push(entry);
+                    }
+                }
+                None => missing.push(key),
+            }
+        }
+        // Remove what matched, then fail for the rest so scripts see exit code 2
+        let not_found = || match missing.as_slice() {
+            [] => Ok(()),
+            keys => Err(KeviError::NotFound(keys.join("', '")).into()),
+        };
+        if targets.is_empty() {
+            return not_found();
+        }
+
+        if !yes {
+            require_interactive("--yes")?;
+            // Show what is about to go, so similar labels are not confused
+            for entry in &targets {
+                print_entry_details(entry, false, "");
+            }
+            let msg = match targets.as_slice() {
+                [one] => format!("Delete entry '{}' ?", one.label),
+                many => format!("Delete {} entries?", many.len()),
+            };
+            let proceed = with_cmd_timeout("confirmation prompt", move || {
+                Confirm::new(&msg).with_default(false).prompt()
+            })
+            .await?;
+            if !proceed {
+                println!("{}", status("❎", "Deletion cancelled."));
+                return Ok(());
+            }
+        }
+
+        let svc_rm = self.service.clone();
+        let labels: Vec<String> = targets.iter().map(|e| e.label.clone()).collect();
+        let to_remove = labels.clone();
+        let removed = spawn_blocking(move || svc_rm.remove_entries(&to_remove))
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        for key in &labels {
+            if removed.contains(key) {
+                println!(
+                    "{}",
+                    status(
+                        "🗑️",
+                        format_args!(
+                            "Entry '{key}' moved to trash (restore with `kevi trash restore {key}`)."
+                        )
+                    )
+                );
+            } else {
+                // Should not happen due to pre-check, but handle race
+                println!(
+                    "{}",
+                    status("❌", format_args!("No entry found with key '{key}'"))
+                );
+            }
+        }
+        not_found()
+    }
+
+    /// List the vault's numbered backups newest first by modification time,
+    /// with how many entries each holds. A backup that does not decrypt with
+    /// the current key (e.g. one from before a password change) is flagged,
+    /// since restoring it would need the old password.
+    pub async fn handle_backup_list(&self) -> Result<()> {
+        let svc = self.service.clone();
+        let path = self.config.vault_path.clone();
+        let naming = self.config.backup_naming();
+        let rows = spawn_blocking(move || {
+            let mut backups: Vec<(PathBuf, u64)> = existing_backups(&path, &naming)
+                .into_iter()
+                .map(|p| {
+                    let mtime = fs::metadata(&p)
+                        .and_then(|m| m.modified())
+                        .ok()
+                        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                        .map_or(0, |d| d.as_secs());
+                    (p, mtime)
+                })
+                .collect();
+            backups.sort_by_key(|b| std::cmp::Reverse(b.1));
+            let counts = svc.count_entries_in(
+                backups
+                    .iter()
+                    .map(|(p, _)| fs::read(p).map_err(anyhow::Error::from)),
+            )?;
+            Ok::<_, anyhow::Error>(
+                backups
+                    .into_iter()
+                    .zip(counts)
+                    .map(|((p, mtime), count)| (p, mtime, count))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+        if rows.is_empty() {
+            println!("(no backups)");
+            return Ok(());
+        }
+        let now = now_secs();
+        for (p, mtime, count) in rows {
+            let state = match count {
+                Ok(1) => "1 entry".to_string(),
+                Ok(n) => format!("{n} entries"),
+                Err(e) if matches!(e.downcast_ref(), Some(KeviError::WrongPassword)) => {
+                    "written under another key (not restorable with the current key)".to_string()
+                }
+                Err(e) => format!("unreadable: {e}"),
+            };
+            println!(
+                "{}\t{mtime} ({} ago)\t{state}",
+                p.display(),
+                format_age(now.saturating_sub(mtime))
+            );
+        }
+        Ok(())
+    }
+
+    pub async fn handle_trash_list(&self) -> Result<()> {
+        let svc = self.service.clone();
+        let doc = spawn_blocking(move || svc.load_document())
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        if doc.trash.is_empty() {
+            println!("(trash is empty)");
+            return Ok(());
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        for t in doc.trash {
+            let age = now.saturating_sub(t.deleted_at);
+            println!("{}\tdeleted {} ago", t.entry.label, format_age(age));
+        }
+        Ok(())
+    }
+
+    pub async fn handle_trash_restore(&self, label: &str) -> Result<()> {
+        let svc = self.service.clone();
+        let label_owned = label.to_string();
+        let restored = spawn_blocking(move || svc.restore_entry(&label_owned))
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        if restored {
+            println!(
+                "{}",
+                status("♻️", format_args!("Entry '{label}' restored."))
+            );
+            Ok(())
+        } else {
+            Err(KeviError::NotInTrash(label.to_string()).into())
+        }
+    }
+
+    pub async fn handle_trash_empty(&self, yes: bool) -> Result<()> {
+        if !yes {
+            require_interactive("--yes")?;
+            let proceed = with_cmd_timeout("confirmation prompt", || {
+                Confirm::new("Permanently delete all trashed entries?")
+                    .with_default(false)
+                    .prompt()
+            })
+            .await?;
+            if !proceed {
+                println!("{}", status("❎", "Cancelled."));
+                return Ok(());
+            }
+        }
+        let svc = self.service.clone();
+        let purged = spawn_blocking(move || svc.empty_trash())
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        println!(
+            "{}",
+            status(
+                "🗑️",
+                format_args!(
+                    "Permanently deleted {purged} trashed entr{}.",
+                    if purged == 1 { "y" } else { "ies" }
+                )
+            )
+        );
         Ok(())
     }
 
-    pub async fn handle_rm(&self, key: &str, yes: bool) -> Result<()> {
-        // Load to check existence and optionally confirm
-        let svc_load = self.service.clone();
-        let entries = spawn_blocking(move || svc_load.load())
+    /// Leniently decode the vault, report damaged items and optionally save the rest.
+    pub async fn handle_repair(&self, yes: bool) -> Result<()> {
+        let svc = self.service.clone();
+        let (doc, issues) = spawn_blocking(move || svc.load_document_lenient())
             .await
             .map_err(|_| anyhow!("task join error"))??;
-        if !entries.iter().any(|e| e.label == key) {
-            println!("❌ No entry found with key '{key}'");
+        if issues.is_empty() {
+            println!("{}", status("✅", "No damaged entries found."));
             return Ok(());
         }
-
+        for issue in &issues {
+            eprintln!("{}", status("⚠️", format_args!("Skipped {issue}")));
+        }
+        println!(
+            "{}",
+            status(
+                "🩹",
+                format_args!(
+                    "Recovered {} entries and {} trashed; {} item(s) could not be parsed.",
+                    doc.entries.len(),
+                    doc.trash.len(),
+                    issues.len()
+                )
+            )
+        );
         if !yes {
-            let msg = format!("Delete entry '{key}' ?");
-            let proceed = Confirm::new(&msg).with_default(false).prompt()?;
+            require_interactive("--yes")?;
+            let proceed = with_cmd_timeout("confirmation prompt", || {
+                Confirm::new("Save the cleaned vault (the current file is kept as a backup)?")
+                    .with_default(false)
+                    .prompt()
+            })
+            .await?;
             if !proceed {
-                println!("❎ Deletion cancelled.");
+                println!("{}", status("❎", "Cancelled."));
                 return Ok(());
             }
         }
+        let svc = self.service.clone();
+        spawn_blocking(move || svc.save_document(&doc))
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        println!("{}", status("✅", "Cleaned vault saved."));
+        Ok(())
+    }
 
-        let svc_rm = self.service.clone();
-        let key_owned = key.to_string();
-        let removed = spawn_blocking(move || svc_rm.remove_entry(&key_owned))
+    /// Read a JSON array of operations from stdin, run them against one unlocked
+    /// vault and print a JSON array of results.
+    pub async fn handle_exec(&self) -> Result<()> {
+        let ops: Vec<ExecOp> = spawn_blocking(|| -> Result<Vec<ExecOp>> {
+            let mut input = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut input)?;
+            serde_json::from_str(&input).map_err(|e| anyhow!("invalid exec request: {e}"))
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+
+        let svc = self.service.clone();
+        let config = self.config.clone();
+        let outcome = spawn_blocking(move || execute(&svc, &config, ops))
             .await
             .map_err(|_| anyhow!("task join error"))??;
-        if removed {
-            println!("🗑️ Entry '{key}' removed.");
-        } else {
-            // Should not happen due to pre-check, but handle race
-            println!("❌ No entry found with key '{key}'");
+        println!("{}", serde_json::to_string(&outcome.results)?);
+        if let Some(i) = outcome.failed_at {
+            anyhow::bail!("operation {i} failed; no changes were saved");
         }
         Ok(())
     }
@@ -356,7 +1689,10 @@ impl<'a> Vault<'a> {
         &self,
         query: Option<String>,
         show_users: bool,
-        json_mode: bool,
+        output: ListOutput,
+        sort: Option<ListSort>,
+        reverse: bool,
+        missing: &[MissingField],
     ) -> Result<()> {
         let svc = self.service.clone();
         let mut entries = spawn_blocking(move || svc.load())
@@ -368,25 +1704,55 @@ impl<'a> Vault<'a> {
             let ql = q.to_lowercase();
             entries.retain(|e| e.label.to_lowercase().contains(&ql));
         }
+        entries.retain(|e| missing.iter().all(|f| f.is_missing(e)));
+        sort_entries(&mut entries, sort, reverse);
 
-        if json_mode {
-            // Build JSON array without secrets
-            let items: Vec<serde_json::Value> = entries
-                .iter()
-                .map(|e| {
-                    if show_users {
-                        let user_opt = e.username.as_ref().map(|u| u.expose_secret().to_string());
-                        match user_opt {
-                            Some(u) if !u.is_empty() => json!({"label": e.label, "username": u}),
-                            _ => json!({"label": e.label}),
-                        }
-                    } else {
-                        json!({"label": e.label})
-                    }
-                })
-                .collect();
-            println!("{}", serde_json::to_string_pretty(&items)?);
-            return Ok(());
+        // JSON objects without secrets
+        let to_json = |e: &VaultEntry, fields: &[ListJsonField]| {
+            if !fields.is_empty() {
+                let obj: serde_json::Map<String, serde_json::Value> = fields
+                    .iter()
+                    .map(|f| (f.key().to_string(), f.value_of(e)))
+                    .collect();
+                serde_json::Value::Object(obj)
+            } else if show_users {
+                let user_opt = e.username.as_ref().map(|u| u.expose_secret().to_string());
+                match user_opt {
+                    Some(u) if !u.is_empty() => json!({"label": e.label, "username": u}),
+                    _ => json!({"label": e.label}),
+                }
+            } else {
+                json!({"label": e.label})
+            }
+        };
+        match output {
+            ListOutput::Json { fields } => {
+                let items: Vec<serde_json::Value> =
+                    entries.iter().map(|e| to_json(e, &fields)).collect();
+                println!("{}", serde_json::to_string_pretty(&items)?);
+                return Ok(());
+            }
+            ListOutput::JsonLines { fields } => {
+                // One compact object per line, written as we go
+                let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+                for e in &entries {
+                    serde_json::to_writer(&mut out, &to_json(e, &fields))?;
+                    out.write_all(b"\n")?;
+                }
+                out.flush()?;
+                return Ok(());
+            }
+            ListOutput::Porcelain { nul } => {
+                // Nothing at all for an empty vault
+                let mut out = std::io::BufWriter::new(std::io::stdout().lock());
+                for e in &entries {
+                    out.write_all(e.label.as_bytes())?;
+                    out.write_all(if nul { b"\0" } else { b"\n" })?;
+                }
+                out.flush()?;
+                return Ok(());
+            }
+            ListOutput::Text => {}
         }
 
         if entries.is_empty() {
@@ -412,54 +1778,235 @@ impl<'a> Vault<'a> {
         Ok(())
     }
 
-    pub async fn handle_init(&self, path_override: Option<&str>) -> Result<()> {
+    /// Ranked search over labels, usernames, notes and custom field names.
+    /// Prints labels and the matched fields; usernames only with `show_users`.
+    pub async fn handle_search(
+        &self,
+        term: &str,
+        fields: &[SearchField],
+        show_users: bool,
+        json: bool,
+    ) -> Result<()> {
+        let svc = self.service.clone();
+        let entries = spawn_blocking(move || svc.load())
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        let fields = if fields.is_empty() {
+            &SearchField::ALL[..]
+        } else {
+            fields
+        };
+        let hits = search_entries(&entries, term, fields);
+        let user_of = |label: &str| {
+            entries
+                .iter()
+                .find(|e| e.label == label)
+                .and_then(|e| e.username.as_ref())
+                .map(|u| u.expose_secret().to_string())
+                .filter(|u| show_users && !u.is_empty())
+        };
+
+        if json {
+            let items: Vec<serde_json::Value> = hits
+                .iter()
+                .map(|h| {
+                    let matched: Vec<String> = h.matched.iter().map(ToString::to_string).collect();
+                    let mut obj = json!({"label": h.label, "matched": matched, "score": h.score});
+                    if let Some(u) = user_of(&h.label) {
+                        obj["username"] = json!(u);
+                    }
+                    obj
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&items)?);
+            return Ok(());
+        }
+
+        if hits.is_empty() {
+            println!(
+                "{}",
+                status("🔍", format_args!("No entries match '{term}'"))
+            );
+            return Ok(());
+        }
+        for h in &hits {
+            let matched = h
+                .matched
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            match user_of(&h.label) {
+                Some(u) => println!("{}\t{matched}\t{u}", h.label),
+                None => println!("{}\t{matched}", h.label),
+            }
+        }
+        Ok(())
+    }
+
+    /// Create an empty vault with Argon2 params chosen by `kdf`.
+    pub async fn handle_init(&self, path_override: Option<&str>, kdf: InitKdf) -> Result<()> {
         // Decide a path
         let target_path = if let Some(p) = path_override {
-            std::path::PathBuf::from(p)
+            PathBuf::from(p)
         } else {
             self.config.vault_path.clone()
         };
+        let target_path = prepare_init_path(target_path)?;
+
+        // External key wrapping: no master password, the resolver wraps a fresh data key
+        #[cfg(feature = "age")]
+        if env::var("KEVI_AGE_IDENTITY").is_ok() {
+            let store: Arc<dyn ByteStore> = Arc::new(FileByteStore::new(target_path.clone()));
+            let codec: Arc<dyn VaultCodec> = Arc::new(RonCodec);
+            let svc = VaultService::new(
+                store,
+                codec,
+                default_key_resolver(target_path.clone(), None, Some(spinner_progress)),
+            );
+            spawn_blocking(move || svc.save(&[]))
+                .await
+                .map_err(|_| anyhow!("task join error"))??;
+            println!(
+                "{}",
+                status(
+                    "✅",
+                    format_args!("Initialized age-wrapped vault at {}", target_path.display())
+                )
+            );
+            return Ok(());
+        }
 
         // Get password (env or prompt twice)
-        let master = if let Ok(pw) = env::var("KEVI_PASSWORD") {
-            pw
-        } else {
-            let pw1 = Password::new("Master password")
-                .with_help_message("Used to encrypt your vault")
-                .without_confirmation()
-                .prompt()?;
-            let pw2 = Password::new("Confirm password")
-                .without_confirmation()
-                .prompt()?;
-            if pw1 != pw2 {
-                return Err(anyhow::anyhow!("Passwords do not match"));
+        let master = new_master_password()?;
+
+        let params = match kdf {
+            InitKdf::Calibrate(target) => {
+                let params = spawn_blocking(move || {
+                    with_spinner("Calibrating KDF…", || calibrate_params(target))
+                })
+                .await
+                .map_err(|_| anyhow!("task join error"))??;
+                let (m_cost_kib, t_cost, p_lanes) = params;
+                println!(
+                    "{}",
+                    status(
+                        "⚙️",
+                        format_args!(
+                            "KDF calibrated for ~{}ms: m_cost={} MiB, t_cost={t_cost}, p_lanes={p_lanes}",
+                            target.as_millis(),
+                            m_cost_kib / 1024
+                        )
+                    )
+                );
+                params
             }
-            pw1
+            InitKdf::Preset(preset) => preset.params(),
+            InitKdf::Default => default_params(),
         };
 
         // Save an empty vault
         let empty: Vec<VaultEntry> = Vec::new();
         let path_clone = target_path.clone();
         let master_clone = master.clone();
-        spawn_blocking(move || save_vault_file(&empty, &path_clone, &master_clone))
+        spawn_blocking(move || {
+            with_spinner("Deriving key…", || {
+                save_vault_file_with_params(&empty, &path_clone, &master_clone, params)
+            })
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+        println!(
+            "{}",
+            status(
+                "✅",
+                format_args!("Initialized encrypted vault at {}", target_path.display())
+            )
+        );
+        // Show the protection just chosen (--preset/--auto-kdf); nothing here is secret
+        let bytes = fs::read(&target_path)?;
+        let (hdr, _off) =
+            parse_kevi_header(&bytes).map_err(|e| anyhow!("Failed to parse header: {}", e))?;
+        print_header(&hdr);
+        Ok(())
+    }
+
+    /// One-way bridge off the legacy plaintext RON format: back up the plaintext,
+    /// then encrypt the same entries in place under a new master password.
+    pub async fn handle_migrate_from_plaintext(&self, confirmed: bool) -> Result<()> {
+        if !confirmed {
+            anyhow::bail!(
+                "refusing to migrate without --i-have-a-plaintext-vault (this reads an unencrypted vault)"
+            );
+        }
+        let path = self.config.vault_path.clone();
+        let read_path = path.clone();
+        let bytes = spawn_blocking(move || fs::read(&read_path))
             .await
-            .map_err(|_| anyhow!("task join error"))??;
+            .map_err(|_| anyhow!("task join error"))?
+            .map_err(|e| anyhow!("cannot read {}: {e}", path.display()))?;
+        if bytes.starts_with(b"KEVI") {
+            anyhow::bail!("{} is already an encrypted vault", path.display());
+        }
+        let entries = RonCodec
+            .decode(&bytes)
+            .map_err(|e| anyhow!("{} is not a plaintext kevi vault: {e}", path.display()))?
+            .entries;
+        for e in &entries {
+            validate_label(&e.label)?;
+        }
+
+        let master = new_master_password()?;
+
+        let backup = PathBuf::from(format!("{}.plaintext.bak", path.display()));
+        let count = entries.len();
+        let (path_clone, backup_clone) = (path.clone(), backup.clone());
+        let codec = codec_for(self.config.compact_storage == Some(true));
+        spawn_blocking(move || -> Result<()> {
+            atomic_write_secure(&backup_clone, &bytes)?;
+            let plain = codec.encode(&VaultDocument {
+                entries,
+                ..Default::default()
+            })?;
+            let ct = with_spinner("Deriving key…", || encrypt_vault(&plain, &master))?;
+            // No rotation: a rotated backup would be another plaintext copy
+            write_with_backups_n(&path_clone, &ct, 0)
+        })
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+
         println!(
-            "✅ Initialized encrypted vault at {}",
-            target_path.display()
+            "{}",
+            status(
+                "✅",
+                format_args!("Encrypted {count} entries into {}", path.display())
+            )
+        );
+        println!(
+            "{}",
+            status(
+                "⚠️",
+                format_args!(
+                    "WARNING: the plaintext copy was kept at {}. Verify the vault, then securely delete it (e.g. `shred -u`).",
+                    backup.display()
+                )
+            )
         );
         Ok(())
     }
 
-    pub async fn handle_unlock(&self, ttl_override: Option<u64>) -> Result<()> {
+    /// Derive the key and cache it for the TTL. A valid session for this header
+    /// is left alone (no KDF) unless `force_derive`; with `extend` it is restarted
+    /// with the TTL using its cached key.
+    pub async fn handle_unlock(
+        &self,
+        ttl_override: Option<u64>,
+        force_derive: bool,
+        extend: bool,
+        timed: bool,
+    ) -> Result<()> {
         // TTL precedence
-        let ttl_secs = ttl_override
-            .or_else(|| {
-                env::var("KEVI_UNLOCK_TTL")
-                    .ok()
-                    .and_then(|s| s.parse::<u64>().ok())
-            })
-            .unwrap_or(900);
+        let ttl_secs = unlock_ttl_seconds(ttl_override);
         let ttl = Duration::from_secs(ttl_secs);
 
         // Read vault header (must exist)
@@ -469,43 +2016,517 @@ impl<'a> Vault<'a> {
             .map_err(|_| anyhow!("task join error"))??;
         let (hdr, _off) = parse_kevi_header(&bytes).map_err(|e| anyhow!("invalid header: {e}"))?;
 
+        if !force_derive {
+            let (dk_path, current) = (dk_session_file_for(&self.config.vault_path), hdr.clone());
+            let max_secs = self.config.session_max_secs;
+            let existing = spawn_blocking(move || {
+                let Some((sess, remaining)) = read_dk_session(&dk_path, &current)? else {
+                    return Ok(None);
+                };
+                // A session about to run out is renewed by deriving as usual
+                if remaining < UNLOCK_MIN_REMAINING {
+                    return Ok(None);
+                }
+                if !extend {
+                    return Ok(Some(remaining));
+                }
+                // A session at its lifetime cap is replaced by deriving as usual
+                let extended = extend_dk_session(&dk_path, &sess, ttl, max_secs)?;
+                Ok::<_, anyhow::Error>((!extended.is_zero()).then_some(extended))
+            })
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+            if let Some(remaining) = existing {
+                let msg = if extend && remaining.as_secs() < ttl_secs {
+                    format!(
+                        "Session extended to {}s, the most session_max_secs allows (cached key reused).",
+                        remaining.as_secs()
+                    )
+                } else if extend {
+                    format!("Session extended to {ttl_secs}s (cached key reused).")
+                } else {
+                    format!(
+                        "Already unlocked ({} remaining); --extend restarts it, --force-derive re-derives.",
+                        format_remaining(remaining)
+                    )
+                };
+                println!("{}", status("🔓", msg));
+                return Ok(());
+            }
+        }
+
         // Get passphrase
-        let password = if let Ok(pw) = env::var("KEVI_PASSWORD") {
+        let password = Zeroizing::new(if let Ok(pw) = env::var("KEVI_PASSWORD") {
             pw
         } else {
-            Password::new("Master password")
-                .without_confirmation()
-                .prompt()?
-        };
+            require_password_prompt()?;
+            with_cmd_timeout("password prompt", || {
+                Password::new("Master password")
+                    .without_confirmation()
+                    .prompt()
+            })
+            .await?
+        });
 
         // Derive key and write dk-session bound to header
-        let key_arr = derive_key_argon2id(
-            &password,
-            &hdr.salt,
-            hdr.m_cost_kib,
-            hdr.t_cost,
-            hdr.p_lanes,
-        )?;
+        let started = Instant::now();
+        let mut key_arr = with_spinner("Deriving key…", || {
+            derive_key_argon2id(
+                &password,
+                &hdr.salt,
+                hdr.m_cost_kib,
+                hdr.t_cost,
+                hdr.p_lanes,
+            )
+        })?;
+        let elapsed_ms = started.elapsed().as_millis();
+        drop(password);
+        if timed {
+            println!(
+                "{}",
+                status(
+                    "⏱",
+                    format_args!(
+                        "Key derivation took {elapsed_ms} ms (m_cost_kib={}, t_cost={}, p_lanes={}).",
+                        hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes
+                    )
+                )
+            );
+        }
+        let _ = lock_slice(&mut key_arr);
+        // Only cache a key that actually decrypts the vault
+        let decrypts = decrypt_vault_with_key(&bytes, &key_arr).is_ok();
+        let key_vec = SecretBox::new(Box::new(key_arr.to_vec()));
+        let _ = unlock_slice(&mut key_arr);
+        key_arr.zeroize();
+        if !decrypts {
+            return Err(anyhow::Error::from(KeviError::WrongPassword)
+                .context("wrong password, not unlocked"));
+        }
         let fp = header_fingerprint_excluding_nonce(&hdr);
+        let fp_prefix = fp[..8].to_string();
         let dk_path = dk_session_file_for(&self.config.vault_path);
-        let key_vec = SecretBox::new(Box::new(key_arr.to_vec()));
         spawn_blocking(move || save_derived_key_session(&dk_path, &fp, &key_vec, ttl))
             .await
             .map_err(|_| anyhow!("task join error"))??;
-        println!("🔓 Unlocked for {ttl_secs}s (derived key cached).");
+        println!(
+            "{}",
+            status(
+                "🔓",
+                format_args!(
+                    "Unlocked for {ttl_secs}s (derived key cached, bound to header {fp_prefix})."
+                )
+            )
+        );
         Ok(())
     }
 
     pub async fn handle_lock(&self) -> Result<()> {
-        let dk_path = dk_session_file_for(&self.config.vault_path);
-        spawn_blocking(move || clear(&dk_path))
+        let dk_paths = dk_session_files_for(&self.config.vault_path);
+        spawn_blocking(move || dk_paths.iter().try_for_each(|p| clear(p)))
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+        println!("{}", status("🔒", "Locked (derived-key session cleared)."));
+        Ok(())
+    }
+
+    /// "Leaving this machine" cleanup for `lock --wipe-sessions/--wipe-backups`.
+    /// Sessions are overwritten and removed for this vault and every profile's vault;
+    /// backups of this vault are listed and wiped only after `--yes` or a prompt.
+    pub async fn handle_lock_wipe(
+        &self,
+        wipe_sessions: bool,
+        wipe_backups: bool,
+        yes: bool,
+    ) -> Result<()> {
+        if wipe_sessions {
+            let mut vaults = vec![self.config.vault_path.clone()];
+            for profile in self.config.profiles.values() {
+                if !vaults.contains(&profile.vault_path) {
+                    vaults.push(profile.vault_path.clone());
+                }
+            }
+            let wiped = spawn_blocking(move || -> Result<usize> {
+                let mut wiped = 0;
+                for dk_path in vaults.iter().flat_map(|v| dk_session_files_for(v)) {
+                    if wipe_file(&dk_path)? {
+                        wiped += 1;
+                    }
+                }
+                Ok(wiped)
+            })
+            .await
+            .map_err(|_| anyhow!("task join error"))??;
+            println!(
+                "{}",
+                status(
+                    "🔒",
+                    format_args!("Wiped {wiped} derived-key session(s) across all profiles.")
+                )
+            );
+        } else {
+            self.handle_lock().await?;
+        }
+
+        if wipe_backups {
+            let backups = existing_backups(&self.config.vault_path, &self.config.backup_naming());
+            if backups.is_empty() {
+                println!("{}", status("ℹ️", "No backups to wipe."));
+                return Ok(());
+            }
+            for b in &backups {
+                println!("  {}", b.display());
+            }
+            if !yes {
+                require_interactive("--yes")?;
+                let msg = format!(
+                    "Permanently wipe these {} backup file(s)? They cannot be recovered",
+                    backups.len()
+                );
+                let proceed = with_cmd_timeout("confirmation prompt", move || {
+                    Confirm::new(&msg).with_default(false).prompt()
+                })
+                .await?;
+                if !proceed {
+                    println!("{}", status("❎", "Backups kept."));
+                    return Ok(());
+                }
+            }
+            let count = backups.len();
+            spawn_blocking(move || -> Result<()> {
+                for b in &backups {
+                    wipe_file(b)?;
+                }
+                Ok(())
+            })
             .await
             .map_err(|_| anyhow!("task join error"))??;
-        println!("🔒 Locked (derived-key session cleared).");
+            println!(
+                "{}",
+                status("🧹", format_args!("Wiped {count} backup file(s)."))
+            );
+        }
         Ok(())
     }
 }
 
+/// Print an entry like `show`; secrets are masked unless `reveal`, with
+/// `mask_hint` appended to the masked password line.
+fn print_entry_details(entry: &VaultEntry, reveal: bool, mask_hint: &str) {
+    println!("Label:    {}", entry.label);
+    if let Some(user) = &entry.username {
+        println!("Username: {}", user.expose_secret());
+    } else {
+        println!("Username: (none)");
+    }
+    if let Some(notes) = &entry.notes {
+        println!("Notes:    {notes}");
+    } else {
+        println!("Notes:    (none)");
+    }
+
+    if reveal {
+        println!("Password: {}", entry.password.expose_secret());
+    } else {
+        println!("Password: ********{mask_hint}");
+    }
+    for (name, value) in &entry.custom {
+        if reveal {
+            println!("{name}: {}", value.expose_secret());
+        } else {
+            println!("{name}: ********");
+        }
+    }
+}
+
+/// `unlock` re-derives instead of reusing a session with less time left than this.
+const UNLOCK_MIN_REMAINING: Duration = Duration::from_secs(60);
+
+/// `14m` or, under a minute, `42s`.
+fn format_remaining(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    if secs >= 60 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{secs}s")
+    }
+}
+
+/// With `require_reveal_reauth`, ask for the master password again (ignoring any
+/// unlocked session) and check it by decrypting the vault before a secret is shown.
+/// Copies never go through here. Blocking. `KEVI_PASSWORD` only satisfies it when
+/// no prompt can be shown (scripts, `--no-prompt`); at a terminal it always asks.
+pub fn reauthenticate_for_reveal(config: &Config) -> Result<()> {
+    if config.require_reveal_reauth != Some(true) {
+        return Ok(());
+    }
+    let store: Arc<dyn ByteStore> = Arc::new(vault_store(config));
+    let codec: Arc<dyn VaultCodec> = Arc::new(RonCodec);
+    let resolver: Arc<dyn KeyResolver> = Arc::new(
+        BypassKeyResolver::new()
+            .with_progress(Some(spinner_progress))
+            .with_env_password(!is_interactive()),
+    );
+    VaultService::new(store, codec, resolver)
+        .load()
+        .map(drop)
+        .map_err(|e| {
+            let msg = format!("re-authentication required to reveal secrets: {e}");
+            e.context(msg)
+        })
+}
+
+/// `kevi gen --wordlist-info`: the passphrase wordlist's size, the bits each word
+/// contributes, and what the configured word count adds up to. Fails if the list
+/// has duplicates, since the entropy figures would then overstate strength.
+pub fn print_wordlist_info(config: &Config) -> Result<()> {
+    let words = crate::cryptography::wordlist::WORDS;
+    let dups = duplicate_words(words);
+    if !dups.is_empty() {
+        anyhow::bail!(
+            "wordlist has {} duplicate word(s): {}",
+            dups.len(),
+            dups.join(", ")
+        );
+    }
+    let count = config.generator_words.unwrap_or(GenPolicy::default().words);
+    let per_word = estimate_bits_passphrase(1, words.len());
+    let total = estimate_bits_passphrase(count, words.len());
+    println!("wordlist: built-in");
+    println!("  words: {} (all unique)", words.len());
+    println!("  bits per word: {per_word:.2}");
+    println!(
+        "  {count}-word passphrase: ~{total:.1} bits ({})",
+        strength_label(total)
+    );
+    Ok(())
+}
+
+/// Print the non-secret header fields (`kevi header`, and after `init`).
+fn print_header(hdr: &KeviHeader) {
+    let kdf = match hdr.kdf_id {
+        KDF_ARGON2ID => "Argon2id",
+        _ => "Unknown",
+    };
+    let aead = match hdr.aead_id {
+        AEAD_AES256GCM => "AES-256-GCM",
+        _ => "Unknown",
+    };
+    let salt_hex: String = hdr.salt.iter().map(|b| format!("{b:02x}")).collect();
+    let nonce_hex: String = hdr.nonce.iter().map(|b| format!("{b:02x}")).collect();
+    println!("KEVI header:");
+    println!("  version: {}", hdr.version);
+    println!("  kdf: {} ({})", kdf, hdr.kdf_id);
+    println!("  aead: {} ({})", aead, hdr.aead_id);
+    println!("  argon2 m_cost_kib: {}", hdr.m_cost_kib);
+    println!("  argon2 t_cost: {}", hdr.t_cost);
+    println!("  argon2 p_lanes: {}", hdr.p_lanes);
+    println!("  salt: {salt_hex}");
+    println!("  nonce: {nonce_hex}");
+}
+
+/// Advisory stderr warnings before a copy: clipboard availability every time,
+/// history-keeping clipboard managers once per detected set.
+fn clipboard_warnings() {
+    if let Some(warn) = environment_warning() {
+        eprintln!("{}", status("⚠️", &warn));
+    }
+    let marker = kevi_config_dir().join("clipboard-history-warned");
+    if let Some(warn) = history_warning_once(&marker) {
+        eprintln!("{}", status("⚠️", &warn));
+    }
+}
+
+/// Copy `value` to the system clipboard with TTL, reporting problems on stderr.
+/// Returns true when the clipboard was updated. Opening the clipboard and copying
+/// both count toward `KEVI_CMD_TIMEOUT`; running out of time is an error.
+async fn copy_to_clipboard(config: &Config, value: String, ttl: Duration) -> Result<bool> {
+    clipboard_warnings();
+    let (restore, on_clear) = (restore_previous(config), clear_hook(config));
+    let secret = SecretString::new(value.into());
+    with_cmd_timeout("clipboard access", move || {
+        let engine = match SystemClipboardEngine::new() {
+            Ok(engine_impl) => {
+                Arc::new(engine_impl) as Arc<dyn crate::filesystem::clipboard::ClipboardEngine>
+            }
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    status("⚠️", format_args!("Clipboard not available: {e}"))
+                );
+                return Ok::<_, anyhow::Error>(false);
+            }
+        };
+        if let Err(e) = copy_with_ttl_then(engine, &secret, ttl, restore, on_clear) {
+            eprintln!(
+                "{}",
+                status("⚠️", format_args!("Failed to copy to clipboard: {e}"))
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    })
+    .await
+}
+
+/// Copy `value` and keep the process alive until the TTL elapses or Ctrl-C,
+/// restoring the previous clipboard contents before returning. With `on_paste`
+/// the hold also ends once the clipboard changes away from the secret. Opening
+/// the clipboard counts toward `KEVI_CMD_TIMEOUT`; running out of time is an error.
+async fn copy_and_hold(
+    config: &Config,
+    value: String,
+    ttl: Duration,
+    on_paste: bool,
+) -> Result<()> {
+    clipboard_warnings();
+    let opened = with_cmd_timeout("clipboard access", || {
+        Ok::<_, anyhow::Error>(SystemClipboardEngine::new())
+    })
+    .await?;
+    let engine = match opened {
+        Ok(engine_impl) => {
+            Arc::new(engine_impl) as Arc<dyn crate::filesystem::clipboard::ClipboardEngine>
+        }
+        Err(e) => {
+            eprintln!(
+                "{}",
+                status("⚠️", format_args!("Clipboard not available: {e}"))
+            );
+            return Ok(());
+        }
+    };
+    let secret = SecretString::new(value.into());
+    if on_paste {
+        eprintln!(
+            "{}",
+            status(
+                "⏳",
+                format_args!(
+                    "Holding clipboard until it changes (up to {}s); press Ctrl-C to clear now.",
+                    ttl.as_secs()
+                )
+            )
+        );
+        match copy_and_wait_for_paste(
+            engine,
+            &secret,
+            ttl,
+            restore_previous(config),
+            clear_hook(config),
+        )
+        .await
+        {
+            Ok(HoldOutcome::Changed) => eprintln!(
+                "{}",
+                status("🧹", "Clipboard changed; left the new contents in place.")
+            ),
+            Ok(HoldOutcome::Interrupted) => {
+                eprintln!("{}", status("🧹", "Interrupted; clipboard cleared."))
+            }
+            Ok(HoldOutcome::Elapsed) => eprintln!("{}", status("🧹", "Clipboard cleared.")),
+            Err(e) => eprintln!(
+                "{}",
+                status("⚠️", format_args!("Failed to copy to clipboard: {e}"))
+            ),
+        }
+        return Ok(());
+    }
+    eprintln!(
+        "{}",
+        status(
+            "⏳",
+            format_args!(
+                "Holding clipboard for {}s; press Ctrl-C to clear now.",
+                ttl.as_secs()
+            )
+        )
+    );
+    match copy_and_wait(
+        engine,
+        &secret,
+        ttl,
+        restore_previous(config),
+        clear_hook(config),
+    )
+    .await
+    {
+        Ok(true) => eprintln!("{}", status("🧹", "Interrupted; clipboard cleared.")),
+        Ok(false) => eprintln!("{}", status("🧹", "Clipboard cleared.")),
+        Err(e) => eprintln!(
+            "{}",
+            status("⚠️", format_args!("Failed to copy to clipboard: {e}"))
+        ),
+    }
+    Ok(())
+}
+
+/// Seconds to switch focus to the target field before `get --type` starts typing.
+#[cfg(feature = "autotype")]
+const AUTOTYPE_COUNTDOWN_SECS: u64 = 3;
+
+/// Type `value` into the focused window after a short countdown (`get --type`).
+#[cfg(feature = "autotype")]
+async fn auto_type(value: String) -> Result<()> {
+    use crate::filesystem::autotype::{detect_tool, type_text};
+    let tool = detect_tool()?;
+    for n in (1..=AUTOTYPE_COUNTDOWN_SECS).rev() {
+        eprint!(
+            "\r{}",
+            status(
+                "⌨️",
+                format_args!("Typing in {n}s; focus the target field…")
+            )
+        );
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    eprintln!();
+    let value = Zeroizing::new(value);
+    spawn_blocking(move || type_text(tool, &value))
+        .await
+        .map_err(|_| anyhow!("task join error"))??;
+    eprintln!(
+        "{}",
+        status("⌨️", format_args!("Typed with {}.", tool.program()))
+    );
+    Ok(())
+}
+
+#[cfg(not(feature = "autotype"))]
+async fn auto_type(_value: String) -> Result<()> {
+    anyhow::bail!("--type is unavailable: kevi was built without the `autotype` feature")
+}
+
+// Options for the get command, constructed by CLI layer
+#[derive(Debug, Clone)]
+pub struct GetOptions {
+    pub field: GetField,
+    // Try these fields in order instead of `field`, using the first non-empty one
+    pub field_fallback: Vec<GetField>,
+    pub no_copy: bool,
+    pub ttl: Option<u64>,
+    pub echo: bool,
+    pub once: bool,
+    pub exact: bool,
+    // Used with `GetField::All`
+    pub format: EntryFormat,
+    pub reveal: bool,
+    // Print the value without a trailing newline / NUL-terminated (no clipboard)
+    pub raw: bool,
+    pub print0: bool,
+    // Block until the TTL elapses (or Ctrl-C), then restore the clipboard
+    pub wait: bool,
+    // Confirm a successful copy on stderr (also enabled by config `get_verbose`)
+    pub verbose: bool,
+    // Write the value to a 0600 file / once to a named pipe instead (no clipboard)
+    pub to_file: Option<PathBuf>,
+    pub to_fifo: Option<PathBuf>,
+    // Restore as soon as the clipboard changes away from the secret (or the TTL elapses)
+    pub clear_on_paste: bool,
+    // Type the value into the focused window instead (needs the `autotype` feature)
+    pub autotype: bool,
+}
+
 // Options for the add command, constructed by CLI layer
 #[derive(Debug, Clone)]
 pub struct AddOptions {
@@ -519,7 +2540,208 @@ pub struct AddOptions {
     pub passphrase: bool,
     pub words: Option<u16>,
     pub sep: Option<String>,
+    /// Named `[generator.presets.<name>]` applied before the flags above
+    pub preset: Option<String>,
+    /// Site's length cap; the generated password is shortened to fit
+    pub max_length: Option<u16>,
     pub label: Option<String>,
+    /// `--label-template`: add `count` generated entries labelled from it
+    pub label_template: Option<String>,
+    pub count: Option<u32>,
     pub user: Option<String>,
     pub notes: Option<String>,
+    /// Custom fields from `--set name=value`
+    pub custom: Vec<(String, String)>,
+    /// Password taken from `--from-uri` instead of generating or prompting
+    pub password: Option<SecretString>,
+    /// Save a generated password without the interactive preview
+    pub yes: bool,
+}
+
+// Options for the edit command, constructed by CLI layer
+#[derive(Debug, Clone, Default)]
+pub struct EditOptions {
+    pub rotate_like_current: bool,
+    pub append_note: Option<String>,
+    pub prepend_note: Option<String>,
+    pub exact: bool,
+    /// Save a rotated password without the interactive preview
+    pub yes: bool,
+}
+
+/// Generator policy for `add --generate`: built-in defaults, then the configured
+/// generator defaults, then `--preset`, then the individual flags, clamped to
+/// `--max-length`.
+fn add_policy(config: &Config, opts: &AddOptions) -> Result<GenPolicy> {
+    let mut policy = GenPolicy::default();
+    if let Some(max) = config.generator_max_length {
+        policy.max_length = max;
+    }
+    if let Some(length) = config.generator_length {
+        policy.length = length;
+    }
+    if let Some(words) = config.generator_words {
+        policy.words = words;
+    }
+    if let Some(sep) = &config.generator_sep {
+        policy.sep = sep.clone();
+    }
+    if let Some(avoid) = config.avoid_ambiguous {
+        policy.avoid_ambiguous = avoid;
+    }
+    if let Some(name) = &opts.preset {
+        config.generator_preset(name)?.apply_to(&mut policy);
+    }
+    policy.passphrase |= opts.passphrase;
+    if let Some(length) = opts.length {
+        policy.length = length;
+    }
+    if let Some(words) = opts.words {
+        policy.words = words;
+    }
+    if let Some(sep) = &opts.sep {
+        policy.sep = sep.clone();
+    }
+    policy.lower &= !opts.no_lower;
+    policy.upper &= !opts.no_upper;
+    policy.digits &= !opts.no_digits;
+    policy.symbols &= !opts.no_symbols;
+    policy.avoid_ambiguous &= !opts.allow_ambiguous;
+    if let Some(max) = opts.max_length {
+        if policy.passphrase {
+            anyhow::bail!("--max-length applies to character passwords, not passphrases");
+        }
+        if policy.length > max {
+            eprintln!(
+                "{}",
+                status(
+                    "✂️",
+                    format_args!(
+                        "Generating {max} characters instead of {} (--max-length)",
+                        policy.length
+                    )
+                )
+            );
+            policy.length = max;
+        }
+    }
+    Ok(policy)
+}
+
+/// Notes with `before` and `after` added as their own lines around `existing`.
+fn join_notes(before: Option<&str>, existing: Option<&str>, after: Option<&str>) -> String {
+    [before, existing, after]
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether two paths name the same file (either may not exist yet).
+fn same_file(a: &std::path::Path, b: &std::path::Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+/// Password to open an export: `KEVI_EXPORT_PASSWORD`, then `KEVI_PASSWORD` (an
+/// export made without `--new-password` uses the vault's), else prompt.
+fn export_password() -> Result<Zeroizing<String>> {
+    if let Ok(pw) = env::var("KEVI_EXPORT_PASSWORD").or_else(|_| env::var("KEVI_PASSWORD")) {
+        return Ok(Zeroizing::new(pw));
+    }
+    require_password_prompt()?;
+    Ok(Zeroizing::new(block_with_cmd_timeout(
+        "password prompt",
+        || {
+            Password::new("Export password")
+                .without_confirmation()
+                .prompt()
+        },
+    )?))
+}
+
+/// Password for `export --new-password`: `KEVI_EXPORT_PASSWORD`, or prompt twice.
+/// Never taken from argv, where shell history and the process list would keep it.
+fn new_export_password() -> Result<Zeroizing<String>> {
+    if let Ok(pw) = env::var("KEVI_EXPORT_PASSWORD") {
+        return Ok(Zeroizing::new(pw));
+    }
+    require_password_prompt()?;
+    let pw1 = Zeroizing::new(block_with_cmd_timeout("password prompt", || {
+        Password::new("Export password")
+            .without_confirmation()
+            .prompt()
+    })?);
+    let pw2 = Zeroizing::new(block_with_cmd_timeout("password prompt", || {
+        Password::new("Confirm export password")
+            .without_confirmation()
+            .prompt()
+    })?);
+    if !secrets_equal(&pw1, &pw2) {
+        anyhow::bail!("Passwords do not match; run the command again and re-enter both");
+    }
+    Ok(pw1)
+}
+
+/// Master password for a new vault: `KEVI_PASSWORD` (checked against
+/// `KEVI_PASSWORD_CONFIRM` when that is set), or prompt twice.
+fn new_master_password() -> Result<Zeroizing<String>> {
+    if let Ok(pw) = env::var("KEVI_PASSWORD") {
+        let pw = Zeroizing::new(pw);
+        if let Ok(confirm) = env::var("KEVI_PASSWORD_CONFIRM") {
+            let confirm = Zeroizing::new(confirm);
+            if !secrets_equal(&pw, &confirm) {
+                anyhow::bail!(
+                    "KEVI_PASSWORD and KEVI_PASSWORD_CONFIRM do not match; set both to the same value and retry"
+                );
+            }
+        }
+        return Ok(pw);
+    }
+    require_password_prompt()?;
+    let pw1 = Zeroizing::new(block_with_cmd_timeout("password prompt", || {
+        Password::new("Master password")
+            .with_help_message("Used to encrypt your vault")
+            .without_confirmation()
+            .prompt()
+    })?);
+    let pw2 = Zeroizing::new(block_with_cmd_timeout("password prompt", || {
+        Password::new("Confirm password")
+            .without_confirmation()
+            .prompt()
+    })?);
+    if !secrets_equal(&pw1, &pw2) {
+        return Err(anyhow::anyhow!(
+            "Passwords do not match; run the command again and re-enter both"
+        ));
+    }
+    Ok(pw1)
+}
+
+/// Resolve the file `init` should create: an existing directory gets the default
+/// `vault.ron` file name, and missing parents are created with 0700 permissions.
+fn prepare_init_path(path: PathBuf) -> Result<PathBuf> {
+    let path = if path.is_dir() {
+        path.join("vault.ron")
+    } else {
+        path
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| anyhow!("cannot create vault directory {}: {e}", parent.display()))?;
+    }
+    ensure_parent_secure(&path)?;
+    Ok(path)
+}
+
+fn format_age(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{s}s"),
+        s if s < 60 * 60 => format!("{}m", s / 60),
+        s if s < 24 * 60 * 60 => format!("{}h", s / (60 * 60)),
+        s => format!("{}d", s / (24 * 60 * 60)),
+    }
 }
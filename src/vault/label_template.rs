@@ -0,0 +1,117 @@
+use crate::cryptography::generator::uniform_index;
+use crate::vault::ports::Rng;
+use anyhow::{anyhow, bail, Result};
+
+const RAND_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+const MAX_RAND_LEN: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    /// `{n}`: 1-based position in the batch
+    Index,
+    /// `{randK}`: K random lowercase letters and digits
+    Random(usize),
+}
+
+/// Label pattern for `add --count`, e.g. `svc-{n}-{rand4}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LabelTemplate {
+    parts: Vec<Part>,
+}
+
+impl LabelTemplate {
+    /// Parse a template. It must contain `{n}` or a `{randK}` token (K in 1..=32),
+    /// otherwise every label would be the same.
+    pub fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            if start > 0 {
+                parts.push(Part::Literal(rest[..start].to_string()));
+            }
+            let end = rest[start..]
+                .find('}')
+                .map(|i| start + i)
+                .ok_or_else(|| anyhow!("unclosed '{{' in label template '{template}'"))?;
+            let token = &rest[start + 1..end];
+            parts.push(match token {
+                "n" => Part::Index,
+                _ => match token.strip_prefix("rand").map(str::parse::<usize>) {
+                    Some(Ok(len)) if (1..=MAX_RAND_LEN).contains(&len) => Part::Random(len),
+                    Some(Ok(_)) => {
+                        bail!("'{{{token}}}' must have between 1 and {MAX_RAND_LEN} characters")
+                    }
+                    _ => bail!(
+                        "unknown token '{{{token}}}' in label template; use {{n}} or {{randK}}"
+                    ),
+                },
+            });
+            rest = &rest[end + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest.to_string()));
+        }
+        if !parts
+            .iter()
+            .any(|p| matches!(p, Part::Index | Part::Random(_)))
+        {
+            bail!("label template '{template}' needs {{n}} or {{randK}} so labels differ");
+        }
+        Ok(Self { parts })
+    }
+
+    /// Whether rendering the same index again can give a different label.
+    pub fn is_random(&self) -> bool {
+        self.parts.iter().any(|p| matches!(p, Part::Random(_)))
+    }
+
+    /// Render the label for position `n`.
+    pub fn render(&self, n: usize, rng: &dyn Rng) -> Result<String> {
+        let mut label = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Literal(s) => label.push_str(s),
+                Part::Index => label.push_str(&n.to_string()),
+                Part::Random(len) => {
+                    for _ in 0..*len {
+                        let i = uniform_index(rng, RAND_ALPHABET.len())?;
+                        label.push(RAND_ALPHABET[i] as char);
+                    }
+                }
+            }
+        }
+        Ok(label)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cryptography::generator::SeededRng;
+
+    #[test]
+    fn renders_index_and_random_tokens() {
+        let rng = SeededRng::new(7);
+        let t = LabelTemplate::parse("svc-{n}-{rand4}").unwrap();
+        assert!(t.is_random());
+        let label = t.render(3, &rng).unwrap();
+        let suffix = label.strip_prefix("svc-3-").expect("prefix kept");
+        assert_eq!(suffix.len(), 4);
+        assert!(suffix.bytes().all(|b| RAND_ALPHABET.contains(&b)));
+
+        let t = LabelTemplate::parse("{n}.db").unwrap();
+        assert!(!t.is_random());
+        assert_eq!(t.render(12, &rng).unwrap(), "12.db");
+    }
+
+    #[test]
+    fn rejects_constant_and_malformed_templates() {
+        for bad in ["svc", "svc-{x}", "svc-{n", "{rand0}", "{rand33}", "{randx}"] {
+            assert!(
+                LabelTemplate::parse(bad).is_err(),
+                "{bad} should be rejected"
+            );
+        }
+    }
+}
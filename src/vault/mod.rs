@@ -1,6 +1,12 @@
 pub mod codec;
+pub mod credential_uri;
+pub mod exec;
+pub mod export;
+pub mod format;
 pub mod handlers;
+pub mod label_template;
 pub mod models;
 pub mod persistence;
 pub mod ports;
+pub mod search;
 pub mod service;
@@ -1,5 +1,7 @@
+use anyhow::Result;
 use secrecy::SecretString;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct VaultEntry {
@@ -8,5 +10,101 @@ pub struct VaultEntry {
     pub username: Option<SecretString>,
     #[serde(with = "crate::cryptography::types::secret_string")]
     pub password: SecretString,
+    #[serde(default)]
     pub notes: Option<String>,
+    /// Site-specific extra fields (security questions, account numbers, ...)
+    #[serde(
+        default,
+        skip_serializing_if = "BTreeMap::is_empty",
+        with = "crate::cryptography::types::secret_string_map"
+    )]
+    pub custom: BTreeMap<String, SecretString>,
+    /// Unix timestamps (seconds); absent for entries written by older versions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+    /// Last `get`/`show`/TUI copy; only recorded when `track_access` is on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_accessed_at: Option<u64>,
+}
+
+impl VaultEntry {
+    /// An entry with only a label and password set. Other fields can be filled in
+    /// with struct update syntax: `VaultEntry { notes, ..VaultEntry::new(label, pw) }`.
+    pub fn new(label: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            username: None,
+            password: SecretString::new(password.into().into()),
+            notes: None,
+            custom: BTreeMap::new(),
+            created_at: None,
+            updated_at: None,
+            last_accessed_at: None,
+        }
+    }
+
+    /// When the entry was last used: its access time when tracked, otherwise
+    /// when it last changed. 0 when nothing was recorded.
+    pub fn recency(&self) -> u64 {
+        self.last_accessed_at
+            .or(self.updated_at)
+            .or(self.created_at)
+            .unwrap_or(0)
+    }
+}
+
+/// Reject labels containing control characters (tab, newline, NUL, ...) so
+/// tab- and NUL-delimited output stays unambiguous.
+pub fn validate_label(label: &str) -> Result<()> {
+    if let Some(c) = label.chars().find(|c| c.is_control()) {
+        anyhow::bail!("label must not contain control characters (found {:?})", c);
+    }
+    Ok(())
+}
+
+/// Reject custom field names that are empty, contain `=`/`:` or control
+/// characters, so `--set name=value` and `--field custom:name` parse unambiguously.
+pub fn validate_custom_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        anyhow::bail!("custom field name must not be empty");
+    }
+    if name.chars().any(|c| c.is_control() || c == '=' || c == ':') {
+        anyhow::bail!("invalid custom field name '{name}'; avoid '=', ':' and control characters");
+    }
+    Ok(())
+}
+
+/// A soft-deleted entry kept in the vault until restored or pruned.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrashedEntry {
+    pub entry: VaultEntry,
+    /// Unix timestamp (seconds) of the deletion
+    pub deleted_at: u64,
+}
+
+/// Version of the decrypted vault layout this build writes. Fields added later
+/// must be `#[serde(default)]` so older vaults keep loading; unknown fields from
+/// newer versions are ignored on read, and such vaults are not saved over.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Decrypted vault contents: live entries plus the trash.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct VaultDocument {
+    /// Layout version the vault was written with; 0 for vaults that predate it
+    #[serde(default)]
+    pub schema_version: u32,
+    pub entries: Vec<VaultEntry>,
+    #[serde(default)]
+    pub trash: Vec<TrashedEntry>,
+}
+
+impl VaultDocument {
+    /// Drop trashed entries older than `retention_days` (0 keeps nothing).
+    pub fn prune_trash(&mut self, retention_days: u64, now: u64) {
+        let keep_secs = retention_days.saturating_mul(24 * 60 * 60);
+        self.trash
+            .retain(|t| t.deleted_at.saturating_add(keep_secs) > now);
+    }
 }
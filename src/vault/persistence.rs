@@ -1,6 +1,9 @@
-use crate::cryptography::primitives::{decrypt_vault, encrypt_vault};
+use crate::cryptography::primitives::{decrypt_vault, default_params, encrypt_vault_with_params};
 use crate::filesystem::secure::write_with_backups;
+use crate::vault::codec::RonCodec;
 use crate::vault::models::VaultEntry;
+use crate::vault::ports::VaultCodec;
+use crate::vault::service::ensure_not_truncated;
 use anyhow::{anyhow, Context, Result};
 use ron::ser::PrettyConfig;
 use std::fs::File;
@@ -22,6 +25,7 @@ pub fn load_vault_file(path: &Path, password: &str) -> Result<Vec<VaultEntry>> {
     if buf.is_empty() {
         return Ok(vec![]);
     }
+    ensure_not_truncated(&buf)?;
 
     if !buf.is_empty() && !buf.starts_with(b"KEVI") {
         return Err(anyhow!(
@@ -33,21 +37,27 @@ pub fn load_vault_file(path: &Path, password: &str) -> Result<Vec<VaultEntry>> {
     let data =
         decrypt_vault(&buf, password).context("Failed to decrypt vault (wrong password?)")?;
 
-    // Interpret as UTF-8 RON
-    let contents =
-        String::from_utf8(data).map_err(|_| anyhow!("vault content not valid UTF-8 RON"))?;
-    let vault: Vec<VaultEntry> =
-        ron::from_str(&contents).context("Failed to parse vault content")?;
-    Ok(vault)
+    // Interpret as UTF-8 RON (bare entry list or document with trash)
+    Ok(RonCodec.decode(&data)?.entries)
 }
 
 /// Serialize Vec<VaultEntry>, encrypt with password, and save atomically to disk.
 pub fn save_vault_file(entries: &[VaultEntry], path: &Path, password: &str) -> Result<()> {
+    save_vault_file_with_params(entries, path, password, default_params())
+}
+
+/// Like `save_vault_file`, with explicit Argon2 `(m_cost_kib, t_cost, p_lanes)`.
+pub fn save_vault_file_with_params(
+    entries: &[VaultEntry],
+    path: &Path,
+    password: &str,
+    params: (u32, u32, u32),
+) -> Result<()> {
     let pretty = PrettyConfig::new()
         .depth_limit(3)
         .separate_tuple_members(true)
         .enumerate_arrays(true);
     let serialized = ron::ser::to_string_pretty(entries, pretty)?;
-    let ciphertext = encrypt_vault(serialized.as_bytes(), password)?;
+    let ciphertext = encrypt_vault_with_params(serialized.as_bytes(), password, params)?;
     write_with_backups(path, &ciphertext)
 }
@@ -1,8 +1,10 @@
 use anyhow::Result;
-use secrecy::SecretBox;
+use secrecy::{ExposeSecretMut, SecretBox};
+use zeroize::Zeroize;
 
+use crate::cryptography::memlock::{lock_slice, unlock_slice};
 use crate::cryptography::primitives::KeviHeader;
-use crate::vault::models::VaultEntry;
+use crate::vault::models::VaultDocument;
 
 // Randomness provider for deterministic testing.
 pub trait Rng: Send + Sync {
@@ -10,13 +12,54 @@ pub trait Rng: Send + Sync {
 }
 
 pub trait VaultCodec: Send + Sync {
-    fn encode(&self, entries: &[VaultEntry]) -> Result<Vec<u8>>;
-    fn decode(&self, data: &[u8]) -> Result<Vec<VaultEntry>>;
+    fn encode(&self, doc: &VaultDocument) -> Result<Vec<u8>>;
+    fn decode(&self, data: &[u8]) -> Result<VaultDocument>;
+    /// Decode what can be salvaged, reporting items that failed to parse.
+    /// Codecs without per-item recovery fall back to a strict decode.
+    fn decode_lenient(&self, data: &[u8]) -> Result<(VaultDocument, Vec<DecodeIssue>)> {
+        Ok((self.decode(data)?, Vec::new()))
+    }
+}
+
+/// An item skipped by a lenient decode.
+#[derive(Debug, Clone)]
+pub struct DecodeIssue {
+    /// `entries` or `trash`
+    pub section: &'static str,
+    /// Zero-based position of the item in its list
+    pub index: usize,
+    /// Line where the item starts in the decrypted content
+    pub line: usize,
+    pub label: Option<String>,
+    pub error: String,
 }
 
+impl core::fmt::Display for DecodeIssue {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} #{} (line {}", self.section, self.index, self.line)?;
+        if let Some(label) = &self.label {
+            write!(f, ", label '{label}'")?;
+        }
+        write!(f, "): {}", self.error)
+    }
+}
+
+/// Runs a slow step while showing `msg` to the user; the CLI passes a spinner.
+/// Key resolvers call it around key derivation.
+pub type Progress = fn(msg: &str, step: &mut dyn FnMut());
+
+/// Shows a warning the user should act on, such as a damaged vault with an intact
+/// backup; the CLI prints it to stderr.
+pub type Notice = fn(msg: &str);
+
 pub trait ByteStore: Send + Sync {
     fn read(&self) -> Result<Vec<u8>>;
     fn write(&self, bytes: &[u8]) -> Result<()>;
+    /// Replace the contents without keeping the old ones as a backup, for
+    /// bookkeeping writes (access stamps) that should not evict real history.
+    fn write_in_place(&self, bytes: &[u8]) -> Result<()> {
+        self.write(bytes)
+    }
 }
 
 // Password generator policy and trait
@@ -27,13 +70,20 @@ pub struct GenPolicy {
     pub upper: bool,
     pub digits: bool,
     pub symbols: bool,
+    /// Symbols to draw from instead of the built-in set (`symbol_set`)
+    pub symbol_set: Option<String>,
     pub avoid_ambiguous: bool,
     // Passphrase options
     pub passphrase: bool,
     pub words: u16,
     pub sep: String,
+    /// Upper bound on the generated length in characters (`generator_max_length`)
+    pub max_length: u16,
 }
 
+/// Default for `generator_max_length`.
+pub const DEFAULT_GENERATOR_MAX_LENGTH: u16 = 4096;
+
 impl Default for GenPolicy {
     fn default() -> Self {
         Self {
@@ -42,10 +92,12 @@ impl Default for GenPolicy {
             upper: true,
             digits: true,
             symbols: true,
+            symbol_set: None,
             avoid_ambiguous: true,
             passphrase: false,
             words: 6,
             sep: ":".to_string(),
+            max_length: DEFAULT_GENERATOR_MAX_LENGTH,
         }
     }
 }
@@ -60,6 +112,25 @@ pub struct DerivedKey {
     pub key: SecretBox<Vec<u8>>, // 32 bytes expected
 }
 
+impl DerivedKey {
+    /// Take ownership of key bytes and best-effort mlock them until drop.
+    pub fn new(mut key: Vec<u8>) -> Self {
+        let _ = lock_slice(&mut key);
+        Self {
+            key: SecretBox::new(Box::new(key)),
+        }
+    }
+}
+
+impl Drop for DerivedKey {
+    fn drop(&mut self) {
+        // Wipe while still locked; SecretBox zeroizes (again) and frees afterwards
+        let buf = self.key.expose_secret_mut();
+        buf.as_mut_slice().zeroize();
+        let _ = unlock_slice(buf);
+    }
+}
+
 impl core::fmt::Debug for DerivedKey {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("DerivedKey")
@@ -0,0 +1,106 @@
+use crate::vault::models::VaultEntry;
+use secrecy::ExposeSecret;
+use std::cmp::Reverse;
+
+/// Parts of an entry `search` looks at. Passwords and custom field values are
+/// never searched; custom fields match by name only.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SearchField {
+    Label,
+    User,
+    Notes,
+    Custom,
+}
+
+impl SearchField {
+    pub const ALL: [SearchField; 4] = [
+        SearchField::Label,
+        SearchField::User,
+        SearchField::Notes,
+        SearchField::Custom,
+    ];
+
+    fn weight(self) -> u32 {
+        match self {
+            SearchField::Label => 60,
+            SearchField::User => 30,
+            SearchField::Custom => 20,
+            SearchField::Notes => 10,
+        }
+    }
+}
+
+impl std::fmt::Display for SearchField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SearchField::Label => "label",
+            SearchField::User => "user",
+            SearchField::Notes => "notes",
+            SearchField::Custom => "custom",
+        })
+    }
+}
+
+/// One matching entry with the fields that matched, best match first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchHit {
+    pub label: String,
+    pub matched: Vec<SearchField>,
+    pub score: u32,
+}
+
+/// Case-insensitive substring search over `fields`, ranked by score: an exact
+/// label beats a label prefix, which beats a label substring, and every other
+/// matching field adds its weight. Ties keep label order.
+pub fn search_entries(
+    entries: &[VaultEntry],
+    term: &str,
+    fields: &[SearchField],
+) -> Vec<SearchHit> {
+    let term = term.to_lowercase();
+    let mut hits: Vec<SearchHit> = entries
+        .iter()
+        .filter_map(|e| {
+            let mut matched: Vec<SearchField> = fields
+                .iter()
+                .copied()
+                .filter(|f| field_matches(e, *f, &term))
+                .collect();
+            matched.sort();
+            matched.dedup();
+            if matched.is_empty() {
+                return None;
+            }
+            let mut score = matched.iter().map(|f| f.weight()).sum();
+            if matched.contains(&SearchField::Label) {
+                let label = e.label.to_lowercase();
+                if label == term {
+                    score += 40;
+                } else if label.starts_with(&term) {
+                    score += 20;
+                }
+            }
+            Some(SearchHit {
+                label: e.label.clone(),
+                matched,
+                score,
+            })
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        Reverse(a.score)
+            .cmp(&Reverse(b.score))
+            .then_with(|| a.label.to_lowercase().cmp(&b.label.to_lowercase()))
+    });
+    hits
+}
+
+fn field_matches(e: &VaultEntry, field: SearchField, term: &str) -> bool {
+    let has = |s: &str| s.to_lowercase().contains(term);
+    match field {
+        SearchField::Label => has(&e.label),
+        SearchField::User => e.username.as_ref().is_some_and(|u| has(u.expose_secret())),
+        SearchField::Notes => e.notes.as_deref().is_some_and(has),
+        SearchField::Custom => e.custom.keys().any(|k| has(k)),
+    }
+}
@@ -1,20 +1,44 @@
+use crate::cli::exit::KeviError;
 use crate::cryptography::memlock::{lock_slice, unlock_slice};
 use crate::cryptography::primitives::{
-    decrypt_vault_with_key, default_params, encrypt_vault_with_key, parse_kevi_header, KEY_LEN,
+    decrypt_vault_with_key, default_params, encrypt_vault_with_key, encrypt_vault_with_key_stream,
+    header_fingerprint_excluding_nonce, parse_kevi_header, KeviHeader, HEADER_LEN, KEY_LEN,
     SALT_LEN,
 };
-use crate::vault::models::VaultEntry;
-use crate::vault::ports::{ByteStore, HeaderParams, KeyResolver, VaultCodec};
-use anyhow::{Context, Result};
+use crate::vault::models::{
+    validate_label, TrashedEntry, VaultDocument, VaultEntry, SCHEMA_VERSION,
+};
+use crate::vault::ports::{
+    ByteStore, DecodeIssue, DerivedKey, HeaderParams, KeyResolver, Notice, VaultCodec,
+};
+use anyhow::Result;
 use ring::rand::{SecureRandom, SystemRandom};
 use secrecy::ExposeSecret;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use zeroize::Zeroize;
 
+pub const DEFAULT_TRASH_RETENTION_DAYS: u64 = 30;
+
+const NEWER_SCHEMA_READ_ONLY: &str =
+    "read-only: the vault was written by a newer kevi; upgrade kevi before making changes";
+
 pub struct VaultService {
     store: Arc<dyn ByteStore>,
     codec: Arc<dyn VaultCodec>,
     key_resolver: Arc<dyn KeyResolver>,
+    trash_retention_days: u64,
+    stream_encryption: bool,
+    /// Backups tried, in order, when the vault fails to decrypt; named for messages
+    backups: Vec<(String, Arc<dyn ByteStore>)>,
+    use_backup: bool,
+    /// Set once a load fell back to a backup; saving is refused from then on
+    loaded_from_backup: AtomicBool,
+    /// Set once a load found a newer schema; saving would drop its unknown fields
+    loaded_newer_schema: AtomicBool,
+    notice: Notice,
 }
 
 impl VaultService {
@@ -27,61 +51,285 @@ impl VaultService {
             store,
             codec,
             key_resolver,
+            trash_retention_days: DEFAULT_TRASH_RETENTION_DAYS,
+            stream_encryption: false,
+            backups: Vec::new(),
+            use_backup: false,
+            loaded_from_backup: AtomicBool::new(false),
+            loaded_newer_schema: AtomicBool::new(false),
+            notice: |msg| log::warn!("{msg}"),
+        }
+    }
+
+    /// How long soft-deleted entries are kept; 0 deletes permanently.
+    pub fn with_trash_retention_days(mut self, days: u64) -> Self {
+        self.trash_retention_days = days;
+        self
+    }
+
+    /// Write the chunked AEAD format (header v2) on save instead of the
+    /// single-shot one. Either format is read regardless.
+    pub fn with_stream_encryption(mut self, enabled: bool) -> Self {
+        self.stream_encryption = enabled;
+        self
+    }
+
+    /// Backups to check when the vault fails to decrypt. The first intact one
+    /// is pointed out; with `use_backup` it is read instead, for this process
+    /// only, and saving is refused so the broken vault is never overwritten.
+    pub fn with_backups(
+        mut self,
+        backups: Vec<(String, Arc<dyn ByteStore>)>,
+        use_backup: bool,
+    ) -> Self {
+        self.backups = backups;
+        self.use_backup = use_backup;
+        self
+    }
+
+    /// Where warnings about a damaged vault and its backups go (default: the log).
+    pub fn with_notice(mut self, notice: Notice) -> Self {
+        self.notice = notice;
+        self
+    }
+
+    fn encrypt(
+        &self,
+        plain: &[u8],
+        (m_cost_kib, t_cost, p_lanes): (u32, u32, u32),
+        salt: [u8; SALT_LEN],
+        key: &[u8; KEY_LEN],
+    ) -> Result<Vec<u8>> {
+        if self.stream_encryption {
+            encrypt_vault_with_key_stream(plain, m_cost_kib, t_cost, p_lanes, salt, key)
+        } else {
+            encrypt_vault_with_key(plain, m_cost_kib, t_cost, p_lanes, salt, key)
         }
     }
 
     pub fn load(&self) -> Result<Vec<VaultEntry>> {
+        Ok(self.load_document()?.entries)
+    }
+
+    /// Load entries together with the (pruned) trash.
+    pub fn load_document(&self) -> Result<VaultDocument> {
+        let Some(mut doc) = self.open(|pt| self.codec.decode(pt))? else {
+            return Ok(VaultDocument::default());
+        };
+        self.note_schema(&doc);
+        doc.prune_trash(self.trash_retention_days, now_secs());
+        Ok(doc)
+    }
+
+    /// Like `load_document`, but skips items that fail to parse and reports them.
+    pub fn load_document_lenient(&self) -> Result<(VaultDocument, Vec<DecodeIssue>)> {
+        let Some((mut doc, issues)) = self.open(|pt| self.codec.decode_lenient(pt))? else {
+            return Ok((VaultDocument::default(), Vec::new()));
+        };
+        self.note_schema(&doc);
+        doc.prune_trash(self.trash_retention_days, now_secs());
+        Ok((doc, issues))
+    }
+
+    /// Warn about a vault written by a newer kevi and refuse to save over it, since
+    /// fields this build does not know would be lost.
+    fn note_schema(&self, doc: &VaultDocument) {
+        if doc.schema_version > SCHEMA_VERSION
+            && !self.loaded_newer_schema.swap(true, Ordering::Relaxed)
+        {
+            (self.notice)(&format!(
+                "The vault was written by a newer kevi (schema {}, this build knows {SCHEMA_VERSION}); it can be read, but changes cannot be saved until kevi is upgraded.",
+                doc.schema_version
+            ));
+        }
+    }
+
+    /// Check that the vault decrypts under the resolved key, without decoding
+    /// the entries. A missing or empty vault is an error.
+    pub fn verify_decrypt(&self) -> Result<()> {
+        match self.open(|_| Ok(()))? {
+            Some(()) => Ok(()),
+            None => anyhow::bail!("vault is empty or does not exist"),
+        }
+    }
+
+    /// Entry counts for other copies of the vault, such as backups, opened with
+    /// the live vault's key, which is resolved once. A copy written under another
+    /// key or salt fails with `KeviError::WrongPassword` without deriving a key for
+    /// it; with no readable live vault, every copy does.
+    pub fn count_entries_in(
+        &self,
+        copies: impl IntoIterator<Item = Result<Vec<u8>>>,
+    ) -> Result<Vec<Result<usize>>> {
+        let live = self.store.read()?;
+        let key = match read_header(&live) {
+            Ok(hdr) if !live.is_empty() => {
+                let dk = self.key_resolver.resolve_for_header(&hdr)?;
+                Some((header_fingerprint_excluding_nonce(&hdr), dk))
+            }
+            _ => None,
+        };
+        let count = |bytes: Vec<u8>| -> Result<usize> {
+            let fingerprint = header_fingerprint_excluding_nonce(&read_header(&bytes)?);
+            let Some((_, dk)) = key.as_ref().filter(|(fp, _)| *fp == fingerprint) else {
+                return Err(KeviError::WrongPassword.into());
+            };
+            let pt = self
+                .decrypt_with_key(&bytes, dk)
+                .map_err(|_| anyhow::anyhow!("damaged: does not decrypt with its own key"))?;
+            Ok(decode_wiped(pt, |pt| self.codec.decode(pt))?.entries.len())
+        };
+        Ok(copies.into_iter().map(|bytes| count(bytes?)).collect())
+    }
+
+    /// Read, decrypt and decode the vault with `decode`; `None` for a missing/empty
+    /// file. Once the key is resolved, a vault that fails to decrypt or decode
+    /// falls back to the backups (see `recover_from_backup`).
+    fn open<T>(&self, decode: impl Fn(&[u8]) -> Result<T>) -> Result<Option<T>> {
         let bytes = self.store.read()?;
         if bytes.is_empty() {
-            return Ok(Vec::new());
-        }
-        if !bytes.starts_with(b"KEVI") {
-            anyhow::bail!(
-                "unsupported vault format: missing KEVI header (plaintext is not allowed)"
-            );
+            return Ok(None);
         }
-        let (hdr, _off) =
-            parse_kevi_header(&bytes).map_err(|e| anyhow::anyhow!("invalid header: {e}"))?;
+        let hdr = read_header(&bytes)?;
         let dk = self.key_resolver.resolve_for_header(&hdr)?;
-        // Convert key vec to array for ring API
-        let key_vec = dk.key.expose_secret().clone();
+        match self
+            .decrypt_with_key(&bytes, &dk)
+            .and_then(|pt| decode_wiped(pt, &decode))
+        {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => self.recover_from_backup(e, &hdr, &dk, &decode).map(Some),
+        }
+    }
+
+    /// The vault failed with `err` under `dk`: find the first backup written with
+    /// the same key that opens and either load it (`use_backup`) or say how to use
+    /// it, then fail with `err`. Backups under another key or salt are skipped, so
+    /// this never prompts or derives.
+    fn recover_from_backup<T>(
+        &self,
+        err: anyhow::Error,
+        hdr: &KeviHeader,
+        dk: &DerivedKey,
+        decode: impl Fn(&[u8]) -> Result<T>,
+    ) -> Result<T> {
+        let fingerprint = header_fingerprint_excluding_nonce(hdr);
+        for (name, store) in &self.backups {
+            let Ok(bytes) = store.read() else { continue };
+            if bytes.is_empty() {
+                continue;
+            }
+            let same_key = read_header(&bytes)
+                .is_ok_and(|h| header_fingerprint_excluding_nonce(&h) == fingerprint);
+            if !same_key {
+                log::debug!("backup {name} was written under another key; skipping");
+                continue;
+            }
+            let Ok(value) = self
+                .decrypt_with_key(&bytes, dk)
+                .and_then(|pt| decode_wiped(pt, &decode))
+            else {
+                log::debug!("backup {name} does not open either");
+                continue;
+            };
+            if self.use_backup {
+                self.loaded_from_backup.store(true, Ordering::Relaxed);
+                (self.notice)(&format!(
+                    "The vault failed to open ({err}); reading backup {name} instead. Changes cannot be saved until the vault is restored."
+                ));
+                return Ok(value);
+            }
+            (self.notice)(&format!(
+                "The vault failed to open, but backup {name} is intact. Rerun with --use-backup to read it, or copy it over the vault file to restore it."
+            ));
+            break;
+        }
+        Err(err)
+    }
+
+    fn decrypt_with_key(&self, bytes: &[u8], dk: &DerivedKey) -> Result<Vec<u8>> {
+        // Copy the key into an array for the ring API
         let mut key_arr = [0u8; KEY_LEN];
-        key_arr.copy_from_slice(&key_vec[..KEY_LEN]);
+        key_arr.copy_from_slice(&dk.key.expose_secret()[..KEY_LEN]);
         // Best‑effort lock while in use
         let _ = lock_slice(&mut key_arr);
-        let pt = decrypt_vault_with_key(&bytes, &key_arr)
-            .context("Failed to decrypt vault (wrong key?)")?;
+        let pt =
+            decrypt_vault_with_key(bytes, &key_arr).map_err(|_| KeviError::WrongPassword.into());
         // Always unlock + zeroize
         let _ = unlock_slice(&mut key_arr);
         key_arr.zeroize();
-        self.codec.decode(&pt)
+        pt
     }
 
+    /// Save entries, keeping whatever trash the vault already holds.
     pub fn save(&self, entries: &[VaultEntry]) -> Result<()> {
-        let plain = self.codec.encode(entries)?;
+        let doc = VaultDocument {
+            entries: entries.to_vec(),
+            ..Default::default()
+        };
+        self.write_document(doc, true, true)
+    }
+
+    /// Save entries and trash exactly as given.
+    pub fn save_document(&self, doc: &VaultDocument) -> Result<()> {
+        self.write_document(doc.clone(), false, true)
+    }
+
+    /// Encrypt and store `doc`. `keep_trash` carries over the stored trash;
+    /// without `rotate_backups` the vault is replaced without a new backup.
+    fn write_document(
+        &self,
+        mut doc: VaultDocument,
+        keep_trash: bool,
+        rotate_backups: bool,
+    ) -> Result<()> {
+        if self.loaded_from_backup.load(Ordering::Relaxed) {
+            anyhow::bail!(
+                "read-only: this run loaded a backup (--use-backup); restore the vault file before making changes"
+            );
+        }
+        if self.loaded_newer_schema.load(Ordering::Relaxed) {
+            anyhow::bail!(NEWER_SCHEMA_READ_ONLY);
+        }
         let bytes = self.store.read()?;
         if !bytes.is_empty() {
+            ensure_not_truncated(&bytes)?;
             // Reuse existing header params and salt, generate new nonce
             let (hdr, _off) =
                 parse_kevi_header(&bytes).map_err(|e| anyhow::anyhow!("invalid header: {e}"))?;
             let dk = self.key_resolver.resolve_for_header(&hdr)?;
-            let key_vec = dk.key.expose_secret().clone();
             let mut key_arr = [0u8; KEY_LEN];
-            key_arr.copy_from_slice(&key_vec[..KEY_LEN]);
+            key_arr.copy_from_slice(&dk.key.expose_secret()[..KEY_LEN]);
             let _ = lock_slice(&mut key_arr);
-            let ct = encrypt_vault_with_key(
-                &plain,
-                hdr.m_cost_kib,
-                hdr.t_cost,
-                hdr.p_lanes,
-                hdr.salt,
-                &key_arr,
-            )?;
+            let result = (|| {
+                if keep_trash {
+                    let pt = decrypt_vault_with_key(&bytes, &key_arr)
+                        .map_err(|_| KeviError::WrongPassword)?;
+                    let stored = self.codec.decode(&pt)?;
+                    if stored.schema_version > SCHEMA_VERSION {
+                        anyhow::bail!(NEWER_SCHEMA_READ_ONLY);
+                    }
+                    doc.trash = stored.trash;
+                }
+                doc.prune_trash(self.trash_retention_days, now_secs());
+                let plain = self.codec.encode(&doc)?;
+                self.encrypt(
+                    &plain,
+                    (hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes),
+                    hdr.salt,
+                    &key_arr,
+                )
+            })();
             let _ = unlock_slice(&mut key_arr);
             key_arr.zeroize();
-            self.store.write(&ct)
+            if rotate_backups {
+                self.store.write(&result?)
+            } else {
+                self.store.write_in_place(&result?)
+            }
         } else {
             // New vault: generate params + salt, derive/cached key, encrypt and write
+            doc.prune_trash(self.trash_retention_days, now_secs());
+            let plain = self.codec.encode(&doc)?;
             let (m_cost_kib, t_cost, p_lanes) = default_params();
             let mut salt = [0u8; SALT_LEN];
             SystemRandom::new()
@@ -93,31 +341,203 @@ impl VaultService {
                 p_lanes,
             };
             let dk = self.key_resolver.resolve_for_new_vault(params, salt)?;
-            let key_vec = dk.key.expose_secret().clone();
             let mut key_arr = [0u8; KEY_LEN];
-            key_arr.copy_from_slice(&key_vec[..KEY_LEN]);
+            key_arr.copy_from_slice(&dk.key.expose_secret()[..KEY_LEN]);
             let _ = lock_slice(&mut key_arr);
-            let ct = encrypt_vault_with_key(&plain, m_cost_kib, t_cost, p_lanes, salt, &key_arr)?;
+            let ct = self.encrypt(&plain, (m_cost_kib, t_cost, p_lanes), salt, &key_arr)?;
             let _ = unlock_slice(&mut key_arr);
             key_arr.zeroize();
             self.store.write(&ct)
         }
     }
 
-    pub fn add_entry(&self, entry: VaultEntry) -> Result<()> {
+    /// Encrypt `plain` as a standalone container under this vault's key, KDF
+    /// params and salt (with a fresh nonce), so the vault's password opens it.
+    pub fn seal_with_vault_key(&self, plain: &[u8]) -> Result<Vec<u8>> {
+        let bytes = self.store.read()?;
+        if bytes.is_empty() {
+            anyhow::bail!("vault is empty or does not exist");
+        }
+        ensure_not_truncated(&bytes)?;
+        let (hdr, _off) =
+            parse_kevi_header(&bytes).map_err(|e| anyhow::anyhow!("invalid header: {e}"))?;
+        let dk = self.key_resolver.resolve_for_header(&hdr)?;
+        let mut key_arr = [0u8; KEY_LEN];
+        key_arr.copy_from_slice(&dk.key.expose_secret()[..KEY_LEN]);
+        let _ = lock_slice(&mut key_arr);
+        let result = encrypt_vault_with_key(
+            plain,
+            hdr.m_cost_kib,
+            hdr.t_cost,
+            hdr.p_lanes,
+            hdr.salt,
+            &key_arr,
+        );
+        let _ = unlock_slice(&mut key_arr);
+        key_arr.zeroize();
+        result
+    }
+
+    /// Load once, let `f` add/remove/edit entries, validate the labels it added
+    /// or renamed to, and save once. If `f` or validation fails nothing is written.
+    pub fn batch<F>(&self, f: F) -> Result<()>
+    where
+        F: FnOnce(&mut Vec<VaultEntry>) -> Result<()>,
+    {
         let mut entries = self.load()?;
-        entries.push(entry);
+        let before = label_counts(&entries);
+        f(&mut entries)?;
+        validate_new_labels(&before, &entries)?;
         self.save(&entries)
     }
 
-    pub fn remove_entry(&self, label: &str) -> Result<bool> {
+    pub fn add_entry(&self, mut entry: VaultEntry) -> Result<()> {
+        validate_label(&entry.label)?;
+        let now = now_secs();
+        entry.created_at.get_or_insert(now);
+        entry.updated_at.get_or_insert(now);
+        self.batch(|entries| {
+            entries.push(entry);
+            Ok(())
+        })
+    }
+
+    /// Stamp `label` as accessed now. Unlike edits this leaves `updated_at` alone,
+    /// and the vault is rewritten without rotating backups so reads do not push
+    /// real history out of them.
+    pub fn record_access(&self, label: &str) -> Result<()> {
         let mut entries = self.load()?;
-        let before = entries.len();
-        entries.retain(|e| e.label != label);
-        let removed = entries.len() != before;
-        if removed {
-            self.save(&entries)?;
+        let Some(entry) = entries.iter_mut().find(|e| e.label == label) else {
+            return Ok(());
+        };
+        entry.last_accessed_at = Some(now_secs());
+        let doc = VaultDocument {
+            entries,
+            ..Default::default()
+        };
+        self.write_document(doc, true, false)
+    }
+
+    /// Soft-delete: move matching entries into the trash.
+    pub fn remove_entry(&self, label: &str) -> Result<bool> {
+        Ok(!self.remove_entries(&[label.to_string()])?.is_empty())
+    }
+
+    /// Soft-delete several labels with one load and one save. Returns the labels
+    /// that existed and were moved to the trash; nothing is written if none did.
+    pub fn remove_entries(&self, labels: &[String]) -> Result<Vec<String>> {
+        let mut doc = self.load_document()?;
+        let (removed, kept): (Vec<_>, Vec<_>) = doc
+            .entries
+            .into_iter()
+            .partition(|e| labels.contains(&e.label));
+        doc.entries = kept;
+        if removed.is_empty() {
+            return Ok(Vec::new());
         }
-        Ok(removed)
+        let names = removed.iter().map(|e| e.label.clone()).collect();
+        let deleted_at = now_secs();
+        doc.trash.extend(
+            removed
+                .into_iter()
+                .map(|entry| TrashedEntry { entry, deleted_at }),
+        );
+        self.save_document(&doc)?;
+        Ok(names)
     }
+
+    /// Move the most recently trashed entry with `label` back into the vault.
+    pub fn restore_entry(&self, label: &str) -> Result<bool> {
+        let mut doc = self.load_document()?;
+        let Some(idx) = doc
+            .trash
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.entry.label == label)
+            .max_by_key(|(_, t)| t.deleted_at)
+            .map(|(i, _)| i)
+        else {
+            return Ok(false);
+        };
+        if doc.entries.iter().any(|e| e.label == label) {
+            anyhow::bail!("an entry labelled '{label}' already exists; remove or rename it first");
+        }
+        let trashed = doc.trash.remove(idx);
+        doc.entries.push(trashed.entry);
+        self.save_document(&doc)?;
+        Ok(true)
+    }
+
+    /// Permanently delete everything in the trash; returns how many entries were purged.
+    pub fn empty_trash(&self) -> Result<usize> {
+        let mut doc = self.load_document()?;
+        let purged = doc.trash.len();
+        if purged > 0 {
+            doc.trash.clear();
+            self.save_document(&doc)?;
+        }
+        Ok(purged)
+    }
+}
+
+fn label_counts(entries: &[VaultEntry]) -> HashMap<String, usize> {
+    let mut counts = HashMap::with_capacity(entries.len());
+    for e in entries {
+        *counts.entry(e.label.clone()).or_default() += 1;
+    }
+    counts
+}
+
+/// Labels that occur more often than `before` must be valid and unique. Entries
+/// the batch left alone are not rechecked.
+fn validate_new_labels(before: &HashMap<String, usize>, entries: &[VaultEntry]) -> Result<()> {
+    for (label, count) in label_counts(entries) {
+        if count <= before.get(&label).copied().unwrap_or(0) {
+            continue;
+        }
+        validate_label(&label)?;
+        if count > 1 {
+            anyhow::bail!("duplicate label '{label}'");
+        }
+    }
+    Ok(())
+}
+
+/// A non-empty file shorter than the header is a failed or partial write; refuse
+/// to treat it as a new vault so the original can still be recovered.
+pub fn ensure_not_truncated(bytes: &[u8]) -> Result<()> {
+    if !bytes.is_empty() && bytes.len() < HEADER_LEN {
+        anyhow::bail!(
+            "vault file is truncated or corrupt ({} bytes, header needs {HEADER_LEN}); \
+             restore it from a backup (`<vault>.1`, `<vault>.2`, ...) or remove it to start over",
+            bytes.len()
+        );
+    }
+    Ok(())
+}
+
+/// The KEVI header of a non-empty vault file.
+fn read_header(bytes: &[u8]) -> Result<KeviHeader> {
+    ensure_not_truncated(bytes)?;
+    if !bytes.starts_with(b"KEVI") {
+        anyhow::bail!("unsupported vault format: missing KEVI header (plaintext is not allowed)");
+    }
+    let (hdr, _off) =
+        parse_kevi_header(bytes).map_err(|e| anyhow::anyhow!("invalid header: {e}"))?;
+    Ok(hdr)
+}
+
+/// Run `decode` on decrypted vault bytes, wiping them afterwards.
+fn decode_wiped<T>(mut pt: Vec<u8>, decode: impl Fn(&[u8]) -> Result<T>) -> Result<T> {
+    let value = decode(&pt);
+    pt.zeroize();
+    value
+}
+
+/// Current Unix time in seconds (0 if the clock is before the epoch).
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
 }
@@ -0,0 +1,59 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use secrecy::ExposeSecret;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::vault::persistence::load_vault_file;
+
+#[test]
+fn add_from_uri_fills_label_user_password_and_totp() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+    let add = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", pw)
+            .arg("add")
+            .arg("--path")
+            .arg(&path)
+            .args(args);
+        cmd.assert()
+    };
+
+    add(&["--from-uri", "alice:s3:cr@t@github"]).success();
+    add(&[
+        "--from-uri",
+        "otpauth://totp/GitLab:bob?secret=JBSWY3DPEHPK3PXP&issuer=GitLab",
+        "--generate",
+        "--yes",
+    ])
+    .success();
+    add(&["--from-uri", "alice:pw@other", "--generate"])
+        .failure()
+        .stderr(predicate::str::contains("drop --generate"));
+    add(&["--from-uri", "alice@github"])
+        .failure()
+        .stderr(predicate::str::contains("expected user:password@label"));
+
+    let entries = load_vault_file(&path, pw).expect("load vault");
+    assert_eq!(entries.len(), 2);
+    let github = &entries[0];
+    assert_eq!(github.label, "github");
+    assert_eq!(
+        github.username.as_ref().map(|u| u.expose_secret()),
+        Some("alice")
+    );
+    assert_eq!(github.password.expose_secret(), "s3:cr@t");
+    let gitlab = &entries[1];
+    assert_eq!(gitlab.label, "GitLab");
+    assert_eq!(
+        gitlab.username.as_ref().map(|u| u.expose_secret()),
+        Some("bob")
+    );
+    assert_eq!(
+        gitlab.custom.get("totp").map(|t| t.expose_secret()),
+        Some("JBSWY3DPEHPK3PXP")
+    );
+    assert!(!gitlab.password.expose_secret().is_empty());
+}
@@ -1,4 +1,5 @@
 use assert_cmd::prelude::*;
+use predicates::prelude::*;
 use secrecy::ExposeSecret;
 use std::process::Command;
 use tempfile::tempdir;
@@ -81,3 +82,272 @@ fn cli_add_generate_passphrase_mode_produces_words() {
     assert!(parts.iter().all(|w| !w.is_empty()));
     assert!(secret.chars().all(|c| c.is_ascii_lowercase() || c == ':'));
 }
+
+#[test]
+fn add_rejects_label_with_control_characters() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    for label in ["with\ttab", "with\nnewline"] {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", "pw")
+            .arg("add")
+            .arg("--path")
+            .arg(path.to_string_lossy().to_string())
+            .arg("--generate")
+            .arg("--label")
+            .arg(label)
+            .arg("--user")
+            .arg("")
+            .arg("--notes")
+            .arg("");
+        cmd.assert().failure().stderr(predicate::str::contains(
+            "label must not contain control characters",
+        ));
+    }
+    assert!(load_vault_file(&path, "pw").unwrap().is_empty());
+}
+
+#[test]
+fn add_rejects_notes_over_configured_limit() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .env("KEVI_MAX_NOTES_BYTES", "8")
+        .arg("add")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string())
+        .arg("--generate")
+        .arg("--label")
+        .arg("big")
+        .arg("--user")
+        .arg("")
+        .arg("--notes")
+        .arg("this is more than eight bytes");
+    cmd.assert().failure().stderr(predicate::str::contains(
+        "over the 8-byte limit (max_notes_bytes)",
+    ));
+    assert!(load_vault_file(&path, "pw").unwrap().is_empty());
+}
+
+#[test]
+fn add_rejects_lengths_over_generator_max_length() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let add = |length: &str, max: Option<&str>| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", "pw")
+            .env_remove("KEVI_GEN_MAX_LENGTH")
+            .arg("add")
+            .arg("--path")
+            .arg(&path)
+            .arg("--generate")
+            .arg("--yes")
+            .arg("--length")
+            .arg(length)
+            .arg("--label")
+            .arg(format!("len-{length}"))
+            .arg("--user")
+            .arg("");
+        if let Some(max) = max {
+            cmd.env("KEVI_GEN_MAX_LENGTH", max);
+        }
+        cmd.assert()
+    };
+
+    // Default cap is 4096
+    add("4096", None).success();
+    add("4097", None)
+        .failure()
+        .stderr(predicate::str::contains("generator_max_length"));
+    add("65000", None)
+        .failure()
+        .stderr(predicate::str::contains(
+            "exceeds the generator maximum of 4096",
+        ));
+    add("33", Some("32"))
+        .failure()
+        .stderr(predicate::str::contains("maximum of 32"));
+    add("65535", Some("65535")).success();
+
+    let entries = load_vault_file(&path, "pw").unwrap();
+    let lengths: Vec<usize> = entries
+        .iter()
+        .map(|e| e.password.expose_secret().len())
+        .collect();
+    assert_eq!(lengths, [4096, 65535]);
+}
+
+#[test]
+fn add_max_length_clamps_generated_password_and_get_warns_when_long() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .arg("add")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string())
+        .arg("--generate")
+        .arg("--length")
+        .arg("32")
+        .arg("--max-length")
+        .arg("16")
+        .arg("--label")
+        .arg("capped")
+        .arg("--user")
+        .arg("")
+        .arg("--notes")
+        .arg("");
+    cmd.assert().success().stderr(predicate::str::contains(
+        "Generating 16 characters instead of 32",
+    ));
+    let entries = load_vault_file(&path, "pw").unwrap();
+    assert_eq!(entries[0].password.expose_secret().len(), 16);
+
+    let get = |limit: &str| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", "pw")
+            .env("KEVI_WARN_PASSWORD_LEN", limit)
+            .args(["get", "capped", "--no-copy", "--path"])
+            .arg(&path);
+        cmd.assert().success()
+    };
+    get("12").stderr(predicate::str::contains(
+        "Password of 'capped' is 16 characters, over warn_password_len (12)",
+    ));
+    get("16").stderr(predicate::str::contains("warn_password_len").not());
+}
+
+#[test]
+fn cli_edit_rotate_like_current_keeps_length_and_classes() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+    let path_arg = path.to_string_lossy().to_string();
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", pw).args([
+        "add",
+        "--path",
+        &path_arg,
+        "--generate",
+        "--length",
+        "12",
+        "--no-symbols",
+        "--label",
+        "site",
+    ]);
+    cmd.assert().success();
+    let before: Vec<VaultEntry> = load_vault_file(&path, pw).expect("load vault");
+    let old = before[0].password.expose_secret().to_string();
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", pw).args([
+        "edit",
+        "site",
+        "--rotate-like-current",
+        "--path",
+        &path_arg,
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Rotated password for 'site'"))
+        .stdout(predicate::str::contains(old.as_str()).not());
+
+    let after: Vec<VaultEntry> = load_vault_file(&path, pw).expect("load vault");
+    let new = after[0].password.expose_secret().to_string();
+    assert_ne!(new, old);
+    assert_eq!(new.len(), 12);
+    assert!(new.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", pw)
+        .args(["edit", "site", "--path", &path_arg]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--rotate-like-current"));
+}
+
+#[test]
+fn cli_edit_appends_and_prepends_note_lines() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+    let path_arg = path.to_string_lossy().to_string();
+    let entry = VaultEntry {
+        notes: Some("line one\nline two".into()),
+        ..VaultEntry::new("site", "unchanged")
+    };
+    kevi::vault::persistence::save_vault_file(&[entry], &path, pw).unwrap();
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", pw).args([
+        "edit",
+        "site",
+        "--append-note",
+        "renew in May",
+        "--prepend-note",
+        "shared account",
+        "--path",
+        &path_arg,
+    ]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Updated notes for 'site'"));
+
+    let after: Vec<VaultEntry> = load_vault_file(&path, pw).expect("load vault");
+    assert_eq!(
+        after[0].notes.as_deref(),
+        Some("shared account\nline one\nline two\nrenew in May")
+    );
+    assert_eq!(after[0].password.expose_secret(), "unchanged");
+    assert!(after[0].updated_at.is_some());
+}
+
+#[test]
+fn cli_add_label_template_adds_many_and_skips_taken_labels() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+    let add = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", pw)
+            .arg("add")
+            .arg("--path")
+            .arg(&path)
+            .arg("--generate")
+            .arg("--yes")
+            .args(args);
+        cmd.assert()
+    };
+
+    add(&["--label", "db-2"]).success();
+    add(&[
+        "--label-template",
+        "db-{n}",
+        "--count",
+        "3",
+        "--user",
+        "svc",
+    ])
+    .success()
+    .stdout(predicate::str::contains("Skipping 'db-2'"))
+    .stdout(predicate::str::contains("Added 2 entries."));
+    add(&["--label-template", "api-{rand6}", "--count", "4"])
+        .success()
+        .stdout(predicate::str::contains("Added 4 entries."));
+    add(&["--label-template", "static", "--count", "2"])
+        .failure()
+        .stderr(predicate::str::contains("needs {n} or {randK}"));
+
+    let entries: Vec<VaultEntry> = load_vault_file(&path, pw).expect("load vault");
+    let mut labels: Vec<&str> = entries.iter().map(|e| e.label.as_str()).collect();
+    labels.sort();
+    assert_eq!(labels.len(), 7);
+    assert_eq!(&labels[4..], ["db-1", "db-2", "db-3"]);
+    assert!(labels[..4].iter().all(|l| l.len() == "api-".len() + 6));
+    let db1 = entries.iter().find(|e| e.label == "db-1").unwrap();
+    let db3 = entries.iter().find(|e| e.label == "db-3").unwrap();
+    assert_eq!(db1.username.as_ref().unwrap().expose_secret(), "svc");
+    assert_ne!(db1.password.expose_secret(), db3.password.expose_secret());
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "age")]
+
+use kevi::filesystem::store::FileByteStore;
+use kevi::session_management::age_resolver::{age_sidecar_file_for, AgeKeyResolver};
+use kevi::vault::codec::RonCodec;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::service::VaultService;
+use secrecy::ExposeSecret;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::tempdir;
+
+fn write_identity(path: &Path) -> age::x25519::Identity {
+    let id = age::x25519::Identity::generate();
+    let content = format!(
+        "# created: test\n# public key: {}\n{}\n",
+        id.to_public(),
+        id.to_string().expose_secret()
+    );
+    fs::write(path, content).unwrap();
+    id
+}
+
+#[test]
+fn age_resolver_round_trips_vault_without_passphrase() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let id_path = td.path().join("key.txt");
+    write_identity(&id_path);
+
+    let store = Arc::new(FileByteStore::new(path.clone()));
+    let codec = Arc::new(RonCodec);
+    let resolver = Arc::new(AgeKeyResolver::new(path.clone(), id_path.clone(), None));
+    let svc = VaultService::new(store, codec, resolver);
+
+    let entry = VaultEntry::new("age", "wrapped!");
+    svc.save(&[entry]).expect("save with age-wrapped key");
+    assert!(age_sidecar_file_for(&path).exists());
+
+    let loaded = svc.load().expect("load with age identity");
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].password.expose_secret(), "wrapped!");
+}
+
+#[test]
+fn age_resolver_rejects_foreign_identity() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let owner = td.path().join("owner.txt");
+    let other = td.path().join("other.txt");
+    write_identity(&owner);
+    write_identity(&other);
+
+    let store = Arc::new(FileByteStore::new(path.clone()));
+    let svc_owner = VaultService::new(
+        store.clone(),
+        Arc::new(RonCodec),
+        Arc::new(AgeKeyResolver::new(path.clone(), owner, None)),
+    );
+    svc_owner.save(&[]).expect("create vault");
+
+    let svc_other = VaultService::new(
+        store,
+        Arc::new(RonCodec),
+        Arc::new(AgeKeyResolver::new(path, other, None)),
+    );
+    let err = svc_other.load().unwrap_err();
+    assert!(format!("{err}").contains("unwrap vault key"));
+}
+
+#[test]
+fn age_sidecar_appends_to_vault_name_and_finds_legacy_sidecar() {
+    let dir = tempdir().unwrap();
+    let multi = dir.path().join("vault.prod.ron");
+    assert_eq!(
+        age_sidecar_file_for(&multi),
+        dir.path().join("vault.prod.ron.agekey")
+    );
+    assert_eq!(
+        age_sidecar_file_for(&dir.path().join("secrets")),
+        dir.path().join("secrets.agekey")
+    );
+
+    let legacy = dir.path().join("vault.prod.agekey");
+    std::fs::write(&legacy, b"{}").unwrap();
+    assert_eq!(age_sidecar_file_for(&multi), legacy);
+}
+
+#[test]
+fn export_of_an_age_vault_needs_its_own_password() {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    use std::process::Command;
+
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let id_path = td.path().join("key.txt");
+    write_identity(&id_path);
+    let svc = VaultService::new(
+        Arc::new(FileByteStore::new(path.clone())),
+        Arc::new(RonCodec),
+        Arc::new(AgeKeyResolver::new(path.clone(), id_path.clone(), None)),
+    );
+    svc.save(&[VaultEntry::new("age", "wrapped!")]).unwrap();
+
+    let out = td.path().join("export.kevi");
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_AGE_IDENTITY", &id_path)
+        .env("KEVI_CONFIG_DIR", td.path())
+        .args(["--no-prompt", "export", "--encrypted", "--path"])
+        .arg(&path)
+        .arg("--out")
+        .arg(&out);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--new-password"));
+    assert!(!out.exists());
+}
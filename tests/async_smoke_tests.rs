@@ -1,5 +1,5 @@
 use kevi::config::app_config::Config;
-use kevi::vault::handlers::Vault;
+use kevi::vault::handlers::{ListOutput, Vault};
 use kevi::vault::models::VaultEntry;
 use kevi::vault::persistence::save_vault_file;
 use secrecy::SecretString;
@@ -13,17 +13,15 @@ async fn vault_handle_header_async_ok() {
 
     // Seed minimal encrypted vault
     let entries: Vec<VaultEntry> = vec![VaultEntry {
-        label: "x".into(),
         username: Some(SecretString::new("u".into())),
-        password: SecretString::new("p".into()),
-        notes: None,
+        ..VaultEntry::new("x", "p")
     }];
     save_vault_file(&entries, &path, pw).expect("seed vault");
 
     // Run async header handler
     let cfg = Config::create(Some(path.clone()), None).unwrap();
     let v = Vault::create(&cfg);
-    let res = v.handle_header().await;
+    let res = v.handle_header(false).await;
     assert!(res.is_ok());
 }
 
@@ -35,17 +33,10 @@ async fn vault_handle_list_async_ok() {
 
     // Seed with two entries
     let entries: Vec<VaultEntry> = vec![
+        VaultEntry::new("alpha", "a"),
         VaultEntry {
-            label: "alpha".into(),
-            username: None,
-            password: SecretString::new("a".into()),
-            notes: None,
-        },
-        VaultEntry {
-            label: "beta".into(),
             username: Some(SecretString::new("b".into())),
-            password: SecretString::new("b".into()),
-            notes: None,
+            ..VaultEntry::new("beta", "b")
         },
     ];
     save_vault_file(&entries, &path, pw).expect("seed vault");
@@ -56,6 +47,8 @@ async fn vault_handle_list_async_ok() {
     let cfg = Config::create(Some(path.clone()), None).unwrap();
     let v = Vault::create(&cfg);
     // Run list without query/json to exercise an async path
-    let res = v.handle_list(None, false, false).await;
+    let res = v
+        .handle_list(None, false, ListOutput::Text, None, false, &[])
+        .await;
     assert!(res.is_ok());
 }
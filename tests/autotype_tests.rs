@@ -0,0 +1,92 @@
+use assert_cmd::Command;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn seed(path: &Path) {
+    let entry = VaultEntry::new("db", "s3cr3t");
+    save_vault_file(&[entry], path, "pw").expect("seed vault");
+}
+
+#[cfg(not(feature = "autotype"))]
+#[test]
+fn get_type_without_feature_fails_instead_of_copying() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .args(["get", "db", "--type", "--path"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("`autotype` feature"));
+}
+
+#[cfg(feature = "autotype")]
+#[test]
+fn tool_follows_the_display_server() {
+    use kevi::filesystem::autotype::{tool_for_session, AutoTypeTool};
+    assert_eq!(tool_for_session(true, true), Some(AutoTypeTool::Wtype));
+    assert_eq!(tool_for_session(false, true), Some(AutoTypeTool::Xdotool));
+    assert_eq!(tool_for_session(false, false), None);
+}
+
+#[cfg(all(feature = "autotype", unix))]
+#[test]
+fn get_type_feeds_the_secret_on_stdin_and_fails_without_the_tool() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+    let bin = dir.path().join("bin");
+    std::fs::create_dir(&bin).unwrap();
+    let log = dir.path().join("typed");
+
+    // Missing tool: an error, never a silent clipboard fallback
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .env("PATH", &bin)
+        .env("DISPLAY", ":99")
+        .env_remove("WAYLAND_DISPLAY")
+        .args(["get", "db", "--type", "--path"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("needs `xdotool` on PATH"));
+
+    let fake = bin.join("xdotool");
+    std::fs::write(
+        &fake,
+        format!(
+            "#!/bin/sh\necho \"$*\" > '{0}.args'\ncat > '{0}'\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    std::fs::set_permissions(&fake, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .env("PATH", format!("{}:/bin:/usr/bin", bin.display()))
+        .env("DISPLAY", ":99")
+        .env_remove("WAYLAND_DISPLAY")
+        .args(["get", "db", "--type", "--path"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("s3cr3t").not());
+    assert_eq!(std::fs::read_to_string(&log).unwrap(), "s3cr3t");
+    let args = std::fs::read_to_string(dir.path().join("typed.args")).unwrap();
+    assert!(
+        !args.contains("s3cr3t"),
+        "secret must not be passed in argv"
+    );
+}
@@ -1,3 +1,4 @@
+use assert_cmd::prelude::*;
 use kevi::cryptography::primitives::decrypt_vault;
 use kevi::vault::models::VaultEntry;
 use kevi::vault::persistence::save_vault_file;
@@ -5,6 +6,7 @@ use secrecy::SecretString;
 #[cfg(target_family = "unix")]
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::{fs, slice};
 use tempfile::tempdir;
 
@@ -23,28 +25,22 @@ fn rotating_backups_keep_two_versions_and_prune() {
 
     // First content
     let e1 = VaultEntry {
-        label: "one".into(),
         username: Some(SecretString::new("u1".into())),
-        password: SecretString::new("p1".into()),
-        notes: None,
+        ..VaultEntry::new("one", "p1")
     };
     save_vault_file(slice::from_ref(&e1), &path, pw).expect("save 1");
 
     // Second content
     let e2 = VaultEntry {
-        label: "two".into(),
         username: Some(SecretString::new("u2".into())),
-        password: SecretString::new("p2".into()),
-        notes: None,
+        ..VaultEntry::new("two", "p2")
     };
     save_vault_file(slice::from_ref(&e2), &path, pw).expect("save 2");
 
     // Third content
     let e3 = VaultEntry {
-        label: "three".into(),
         username: Some(SecretString::new("u3".into())),
-        password: SecretString::new("p3".into()),
-        notes: None,
+        ..VaultEntry::new("three", "p3")
     };
     save_vault_file(slice::from_ref(&e3), &path, pw).expect("save 3");
 
@@ -84,3 +80,144 @@ fn rotating_backups_keep_two_versions_and_prune() {
         assert_eq!(mode2, 0o600);
     }
 }
+
+#[test]
+fn corrupt_vault_points_to_intact_backup_and_use_backup_reads_it() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    let pw = "pw";
+    let entry = |label: &str| VaultEntry::new(label, "secret");
+    save_vault_file(&[entry("old")], &path, pw).expect("save 1");
+
+    let kevi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", pw)
+            .env("KEVI_CONFIG_DIR", dir.path())
+            .args(args)
+            .arg("--path")
+            .arg(&path);
+        cmd.assert()
+    };
+    // Saving through kevi keeps the salt, so the `.1` backup opens with the same key
+    kevi(&["add", "--generate", "--label", "new"]).success();
+
+    // Damage the ciphertext of the main file; the `.1` backup stays intact
+    let mut bytes = fs::read(&path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0xff;
+    fs::write(&path, &bytes).unwrap();
+
+    let out = kevi(&["list", "--porcelain"]).failure();
+    let stderr = String::from_utf8_lossy(&out.get_output().stderr).to_string();
+    assert!(
+        stderr.contains(&format!("backup {} is intact", bp(&path, 1).display())),
+        "{stderr}"
+    );
+    assert!(stderr.contains("--use-backup"), "{stderr}");
+
+    let out = kevi(&["--use-backup", "list", "--porcelain"]).success();
+    assert_eq!(out.get_output().stdout, b"old\n");
+    let stderr = String::from_utf8_lossy(&out.get_output().stderr).to_string();
+    assert!(stderr.contains("reading backup"), "{stderr}");
+
+    // Read-only: nothing is written over the damaged vault
+    let out = kevi(&["--use-backup", "rm", "old", "--yes"]).failure();
+    let stderr = String::from_utf8_lossy(&out.get_output().stderr).to_string();
+    assert!(stderr.contains("read-only"), "{stderr}");
+    assert_eq!(fs::read(&path).unwrap(), bytes);
+}
+
+#[test]
+fn backup_list_sorts_by_mtime_and_flags_backups_under_another_key() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    let entry = |label: &str| VaultEntry::new(label, "secret");
+    // `.2` predates a password change; `.1` has two entries under the current one
+    save_vault_file(&[entry("a")], &path, "old-pw").expect("save 1");
+    save_vault_file(&[entry("a"), entry("b")], &path, "pw").expect("save 2");
+    // Saving through kevi keeps the salt of save 2
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .env("KEVI_CONFIG_DIR", dir.path())
+        .args(["add", "--generate", "--label", "c", "--path"])
+        .arg(&path)
+        .assert()
+        .success();
+
+    // Make `.2` the newer file so the order follows mtime, not the suffix
+    let now = std::time::SystemTime::now();
+    let set_mtime = |n: usize, ago: u64| {
+        fs::File::options()
+            .write(true)
+            .open(bp(&path, n))
+            .unwrap()
+            .set_modified(now - std::time::Duration::from_secs(ago))
+            .unwrap();
+    };
+    set_mtime(1, 7200);
+    set_mtime(2, 60);
+
+    let out = Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .env("KEVI_CONFIG_DIR", dir.path())
+        .args(["backup", "list", "--path"])
+        .arg(&path)
+        .assert()
+        .success();
+    let stdout = String::from_utf8_lossy(&out.get_output().stdout).to_string();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{stdout}");
+    assert!(
+        lines[0].starts_with(&bp(&path, 2).display().to_string()),
+        "{stdout}"
+    );
+    assert!(lines[0].contains("(1m ago)"), "{stdout}");
+    assert!(
+        lines[0].contains("not restorable with the current key"),
+        "{stdout}"
+    );
+    assert!(
+        lines[1].starts_with(&bp(&path, 1).display().to_string()),
+        "{stdout}"
+    );
+    assert!(lines[1].contains("(2h ago)"), "{stdout}");
+    assert!(lines[1].ends_with("\t2 entries"), "{stdout}");
+}
+
+#[test]
+fn backup_list_reuses_the_unlocked_session_and_leaves_it_in_place() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    let entry = |label: &str| VaultEntry::new(label, "secret");
+    save_vault_file(&[entry("a")], &path, "old-pw").expect("save 1");
+    save_vault_file(&[entry("a"), entry("b")], &path, "pw").expect("save 2");
+
+    let kevi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env_remove("KEVI_PASSWORD")
+            .env("KEVI_CONFIG_DIR", dir.path())
+            .arg("--no-prompt")
+            .args(args)
+            .arg("--path")
+            .arg(&path);
+        cmd
+    };
+    kevi(&["unlock"])
+        .env("KEVI_PASSWORD", "pw")
+        .assert()
+        .success();
+
+    // No password is available: the backup under the old one is flagged, not derived
+    let out = kevi(&["backup", "list"]).assert().success();
+    let stdout = String::from_utf8_lossy(&out.get_output().stdout).to_string();
+    assert!(
+        stdout.contains("not restorable with the current key"),
+        "{stdout}"
+    );
+    kevi(&["list", "--porcelain"])
+        .assert()
+        .success()
+        .stdout("a\nb\n");
+}
@@ -15,10 +15,8 @@ fn get_warns_in_ssh_like_environment() {
 
     // Seed vault with one item
     let entry = VaultEntry {
-        label: "srv".into(),
         username: Some(SecretString::new("u".into())),
-        password: SecretString::new("p".into()),
-        notes: None,
+        ..VaultEntry::new("srv", "p")
     };
     save_vault_file(&[entry], &path, pw).expect("seed vault");
 
@@ -1,4 +1,8 @@
-use kevi::filesystem::clipboard::{copy_with_ttl, ClipboardEngine};
+use kevi::filesystem::clipboard::{
+    copy_and_wait, copy_and_wait_for_paste, copy_until_paste_then, copy_with_ttl,
+    copy_with_ttl_then, history_managers_in, history_warning, shell_hook, wait_until_changed,
+    ClipboardEngine, HoldOutcome,
+};
 use secrecy::SecretString;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -52,3 +56,216 @@ fn test_copy_with_ttl_restores_previous() {
     }
     assert_eq!(restored.as_deref(), Some("old"));
 }
+
+#[cfg(unix)]
+#[test]
+fn test_clear_hook_runs_after_restore() {
+    let td = tempfile::tempdir().unwrap();
+    let flag = td.path().join("cleared.flag");
+    let engine: Arc<dyn ClipboardEngine> = Arc::new(MockClipboard::new("old"));
+    let secret = SecretString::new("new-secret".into());
+
+    let hook = shell_hook(format!("touch '{}'", flag.display()));
+    copy_with_ttl_then(
+        engine.clone(),
+        &secret,
+        Duration::from_millis(50),
+        true,
+        Some(hook),
+    )
+    .expect("copy ok");
+    assert!(!flag.exists(), "hook must not run before the TTL elapses");
+
+    let start = std::time::Instant::now();
+    while !flag.exists() && start.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(flag.exists(), "hook should create the flag file");
+    assert_eq!(engine.get_contents().unwrap().as_deref(), Some("old"));
+}
+
+#[tokio::test]
+async fn test_copy_and_wait_restores_after_ttl() {
+    let engine: Arc<dyn ClipboardEngine> = Arc::new(MockClipboard::new("old"));
+    let secret = SecretString::new("new-secret".into());
+
+    let interrupted = copy_and_wait(
+        engine.clone(),
+        &secret,
+        Duration::from_millis(50),
+        true,
+        None,
+    )
+    .await
+    .expect("copy ok");
+    assert!(!interrupted);
+    assert_eq!(engine.get_contents().unwrap().as_deref(), Some("old"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_copy_and_wait_clears_on_sigint() {
+    let engine: Arc<dyn ClipboardEngine> = Arc::new(MockClipboard::new("old"));
+    let secret = SecretString::new("new-secret".into());
+
+    let probe = engine.clone();
+    tokio::spawn(async move {
+        // Wait until the secret is in place (and the handler installed), then interrupt
+        while probe.get_contents().unwrap().as_deref() != Some("new-secret") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        std::process::Command::new("kill")
+            .args(["-INT", &std::process::id().to_string()])
+            .status()
+            .expect("send SIGINT");
+    });
+
+    let start = std::time::Instant::now();
+    let interrupted = copy_and_wait(engine.clone(), &secret, Duration::from_secs(30), true, None)
+        .await
+        .expect("copy ok");
+    assert!(interrupted);
+    assert!(start.elapsed() < Duration::from_secs(10));
+    assert_eq!(engine.get_contents().unwrap().as_deref(), Some("old"));
+}
+
+#[test]
+fn test_wait_until_changed_stops_on_change_or_ttl() {
+    let engine = MockClipboard::new("secret");
+    let ttl = Duration::from_millis(60);
+    assert!(!wait_until_changed(
+        &engine,
+        "secret",
+        ttl,
+        Duration::from_millis(10)
+    ));
+
+    engine.set_contents("pasted over").unwrap();
+    let start = std::time::Instant::now();
+    assert!(wait_until_changed(
+        &engine,
+        "secret",
+        Duration::from_secs(30),
+        Duration::from_millis(10)
+    ));
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn test_no_restore_clears_instead_of_restoring() {
+    let engine: Arc<dyn ClipboardEngine> = Arc::new(MockClipboard::new("earlier-secret"));
+    let secret = SecretString::new("new-secret".into());
+
+    let interrupted = copy_and_wait(
+        engine.clone(),
+        &secret,
+        Duration::from_millis(50),
+        false,
+        None,
+    )
+    .await
+    .expect("copy ok");
+    assert!(!interrupted);
+    assert_eq!(engine.get_contents().unwrap().as_deref(), Some(""));
+
+    engine.set_contents("earlier-secret").unwrap();
+    copy_with_ttl_then(
+        engine.clone(),
+        &secret,
+        Duration::from_millis(50),
+        false,
+        None,
+    )
+    .expect("copy ok");
+    let start = std::time::Instant::now();
+    while engine.get_contents().unwrap().as_deref() != Some("")
+        && start.elapsed() < Duration::from_secs(5)
+    {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(engine.get_contents().unwrap().as_deref(), Some(""));
+}
+
+#[test]
+fn test_copy_until_paste_keeps_new_contents() {
+    let engine: Arc<dyn ClipboardEngine> = Arc::new(MockClipboard::new("old"));
+    let secret = SecretString::new("new-secret".into());
+    let cleared = Arc::new(Mutex::new(false));
+    let flag = cleared.clone();
+
+    copy_until_paste_then(
+        engine.clone(),
+        &secret,
+        Duration::from_secs(30),
+        true,
+        Some(Box::new(move || *flag.lock().unwrap() = true)),
+    )
+    .expect("copy ok");
+    assert_eq!(
+        engine.get_contents().unwrap().as_deref(),
+        Some("new-secret")
+    );
+
+    engine.set_contents("copied elsewhere").unwrap();
+    let start = std::time::Instant::now();
+    while !*cleared.lock().unwrap() && start.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(
+        *cleared.lock().unwrap(),
+        "hold should end well before the TTL"
+    );
+    assert_eq!(
+        engine.get_contents().unwrap().as_deref(),
+        Some("copied elsewhere")
+    );
+}
+
+#[tokio::test]
+async fn test_copy_and_wait_for_paste_outcomes() {
+    let engine: Arc<dyn ClipboardEngine> = Arc::new(MockClipboard::new("old"));
+    let secret = SecretString::new("new-secret".into());
+
+    let outcome = copy_and_wait_for_paste(
+        engine.clone(),
+        &secret,
+        Duration::from_millis(50),
+        true,
+        None,
+    )
+    .await
+    .expect("copy ok");
+    assert_eq!(outcome, HoldOutcome::Elapsed);
+    assert_eq!(engine.get_contents().unwrap().as_deref(), Some("old"));
+
+    let probe = engine.clone();
+    tokio::spawn(async move {
+        while probe.get_contents().unwrap().as_deref() != Some("new-secret") {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        probe.set_contents("pasted").unwrap();
+    });
+    let outcome =
+        copy_and_wait_for_paste(engine.clone(), &secret, Duration::from_secs(30), true, None)
+            .await
+            .expect("copy ok");
+    assert_eq!(outcome, HoldOutcome::Changed);
+    assert_eq!(engine.get_contents().unwrap().as_deref(), Some("pasted"));
+}
+
+#[test]
+fn test_history_manager_detection_is_advisory() {
+    let procs = ["bash", "copyq", "wl-paste --watch cliphist store", "copyq"];
+    assert_eq!(
+        history_managers_in(procs, Some("GNOME")),
+        ["CopyQ", "cliphist"]
+    );
+    assert_eq!(history_managers_in(["bash"], Some("KDE")), ["Klipper"]);
+    assert!(history_managers_in(["bash", "vim"], None).is_empty());
+
+    assert!(history_warning(&[]).is_none());
+    let warn = history_warning(&["GPaste"]).unwrap();
+    assert!(warn.contains("GPaste"));
+    assert!(warn.contains("--no-copy --echo"));
+}
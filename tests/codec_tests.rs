@@ -0,0 +1,99 @@
+use kevi::vault::codec::{CompactRonCodec, RonCodec};
+use kevi::vault::models::{TrashedEntry, VaultDocument, VaultEntry, SCHEMA_VERSION};
+use kevi::vault::ports::VaultCodec;
+use secrecy::{ExposeSecret, SecretString};
+
+fn entry(label: &str) -> VaultEntry {
+    VaultEntry {
+        username: Some(SecretString::new("user".into())),
+        notes: Some("line one\nline two".into()),
+        custom: [("pin".to_string(), SecretString::new("1234".into()))].into(),
+        created_at: Some(1),
+        ..VaultEntry::new(label, "p@ss, \"quoted\"")
+    }
+}
+
+fn labels(doc: &VaultDocument) -> (Vec<&str>, Vec<&str>) {
+    (
+        doc.entries.iter().map(|e| e.label.as_str()).collect(),
+        doc.trash.iter().map(|t| t.entry.label.as_str()).collect(),
+    )
+}
+
+#[test]
+fn compact_and_pretty_round_trip_and_read_each_other() {
+    let docs = [
+        VaultDocument {
+            entries: (0..20).map(|i| entry(&format!("site-{i}"))).collect(),
+            ..Default::default()
+        },
+        VaultDocument {
+            schema_version: 0,
+            entries: vec![entry("live")],
+            trash: vec![TrashedEntry {
+                entry: entry("gone"),
+                deleted_at: 42,
+            }],
+        },
+    ];
+    for doc in &docs {
+        let pretty = RonCodec.encode(doc).unwrap();
+        let compact = CompactRonCodec.encode(doc).unwrap();
+        assert!(
+            compact.len() < pretty.len(),
+            "compact {} >= pretty {}",
+            compact.len(),
+            pretty.len()
+        );
+
+        for bytes in [&pretty, &compact] {
+            for codec in [&RonCodec as &dyn VaultCodec, &CompactRonCodec] {
+                let back = codec.decode(bytes).unwrap();
+                assert_eq!(labels(&back), labels(doc));
+                let first = &back.entries[0];
+                assert_eq!(first.password.expose_secret(), "p@ss, \"quoted\"");
+                assert_eq!(first.notes.as_deref(), Some("line one\nline two"));
+                assert_eq!(first.custom["pin"].expose_secret(), "1234");
+            }
+        }
+    }
+}
+
+#[test]
+fn writes_schema_version_and_reads_older_and_newer_layouts() {
+    let doc = VaultDocument {
+        entries: vec![entry("site")],
+        ..Default::default()
+    };
+    for codec in [&RonCodec as &dyn VaultCodec, &CompactRonCodec] {
+        let bytes = codec.encode(&doc).unwrap();
+        let back = codec.decode(&bytes).unwrap();
+        assert_eq!(back.schema_version, SCHEMA_VERSION);
+        assert_eq!(labels(&back), (vec!["site"], vec![]));
+    }
+
+    // Old vault: a bare entry list without schema_version or newer fields
+    let old = br#"[(label: "old", password: "pw")]"#;
+    let back = RonCodec.decode(old).unwrap();
+    assert_eq!(back.schema_version, 0);
+    assert_eq!(back.entries[0].label, "old");
+    assert!(back.entries[0].username.is_none());
+    assert!(back.entries[0].notes.is_none());
+    assert!(back.entries[0].custom.is_empty());
+    assert!(back.trash.is_empty());
+
+    // Newer vault: a higher schema with fields this build doesn't know
+    let newer = br#"(
+        schema_version: 99,
+        entries: [(label: "new", password: "pw", url: "https://example.com", tags: ["a"])],
+        trash: [],
+        sync: (peer: "x"),
+    )"#;
+    let back = RonCodec.decode(newer).unwrap();
+    assert_eq!(back.schema_version, 99);
+    assert_eq!(back.entries[0].label, "new");
+    assert_eq!(back.entries[0].password.expose_secret(), "pw");
+    let (lenient, issues) = RonCodec.decode_lenient(newer).unwrap();
+    assert!(issues.is_empty());
+    assert_eq!(lenient.schema_version, 99);
+}
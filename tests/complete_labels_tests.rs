@@ -0,0 +1,109 @@
+use assert_cmd::Command;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn entry(label: &str) -> VaultEntry {
+    VaultEntry::new(label, "pw!")
+}
+
+fn complete(path: &Path, prefix: &str) -> Command {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env_remove("KEVI_PASSWORD")
+        .env_remove("KEVI_AGE_IDENTITY")
+        .args(["_complete-labels", "--path"])
+        .arg(path)
+        .args(["--", prefix]);
+    cmd
+}
+
+#[test]
+fn complete_labels_is_silent_when_locked_and_lists_when_unlocked() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    save_vault_file(
+        &[entry("github"), entry("gitlab"), entry("mail")],
+        &path,
+        "pw",
+    )
+    .unwrap();
+
+    // Locked: nothing on stdout, success, and no prompt even with KEVI_PASSWORD unset
+    complete(&path, "").assert().success().stdout("");
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .args(["unlock", "--path"])
+        .arg(&path)
+        .assert()
+        .success();
+
+    complete(&path, "")
+        .assert()
+        .success()
+        .stdout("github\ngitlab\nmail\n");
+    complete(&path, "git")
+        .assert()
+        .success()
+        .stdout("github\ngitlab\n");
+
+    // Hidden from help
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("_complete-labels").not());
+}
+
+#[test]
+fn complete_labels_ignores_missing_vault() {
+    let dir = tempdir().unwrap();
+    complete(&dir.path().join("none.ron"), "")
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn complete_profiles_lists_sorted_names_from_config() {
+    let dir = tempdir().unwrap();
+    let complete_profiles = |prefix: &str| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_CONFIG_DIR", dir.path())
+            .args(["_complete-profiles", "--", prefix]);
+        cmd
+    };
+
+    // No config file yet
+    complete_profiles("").assert().success().stdout("");
+
+    std::fs::create_dir_all(dir.path().join("kevi")).unwrap();
+    std::fs::write(
+        dir.path().join("kevi/config.toml"),
+        "default_profile = \"work\"\n\
+         [profiles.work]\nvault_path = \"/tmp/work.ron\"\n\
+         [profiles.home]\nvault_path = \"/tmp/home.ron\"\n\
+         [profiles.homelab]\nvault_path = \"/tmp/lab.ron\"\n",
+    )
+    .unwrap();
+    complete_profiles("")
+        .assert()
+        .success()
+        .stdout("home\nhomelab\nwork\n");
+    complete_profiles("home")
+        .assert()
+        .success()
+        .stdout("home\nhomelab\n");
+
+    // An unknown --profile does not get in the way of completing it
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_CONFIG_DIR", dir.path())
+        .args(["--profile", "nope", "_complete-profiles", "w"])
+        .assert()
+        .success()
+        .stdout("work\n");
+}
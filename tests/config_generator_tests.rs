@@ -121,3 +121,81 @@ fn passphrase_uses_config_words_and_sep_when_not_overridden() {
     }
     assert_eq!(count, 6);
 }
+
+#[test]
+fn gen_wordlist_info_reports_size_and_bits_per_word() {
+    let words = kevi::cryptography::wordlist::WORDS.len();
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_GEN_WORDS", "4")
+        .args(["gen", "--wordlist-info"]);
+    let out = cmd.assert().success().get_output().stdout.clone();
+    let out = String::from_utf8(out).unwrap();
+    assert!(
+        out.contains(&format!("words: {words} (all unique)")),
+        "{out}"
+    );
+    assert!(
+        out.contains(&format!("bits per word: {:.2}", (words as f64).log2())),
+        "{out}"
+    );
+    assert!(out.contains("4-word passphrase"), "{out}");
+}
+
+#[test]
+fn add_preset_applies_config_preset_before_flags() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let cfg = td.path().join("cfg");
+    fs::create_dir_all(cfg.join("kevi")).unwrap();
+    fs::write(
+        cfg.join("kevi").join("config.toml"),
+        "[generator.presets.bank]\nlength = 12\nsymbols = false\n\n\
+         [generator.presets.shop]\nlength = 40\nsymbol_set = \"#$\"\n",
+    )
+    .unwrap();
+    let add = |label: &str, extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", "pw")
+            .env("KEVI_CONFIG_DIR", &cfg)
+            .env_remove("KEVI_GEN_LENGTH")
+            .args(["add", "--generate", "--user", "u", "--notes", "", "--label"])
+            .arg(label)
+            .arg("--path")
+            .arg(&path)
+            .args(extra)
+            .assert()
+    };
+
+    add("bank", &["--preset", "bank"]).success();
+    add("bank16", &["--preset", "bank", "--length", "16"]).success();
+    add("shop", &["--preset", "shop"]).success();
+    add("nope", &["--preset", "nope"])
+        .failure()
+        .stderr(predicates::str::contains(
+            "unknown generator preset 'nope' (defined: bank, shop)",
+        ));
+
+    let entries: Vec<VaultEntry> = load_vault_file(&path, "pw").expect("load");
+    let pw_of = |label: &str| {
+        entries
+            .iter()
+            .find(|e| e.label == label)
+            .unwrap()
+            .password
+            .expose_secret()
+            .to_string()
+    };
+    let bank = pw_of("bank");
+    assert_eq!(bank.len(), 12);
+    assert!(bank.chars().all(|c| c.is_ascii_alphanumeric()));
+    let bank16 = pw_of("bank16");
+    assert_eq!(bank16.len(), 16);
+    assert!(bank16.chars().all(|c| c.is_ascii_alphanumeric()));
+    let shop = pw_of("shop");
+    assert_eq!(shop.len(), 40);
+    assert!(shop.contains(['#', '$']), "{shop}");
+    assert!(shop
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '#' || c == '$'));
+    assert!(!entries.iter().any(|e| e.label == "nope"));
+}
@@ -1,5 +1,6 @@
-use kevi::config::app_config::Config;
+use kevi::config::app_config::{effective_settings, Config};
 use kevi::filesystem::store::FileByteStore;
+use kevi::vault::handlers::vault_store;
 use kevi::vault::ports::ByteStore;
 use serial_test::serial;
 use std::env;
@@ -75,6 +76,87 @@ fn vault_path_precedence_file_over_default() {
     assert_eq!(cfg.vault_path, PathBuf::from("/tmp/cfg_vault.ron"));
 }
 
+#[test]
+#[serial]
+fn effective_settings_report_values_and_sources() {
+    let td = tempdir().unwrap();
+    env::set_var("HOME", td.path());
+    env::set_var(
+        "KEVI_CONFIG_DIR",
+        td.path().join("cfg").to_string_lossy().to_string(),
+    );
+    env::remove_var("KEVI_VAULT_PATH");
+    env::remove_var("KEVI_CLIP_TTL");
+    env::set_var("KEVI_BACKUPS", "5");
+    write_config_file(
+        td.path(),
+        "vault_path = \"/tmp/cfg_vault.ron\"\nclipboard_ttl = 45\nbackups = 3\n\
+         [profiles.work]\nvault_path = \"/tmp/work.ron\"\n",
+    );
+
+    let settings = effective_settings(None, None).unwrap();
+    let get = |key: &str| {
+        let s = settings.iter().find(|s| s.key == key).expect(key);
+        (s.value.clone(), s.source.as_str())
+    };
+    assert_eq!(settings[0].key, "vault_path");
+    assert_eq!(
+        get("vault_path"),
+        (
+            serde_json::json!("/tmp/cfg_vault.ron"),
+            "vault_path in config.toml"
+        )
+    );
+    assert_eq!(get("backups"), (serde_json::json!(5), "KEVI_BACKUPS"));
+    assert_eq!(get("clipboard_ttl"), (serde_json::json!(45), "config.toml"));
+    assert_eq!(get("tui_theme"), (serde_json::Value::Null, "unset"));
+    assert_eq!(get("profiles").1, "config.toml");
+
+    // A profile changes the vault path and its source
+    let settings = effective_settings(None, Some("work")).unwrap();
+    assert_eq!(settings[0].value, serde_json::json!("/tmp/work.ron"));
+    assert_eq!(settings[0].source, "--profile");
+    env::remove_var("KEVI_BACKUPS");
+}
+
+#[test]
+#[serial]
+fn vault_paths_expand_tilde_and_env_vars() {
+    let td = tempdir().unwrap();
+    env::set_var("HOME", td.path());
+    env::set_var(
+        "KEVI_CONFIG_DIR",
+        td.path().join("cfg").to_string_lossy().to_string(),
+    );
+    env::remove_var("KEVI_VAULT_PATH");
+    env::set_var("KEVI_TEST_VAULT_DIR", "/srv/vaults");
+    write_config_file(
+        td.path(),
+        "vault_path = \"~/kevi/vault.ron\"\n\
+         [profiles.work]\nvault_path = \"${KEVI_TEST_VAULT_DIR}/work.ron\"\n\
+         [profiles.plain]\nvault_path = \"/tmp/$notset/plain.ron\"\n",
+    );
+
+    // `~/` in config.toml
+    let cfg = Config::create(None, None).unwrap();
+    assert_eq!(cfg.vault_path, td.path().join("kevi/vault.ron"));
+
+    // `$HOME/` in KEVI_VAULT_PATH
+    env::set_var("KEVI_VAULT_PATH", "$HOME/env.ron");
+    let cfg = Config::create(None, None).unwrap();
+    assert_eq!(cfg.vault_path, td.path().join("env.ron"));
+    env::remove_var("KEVI_VAULT_PATH");
+
+    // `${VAR}` in a profile path
+    let cfg = Config::create(None, Some("work".into())).unwrap();
+    assert_eq!(cfg.vault_path, PathBuf::from("/srv/vaults/work.ron"));
+
+    // Absolute paths, and unset variables, are kept as written
+    let cfg = Config::create(None, Some("plain".into())).unwrap();
+    assert_eq!(cfg.vault_path, PathBuf::from("/tmp/$notset/plain.ron"));
+    env::remove_var("KEVI_TEST_VAULT_DIR");
+}
+
 #[test]
 #[serial]
 fn clipboard_ttl_and_backups_precedence() {
@@ -101,6 +183,25 @@ fn clipboard_ttl_and_backups_precedence() {
     assert_eq!(cfg2.backups, Some(7));
 }
 
+#[test]
+#[serial]
+fn get_verbose_precedence() {
+    let td = tempdir().unwrap();
+    env::set_var("HOME", td.path());
+    env::set_var(
+        "KEVI_CONFIG_DIR",
+        td.path().join("cfg").to_string_lossy().to_string(),
+    );
+    env::remove_var("KEVI_GET_VERBOSE");
+
+    assert_eq!(Config::create(None, None).unwrap().get_verbose, None);
+    write_config_file(td.path(), "get_verbose = true\n");
+    assert_eq!(Config::create(None, None).unwrap().get_verbose, Some(true));
+    env::set_var("KEVI_GET_VERBOSE", "false");
+    assert_eq!(Config::create(None, None).unwrap().get_verbose, Some(false));
+    env::remove_var("KEVI_GET_VERBOSE");
+}
+
 #[test]
 #[serial]
 fn default_vault_path_uses_platform_data_dir_under_home() {
@@ -147,10 +248,33 @@ fn backups_rotation_uses_configured_count() {
         vault_path: path.clone(),
         clipboard_ttl: None,
         backups: Some(backups),
+        backup_hidden: None,
+        backup_suffix: None,
+        compact_storage: None,
+        stream_encryption: None,
         generator_length: None,
+        generator_max_length: None,
         generator_words: None,
         generator_sep: None,
         avoid_ambiguous: None,
+        generator_presets: Default::default(),
+        tui_theme: None,
+        status_style: None,
+        tui_tick_ms: None,
+        tui_render_markdown: None,
+        trash_retention_days: None,
+        max_username_bytes: None,
+        max_notes_bytes: None,
+        username_secret: None,
+        clipboard_clear_hook: None,
+        get_verbose: None,
+        clipboard_clear_on_paste: None,
+        clipboard_no_restore: None,
+        require_reveal_reauth: None,
+        track_access: None,
+        session_sliding: None,
+        session_max_secs: None,
+        warn_password_len: None,
         default_profile: None,
         profiles: Default::default(),
     };
@@ -174,3 +298,45 @@ fn backups_rotation_uses_configured_count() {
     assert!(Path::new(&format!("{}{}", path.display(), ".3")).exists());
     assert!(!Path::new(&format!("{}{}", path.display(), ".4")).exists());
 }
+
+#[test]
+#[serial]
+fn vault_store_uses_configured_backup_count_and_naming() {
+    let td = tempdir().unwrap();
+    env::set_var("KEVI_CONFIG_DIR", td.path().join("cfg"));
+    env::set_var("KEVI_BACKUPS", "1");
+    env::set_var("KEVI_BACKUP_SUFFIX", "bak");
+    let path = td.path().join("vault.ron");
+    let cfg = Config::create(Some(path.clone()), None).unwrap();
+    env::remove_var("KEVI_BACKUPS");
+    env::remove_var("KEVI_BACKUP_SUFFIX");
+
+    let store = vault_store(&cfg);
+    for data in [b"A", b"B", b"C"] {
+        store.write(data).unwrap();
+    }
+    assert_eq!(fs::read(td.path().join("vault.ron.bak.1")).unwrap(), b"B");
+    assert!(!td.path().join("vault.ron.bak.2").exists());
+    assert!(!td.path().join("vault.ron.1").exists());
+}
+
+#[test]
+#[serial]
+fn tui_theme_precedence_env_over_file() {
+    let td = tempdir().unwrap();
+    env::set_var("HOME", td.path());
+    env::set_var(
+        "KEVI_CONFIG_DIR",
+        td.path().join("cfg").to_string_lossy().to_string(),
+    );
+    env::remove_var("KEVI_TUI_THEME");
+
+    write_config_file(td.path(), "tui_theme = \"light\"\n");
+    let cfg = Config::create(None, None).unwrap();
+    assert_eq!(cfg.tui_theme.as_deref(), Some("light"));
+
+    env::set_var("KEVI_TUI_THEME", "high-contrast");
+    let cfg2 = Config::create(None, None).unwrap();
+    assert_eq!(cfg2.tui_theme.as_deref(), Some("high-contrast"));
+    env::remove_var("KEVI_TUI_THEME");
+}
@@ -1,5 +1,12 @@
 use anyhow::Result;
-use kevi::cryptography::primitives::{decrypt_vault, encrypt_vault};
+use kevi::cryptography::compare::{ct_eq, secrets_equal};
+use kevi::cryptography::primitives::{
+    calibrate_params, decrypt_stream_with_key, decrypt_vault, decrypt_vault_with_key,
+    default_params, encrypt_stream_with_key, encrypt_vault, encrypt_vault_with_key,
+    encrypt_vault_with_key_stream, encrypt_vault_with_params, header_fingerprint_excluding_nonce,
+    parse_kevi_header, KdfPreset, HEADER_LEN, HEADER_VERSION_STREAM, KDF_PRESETS, STREAM_CHUNK_LEN,
+};
+use std::time::Duration;
 
 #[tokio::test]
 async fn test_encryption_decryption() -> Result<()> {
@@ -13,3 +20,93 @@ async fn test_encryption_decryption() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_encrypt_with_params_records_them_in_header() -> Result<()> {
+    let encrypted = encrypt_vault_with_params(b"data", "pw", (8 * 1024, 2, 1))?;
+    let (hdr, _) = parse_kevi_header(&encrypted).unwrap();
+    assert_eq!((hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes), (8 * 1024, 2, 1));
+    assert_eq!(decrypt_vault(&encrypted, "pw")?, b"data");
+    Ok(())
+}
+
+#[test]
+fn test_calibration_never_goes_below_defaults() -> Result<()> {
+    // An unreachable target falls back to the defaults
+    assert_eq!(calibrate_params(Duration::ZERO)?, default_params());
+    Ok(())
+}
+
+#[test]
+fn test_kdf_presets_table() {
+    assert_eq!(KdfPreset::Paranoid.params(), (256 * 1024, 4, 4));
+    assert_eq!(KdfPreset::Balanced.params(), default_params());
+    // Ordered from weakest to strongest
+    let mems: Vec<u32> = KDF_PRESETS.iter().map(|(_, (m, _, _))| *m).collect();
+    assert!(mems.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_constant_time_equality_helpers() {
+    assert!(secrets_equal("correct horse", "correct horse"));
+    assert!(!secrets_equal("correct horse", "correct horsf"));
+    assert!(!secrets_equal("short", "shorter"));
+    assert!(secrets_equal("", ""));
+    assert!(ct_eq(&[1, 2, 3], &[1, 2, 3]));
+    assert!(!ct_eq(&[1, 2, 3], &[1, 2, 4]));
+}
+
+#[test]
+fn test_chunked_format_round_trips_multi_megabyte_inputs() -> Result<()> {
+    let key = [7u8; 32];
+    let salt = [3u8; 16];
+    let big: Vec<u8> = (0..5 * 1024 * 1024 + 123)
+        .map(|i| (i % 251) as u8)
+        .collect();
+    for data in [&big[..], &big[..2 * STREAM_CHUNK_LEN], &[][..]] {
+        let ct = encrypt_vault_with_key_stream(data, 8 * 1024, 2, 1, salt, &key)?;
+        let (hdr, _) = parse_kevi_header(&ct).unwrap();
+        assert_eq!(hdr.version, HEADER_VERSION_STREAM);
+        assert_eq!(decrypt_vault_with_key(&ct, &key)?, data);
+    }
+
+    // Same key and params, so a cached session covers both formats
+    let single = encrypt_vault_with_key(b"x", 8 * 1024, 2, 1, salt, &key)?;
+    let chunked = encrypt_vault_with_key_stream(b"x", 8 * 1024, 2, 1, salt, &key)?;
+    assert_eq!(
+        header_fingerprint_excluding_nonce(&parse_kevi_header(&single).unwrap().0),
+        header_fingerprint_excluding_nonce(&parse_kevi_header(&chunked).unwrap().0)
+    );
+
+    // The reader/writer variants produce the same format
+    let mut ct = Vec::new();
+    encrypt_stream_with_key(&big[..], &mut ct, 8 * 1024, 2, 1, salt, &key)?;
+    let mut pt = Vec::new();
+    decrypt_stream_with_key(&ct[..], &mut pt, &key)?;
+    assert_eq!(pt, big);
+    Ok(())
+}
+
+#[test]
+fn test_chunked_format_rejects_tampering_and_truncation() -> Result<()> {
+    let key = [7u8; 32];
+    let data = vec![42u8; 3 * STREAM_CHUNK_LEN + 10];
+    let ct = encrypt_vault_with_key_stream(&data, 8 * 1024, 2, 1, [3u8; 16], &key)?;
+    let sealed = STREAM_CHUNK_LEN + 16;
+
+    let mut flipped = ct.clone();
+    flipped[HEADER_LEN + sealed + 5] ^= 1;
+    // Dropping the final chunk leaves a non-final chunk last
+    let truncated = &ct[..HEADER_LEN + 3 * sealed];
+    // Swapping two full chunks breaks the counter
+    let mut swapped = ct[..HEADER_LEN].to_vec();
+    swapped.extend_from_slice(&ct[HEADER_LEN + sealed..HEADER_LEN + 2 * sealed]);
+    swapped.extend_from_slice(&ct[HEADER_LEN..HEADER_LEN + sealed]);
+    swapped.extend_from_slice(&ct[HEADER_LEN + 2 * sealed..]);
+
+    for bad in [&flipped[..], truncated, &swapped[..], &ct[..HEADER_LEN]] {
+        assert!(decrypt_vault_with_key(bad, &key).is_err());
+    }
+    assert!(decrypt_vault_with_key(&ct, &[8u8; 32]).is_err());
+    Ok(())
+}
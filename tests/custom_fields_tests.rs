@@ -0,0 +1,186 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use secrecy::ExposeSecret;
+use tempfile::tempdir;
+
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::load_vault_file;
+
+fn kevi(path: &std::path::Path, args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .args(args)
+        .arg("--path")
+        .arg(path);
+    cmd
+}
+
+fn add_bank(path: &std::path::Path) {
+    kevi(
+        path,
+        &[
+            "add",
+            "--generate",
+            "--label",
+            "bank",
+            "--set",
+            "account=12-3456",
+            "--set",
+            "question=first pet: rex",
+        ],
+    )
+    .assert()
+    .success();
+}
+
+#[test]
+fn add_set_stores_custom_fields() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    add_bank(&path);
+
+    let entries: Vec<VaultEntry> = load_vault_file(&path, "pw").expect("load vault");
+    let e = entries.iter().find(|e| e.label == "bank").unwrap();
+    assert_eq!(e.custom.len(), 2);
+    assert_eq!(e.custom["account"].expose_secret(), "12-3456");
+    assert_eq!(e.custom["question"].expose_secret(), "first pet: rex");
+}
+
+#[test]
+fn get_custom_field_echoes_value() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    add_bank(&path);
+
+    kevi(
+        &path,
+        &[
+            "get",
+            "bank",
+            "--field",
+            "custom:account",
+            "--no-copy",
+            "--echo",
+        ],
+    )
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("12-3456"));
+
+    kevi(
+        &path,
+        &[
+            "get",
+            "bank",
+            "--field",
+            "custom:missing",
+            "--no-copy",
+            "--echo",
+        ],
+    )
+    .assert()
+    .code(2)
+    .stdout("")
+    .stderr(predicate::str::contains("no custom:missing set for 'bank'"));
+}
+
+#[test]
+fn show_masks_custom_values_unless_revealed() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    add_bank(&path);
+
+    kevi(&path, &["show", "bank"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("account: ********"))
+        .stdout(predicate::str::contains("12-3456").not());
+
+    kevi(&path, &["show", "bank", "--reveal"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("account: 12-3456"))
+        .stdout(predicate::str::contains("question: first pet: rex"));
+}
+
+#[test]
+fn invalid_set_and_field_are_rejected() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+
+    kevi(
+        &path,
+        &["add", "--generate", "--label", "x", "--set", "novalue"],
+    )
+    .assert()
+    .failure()
+    .stderr(predicate::str::contains("expected NAME=VALUE"));
+
+    kevi(&path, &["get", "x", "--field", "custom:"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("must not be empty"));
+}
+
+#[test]
+fn get_field_fallback_uses_first_set_field() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    add_bank(&path);
+
+    kevi(
+        &path,
+        &[
+            "get",
+            "bank",
+            "--field-fallback",
+            "account,password",
+            "--no-copy",
+            "--echo",
+        ],
+    )
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("12-3456"))
+    .stderr(predicate::str::contains("using custom:account of bank"));
+
+    kevi(
+        &path,
+        &[
+            "get",
+            "bank",
+            "--field-fallback",
+            "totp,notes,password",
+            "--no-copy",
+            "--echo",
+        ],
+    )
+    .assert()
+    .success()
+    .stderr(predicate::str::contains("using password of bank"));
+
+    kevi(
+        &path,
+        &[
+            "get",
+            "bank",
+            "--field-fallback",
+            "totp,notes",
+            "--no-copy",
+            "--raw",
+        ],
+    )
+    .assert()
+    .code(2)
+    .stdout("")
+    .stderr(predicate::str::contains(
+        "no value among custom:totp, notes set for 'bank'",
+    ));
+
+    kevi(
+        &path,
+        &["get", "bank", "--field-fallback", "all", "--no-copy"],
+    )
+    .assert()
+    .failure();
+}
@@ -0,0 +1,119 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::tempdir;
+
+use kevi::config::env_file::parse_env_file;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+
+fn seed(path: &std::path::Path) {
+    let entry = VaultEntry::new("github", "p");
+    save_vault_file(&[entry], path, "pw").expect("seed vault");
+}
+
+fn write_env(path: &std::path::Path, content: &str, mode: u32) {
+    fs::write(path, content).unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+}
+
+#[test]
+fn parse_env_file_handles_comments_export_and_quotes() {
+    let pairs = parse_env_file(
+        "# comment\n\nexport KEVI_CLIP_TTL=30\nKEVI_GEN_SEP='-'\nKEVI_PASSWORD=\"a \\\"b\\\" \\$c\"\n",
+    )
+    .unwrap();
+    assert_eq!(
+        pairs,
+        vec![
+            ("KEVI_CLIP_TTL".to_string(), "30".to_string()),
+            ("KEVI_GEN_SEP".to_string(), "-".to_string()),
+            ("KEVI_PASSWORD".to_string(), "a \"b\" $c".to_string()),
+        ]
+    );
+    assert!(parse_env_file("not a pair\n").is_err());
+}
+
+#[test]
+fn env_file_supplies_password() {
+    let td = tempdir().unwrap();
+    let vault = td.path().join("vault.ron");
+    seed(&vault);
+    let env_file = td.path().join("kevi.env");
+    write_env(&env_file, "KEVI_PASSWORD=pw\n", 0o600);
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env_remove("KEVI_PASSWORD")
+        .arg("--env-file")
+        .arg(&env_file)
+        .args(["list", "--path"])
+        .arg(&vault);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("github"));
+}
+
+#[test]
+fn already_set_variables_win_over_env_file() {
+    let td = tempdir().unwrap();
+    let vault = td.path().join("vault.ron");
+    seed(&vault);
+    let env_file = td.path().join("kevi.env");
+    write_env(&env_file, "KEVI_PASSWORD=wrong\n", 0o600);
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .args(["list", "--path"])
+        .arg(&vault)
+        .arg("--env-file")
+        .arg(&env_file);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("github"));
+}
+
+#[cfg(unix)]
+#[test]
+fn world_readable_env_file_is_refused() {
+    let td = tempdir().unwrap();
+    let vault = td.path().join("vault.ron");
+    seed(&vault);
+    let env_file = td.path().join("kevi.env");
+    write_env(&env_file, "KEVI_PASSWORD=pw\n", 0o644);
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env_remove("KEVI_PASSWORD")
+        .arg("--env-file")
+        .arg(&env_file)
+        .args(["list", "--path"])
+        .arg(&vault);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("refusing world-readable env file"));
+}
+
+#[cfg(unix)]
+#[test]
+fn group_readable_password_file_warns() {
+    let td = tempdir().unwrap();
+    let vault = td.path().join("vault.ron");
+    seed(&vault);
+    let env_file = td.path().join("kevi.env");
+    write_env(&env_file, "KEVI_PASSWORD=pw\n", 0o640);
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env_remove("KEVI_PASSWORD")
+        .arg("--env-file")
+        .arg(&env_file)
+        .args(["list", "--path"])
+        .arg(&vault);
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("contains KEVI_PASSWORD"));
+}
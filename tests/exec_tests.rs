@@ -0,0 +1,103 @@
+use assert_cmd::Command;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::{load_vault_file, save_vault_file};
+use secrecy::{ExposeSecret, SecretString};
+use serde_json::Value;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn seed(path: &Path) {
+    let entry = VaultEntry {
+        username: Some(SecretString::new("octo".into())),
+        ..VaultEntry::new("github", "s3cr3t")
+    };
+    save_vault_file(&[entry], path, "pw").expect("seed vault");
+}
+
+fn exec(path: &Path, request: &str) -> (bool, Vec<Value>) {
+    let out = Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .arg("exec")
+        .arg("--path")
+        .arg(path)
+        .write_stdin(request)
+        .output()
+        .unwrap();
+    let results = serde_json::from_slice(&out.stdout).expect("json results");
+    (out.status.success(), results)
+}
+
+#[test]
+fn exec_runs_ops_and_reveals_secrets_only_on_request() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+
+    let (ok, results) = exec(
+        &path,
+        r#"[{"op":"get","key":"git"},
+            {"op":"get","key":"github","field":"password","reveal":true},
+            {"op":"list","query":"HUB"}]"#,
+    );
+    assert!(ok);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["label"], "github");
+    assert_eq!(results[0]["present"], true);
+    assert!(results[0].get("value").is_none());
+    assert!(!results[0].to_string().contains("s3cr3t"));
+    assert_eq!(results[1]["value"], "s3cr3t");
+    assert_eq!(results[2]["labels"], serde_json::json!(["github"]));
+}
+
+#[test]
+fn exec_saves_adds_once_and_nothing_when_an_op_fails() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+
+    let (ok, results) = exec(
+        &path,
+        r#"[{"op":"add","label":"a","password":"1"},
+            {"op":"get","key":"missing"},
+            {"op":"list"}]"#,
+    );
+    assert!(!ok);
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["ok"], true);
+    assert_eq!(results[1]["ok"], false);
+    assert!(results[1]["error"]
+        .as_str()
+        .unwrap()
+        .contains("no entry found"));
+    assert_eq!(load_vault_file(&path, "pw").unwrap().len(), 1);
+
+    let (ok, results) = exec(
+        &path,
+        r#"[{"op":"add","label":"a","password":"1","custom":{"pin":"42"}},
+            {"op":"get","key":"a","field":"custom:pin","reveal":true}]"#,
+    );
+    assert!(ok);
+    assert_eq!(results[1]["value"], "42");
+    let entries = load_vault_file(&path, "pw").unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].custom["pin"].expose_secret(), "42");
+}
+
+#[test]
+fn exec_rejects_malformed_requests() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .arg("exec")
+        .arg("--path")
+        .arg(&path)
+        .write_stdin(r#"[{"op":"frobnicate"}]"#)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("invalid exec request"));
+}
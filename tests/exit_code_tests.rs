@@ -0,0 +1,47 @@
+use assert_cmd::prelude::*;
+use std::process::{Command, Stdio};
+use tempfile::tempdir;
+
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+
+#[test]
+fn failures_exit_with_documented_codes() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let entry = VaultEntry::new("alpha", "aaa");
+    save_vault_file(&[entry], &path, "pw").expect("seed vault");
+
+    let kevi = |password: Option<&str>, args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.stdin(Stdio::null())
+            .env("KEVI_CONFIG_DIR", td.path())
+            .env_remove("KEVI_PASSWORD")
+            .args(args)
+            .arg("--path")
+            .arg(&path);
+        if let Some(pw) = password {
+            cmd.env("KEVI_PASSWORD", pw);
+        }
+        cmd.assert()
+    };
+
+    // Before any session is cached
+    kevi(None, &["list"]).code(4);
+    kevi(Some("wrong"), &["list"]).code(3);
+    kevi(Some("pw"), &["lock"]).code(0);
+    kevi(Some("pw"), &["get", "alpha", "--no-copy"]).code(0);
+    kevi(Some("pw"), &["get", "nope", "--no-copy"]).code(2);
+    kevi(Some("pw"), &["trash", "restore", "nope"]).code(2);
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_CONFIG_DIR", td.path())
+        .args(["--profile", "does-not-exist", "list"])
+        .assert()
+        .code(5);
+    kevi(
+        Some("pw"),
+        &["get", "alpha", "--field", "notes", "--measure"],
+    )
+    .code(1);
+}
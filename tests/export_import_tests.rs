@@ -0,0 +1,133 @@
+use assert_cmd::Command;
+use kevi::vault::export::ExportBundle;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::{load_vault_file, save_vault_file};
+use secrecy::{ExposeSecret, SecretString};
+use tempfile::tempdir;
+
+fn entry(label: &str, password: &str) -> VaultEntry {
+    VaultEntry {
+        username: Some(SecretString::new("alice".into())),
+        created_at: Some(100),
+        updated_at: Some(200),
+        ..VaultEntry::new(label, password)
+    }
+}
+
+fn kevi(password: &str) -> Command {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", password);
+    cmd
+}
+
+#[test]
+fn export_under_new_password_imports_into_another_vault() {
+    let td = tempdir().unwrap();
+    let src = td.path().join("src.ron");
+    let dst = td.path().join("dst.ron");
+    let out = td.path().join("transfer.kevi");
+    save_vault_file(
+        &[entry("mail", "m-secret"), entry("bank", "b-secret")],
+        &src,
+        "pw-a",
+    )
+    .unwrap();
+    save_vault_file(&[entry("bank", "other")], &dst, "pw-b").unwrap();
+
+    kevi("pw-a")
+        .env("KEVI_EXPORT_PASSWORD", "transfer")
+        .args(["export", "--encrypted", "--new-password", "--out"])
+        .arg(&out)
+        .arg("--path")
+        .arg(&src)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Exported 2 entries"));
+
+    // A standalone container: only the export password opens it, and it names itself
+    let bytes = std::fs::read(&out).unwrap();
+    assert!(bytes.starts_with(b"KEVI"));
+    assert!(ExportBundle::open(&bytes, "pw-a").is_err());
+    let bundle = ExportBundle::open(&bytes, "transfer").unwrap();
+    assert_eq!(bundle.format, "kevi-export");
+    assert_eq!(bundle.version, 1);
+
+    kevi("pw-b")
+        .env("KEVI_EXPORT_PASSWORD", "transfer")
+        .args(["import", "--encrypted"])
+        .arg(&out)
+        .arg("--path")
+        .arg(&dst)
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Imported 1 entries"))
+        .stderr(predicates::str::contains("Skipped existing labels: bank"));
+
+    let entries = load_vault_file(&dst, "pw-b").unwrap();
+    let labels: Vec<&str> = entries.iter().map(|e| e.label.as_str()).collect();
+    assert_eq!(labels, ["bank", "mail"]);
+    assert_eq!(entries[0].password.expose_secret(), "other");
+    assert_eq!(entries[1].password.expose_secret(), "m-secret");
+    assert_eq!(entries[1].created_at, Some(100));
+}
+
+#[test]
+fn export_defaults_to_the_vault_password_and_rejects_raw_vaults() {
+    let td = tempdir().unwrap();
+    let src = td.path().join("src.ron");
+    let out = td.path().join("transfer.kevi");
+    save_vault_file(&[entry("mail", "m-secret")], &src, "pw-a").unwrap();
+
+    kevi("pw-a")
+        .args(["export", "--encrypted", "--out"])
+        .arg(&out)
+        .arg("--path")
+        .arg(&src)
+        .assert()
+        .success();
+    let bundle = ExportBundle::open(&std::fs::read(&out).unwrap(), "pw-a").unwrap();
+    assert_eq!(bundle.entries.len(), 1);
+
+    // A vault file is a KEVI container too, but lacks the export marker
+    let dst = td.path().join("dst.ron");
+    kevi("pw-a")
+        .args(["import", "--encrypted"])
+        .arg(&src)
+        .arg("--path")
+        .arg(&dst)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("not a kevi export"));
+
+    kevi("pw-a")
+        .args(["export", "--encrypted", "--out"])
+        .arg(&src)
+        .arg("--path")
+        .arg(&src)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("refusing to overwrite the vault"));
+
+    // Export passwords are never taken from argv
+    kevi("pw-a")
+        .args(["export", "--encrypted", "--password", "transfer", "--out"])
+        .arg(&out)
+        .arg("--path")
+        .arg(&src)
+        .assert()
+        .failure();
+    kevi("pw-a")
+        .args([
+            "--no-prompt",
+            "export",
+            "--encrypted",
+            "--new-password",
+            "--out",
+        ])
+        .arg(&out)
+        .arg("--path")
+        .arg(&src)
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("no password available"));
+}
@@ -19,10 +19,9 @@ fn seed_vault(home: &std::path::Path) {
     }
     let pw = "pw";
     let entry = VaultEntry {
-        label: "label1".into(),
         username: Some(SecretString::new("user123".into())),
-        password: SecretString::new("p@ss".into()),
         notes: Some("noteZ".into()),
+        ..VaultEntry::new("label1", "p@ss")
     };
     save_vault_file(&[entry], &path, pw).expect("seed vault");
 }
@@ -107,3 +106,56 @@ fn get_no_copy_without_echo_prints_nothing() {
         .arg("--no-copy");
     cmd.assert().success().stdout(predicate::str::is_empty());
 }
+
+#[test]
+fn get_raw_and_print0_write_exact_bytes() {
+    let td = tempdir().unwrap();
+    let home = td.path();
+    seed_vault(home);
+    let path = default_vault_path_for(home).to_string_lossy().to_string();
+
+    let mut raw = Command::cargo_bin("kevi").unwrap();
+    raw.env("HOME", home)
+        .env("KEVI_PASSWORD", "pw")
+        .args(["get", "label1", "--raw", "--path", &path]);
+    raw.assert()
+        .success()
+        .stdout(predicate::eq(b"p@ss" as &[u8]));
+
+    let mut nul = Command::cargo_bin("kevi").unwrap();
+    nul.env("HOME", home).env("KEVI_PASSWORD", "pw").args([
+        "get", "label1", "--field", "user", "--print0", "--path", &path,
+    ]);
+    nul.assert()
+        .success()
+        .stdout(predicate::eq(b"user123\0" as &[u8]));
+}
+
+#[test]
+fn get_measure_prints_strength_only_and_fails_for_missing_entries() {
+    let td = tempdir().unwrap();
+    let home = td.path();
+    seed_vault(home);
+    let measure = |key: &str, extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("HOME", home)
+            .env("KEVI_PASSWORD", "pw")
+            .arg("get")
+            .arg(key)
+            .arg("--path")
+            .arg(default_vault_path_for(home))
+            .arg("--measure")
+            .args(extra);
+        cmd.assert()
+    };
+
+    measure("label1", &["--field", "password"])
+        .success()
+        .stdout("23 bits (very weak)\n");
+    measure("missing", &[])
+        .failure()
+        .stderr(predicate::str::contains("no entry found"));
+    measure("label1", &["--field", "user"])
+        .failure()
+        .stderr(predicate::str::contains("only works with --field password"));
+}
@@ -0,0 +1,88 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use secrecy::SecretString;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::vault::format::{dotenv_quote, format_entry, EntryFormat};
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+
+fn sample() -> VaultEntry {
+    VaultEntry {
+        username: Some(SecretString::new("admin".into())),
+        notes: Some("line1\nline2".into()),
+        ..VaultEntry::new("db", "p\"a$s`s\\\nx")
+    }
+}
+
+#[test]
+fn dotenv_quote_escapes_quotes_newlines_and_shell_chars() {
+    assert_eq!(dotenv_quote("plain"), "\"plain\"");
+    assert_eq!(dotenv_quote("a\"b"), "\"a\\\"b\"");
+    assert_eq!(dotenv_quote("a\nb"), "\"a\\nb\"");
+    assert_eq!(dotenv_quote("$HOME `x` \\"), "\"\\$HOME \\`x\\` \\\\\"");
+}
+
+#[test]
+fn format_entry_hides_password_unless_revealed() {
+    let e = sample();
+    let hidden = format_entry(&e, EntryFormat::Dotenv, false).unwrap();
+    assert!(hidden.contains("KEVI_USER=\"admin\""));
+    assert!(!hidden.contains("KEVI_PASSWORD"));
+    assert_eq!(hidden.lines().count(), 3, "values must stay on one line");
+
+    let shown = format_entry(&e, EntryFormat::Dotenv, true).unwrap();
+    assert!(shown.contains("KEVI_PASSWORD=\"p\\\"a\\$s\\`s\\\\\\nx\""));
+
+    let json: serde_json::Value =
+        serde_json::from_str(&format_entry(&e, EntryFormat::Json, true).unwrap()).unwrap();
+    assert_eq!(json["username"], "admin");
+    assert_eq!(json["password"], "p\"a$s`s\\\nx");
+    assert_eq!(json["notes"], "line1\nline2");
+}
+
+#[test]
+fn dotenv_rejects_custom_names_that_map_to_one_variable() {
+    let mut e = sample();
+    e.custom
+        .insert("pin-code".into(), SecretString::new("1".into()));
+    e.custom
+        .insert("pin_code".into(), SecretString::new("2".into()));
+    let err = format_entry(&e, EntryFormat::Dotenv, true).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "custom fields 'pin-code' and 'pin_code' both map to KEVI_CUSTOM_PIN_CODE; use --format json"
+    );
+    // Without --reveal custom fields are not printed, so nothing collides
+    assert!(format_entry(&e, EntryFormat::Dotenv, false).is_ok());
+    assert!(format_entry(&e, EntryFormat::Json, true).is_ok());
+}
+
+#[test]
+fn cli_get_field_all_prints_dotenv_and_json() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    save_vault_file(&[sample()], &path, "pw").expect("seed vault");
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .args(["get", "db", "--field", "all", "--path"])
+        .arg(path.to_string_lossy().to_string());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("KEVI_USER=\"admin\""))
+        .stdout(predicate::str::contains("KEVI_PASSWORD").not());
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .args([
+            "get", "db", "--field", "all", "--format", "json", "--reveal",
+        ])
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string());
+    let out = cmd.assert().success().get_output().stdout.clone();
+    let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    assert_eq!(json["label"], "db");
+    assert_eq!(json["password"], "p\"a$s`s\\\nx");
+}
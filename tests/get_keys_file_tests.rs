@@ -0,0 +1,95 @@
+use assert_cmd::Command;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn entry(label: &str, password: &str) -> VaultEntry {
+    VaultEntry::new(label, password)
+}
+
+fn seed(path: &Path) {
+    let entries = [entry("github", "gh-pw"), entry("gitlab", "gl-pw")];
+    save_vault_file(&entries, path, "pw").expect("seed vault");
+}
+
+#[test]
+fn keys_file_prints_each_field_in_order() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+    let keys = dir.path().join("labels.txt");
+    std::fs::write(&keys, "gitlab\n\ngithub\n").unwrap();
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .args(["get", "--field", "password", "--echo", "--no-copy"])
+        .arg("--keys-file")
+        .arg(&keys)
+        .arg("--path")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("gl-pw\ngh-pw\n");
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .args(["get", "--keys-file", "-", "--print0", "--path"])
+        .arg(&path)
+        .write_stdin("github\ngitlab\n")
+        .assert()
+        .success()
+        .stdout("gh-pw\0gl-pw\0");
+}
+
+#[test]
+fn keys_file_reports_missing_labels_and_fails() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+    let keys = dir.path().join("labels.txt");
+    std::fs::write(&keys, "github\nnope\ngit\n").unwrap();
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .args(["get", "--echo"])
+        .arg("--keys-file")
+        .arg(&keys)
+        .arg("--path")
+        .arg(&path)
+        .assert()
+        .failure()
+        .stdout("gh-pw\n")
+        .stderr(predicate::str::contains("no entry found with key 'nope'"))
+        .stderr(predicate::str::contains("matches several entries"))
+        .stderr(predicate::str::contains("2 of 3 keys could not be printed"));
+}
+
+#[test]
+fn keys_file_needs_an_explicit_output_flag() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+
+    for extra in [
+        &[][..],
+        &["--echo", "--ttl", "30"][..],
+        &["--echo", "--reveal"][..],
+    ] {
+        Command::cargo_bin("kevi")
+            .unwrap()
+            .env("KEVI_PASSWORD", "pw")
+            .args(["get", "--keys-file", "-"])
+            .args(extra)
+            .arg("--path")
+            .arg(&path)
+            .write_stdin("github\n")
+            .assert()
+            .code(2)
+            .stdout("");
+    }
+}
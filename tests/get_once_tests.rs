@@ -16,10 +16,8 @@ fn get_once_bypasses_session_cache_and_does_not_create_it() {
 
     // Seed vault with one item
     let entry = VaultEntry {
-        label: "k".into(),
         username: Some(SecretString::new("u".into())),
-        password: SecretString::new("s3cr3t".into()),
-        notes: None,
+        ..VaultEntry::new("k", "s3cr3t")
     };
     save_vault_file(&[entry], &path, pw).expect("seed vault");
 
@@ -51,3 +49,48 @@ fn get_once_bypasses_session_cache_and_does_not_create_it() {
         "dk-session should not be created by --once"
     );
 }
+
+#[test]
+fn get_no_cache_ignores_an_existing_session_and_leaves_it_untouched() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let entry = VaultEntry::new("k", "s3cr3t");
+    save_vault_file(&[entry], &path, "pw").expect("seed vault");
+
+    let kevi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env_remove("KEVI_PASSWORD")
+            .arg("--no-prompt")
+            .args(args)
+            .arg("--path")
+            .arg(&path);
+        cmd
+    };
+    let mut unlock = kevi(&["unlock"]);
+    unlock.env("KEVI_PASSWORD", "pw");
+    unlock.assert().success();
+    let dk_path = dk_session_file_for(&path);
+    let before = std::fs::read(&dk_path).expect("session written by unlock");
+
+    // The valid session works for a normal get...
+    kevi(&["get", "k", "--no-copy", "--echo"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("s3cr3t"));
+
+    // ...but --no-cache / --once must not read it, so without a password they fail
+    for flag in ["--no-cache", "--once"] {
+        kevi(&["get", "k", "--no-copy", "--echo", flag])
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("s3cr3t").not());
+    }
+
+    // With the password, --once succeeds and leaves the session file as it was
+    kevi(&["get", "k", "--no-copy", "--echo", "--once"])
+        .env("KEVI_PASSWORD", "pw")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("s3cr3t"));
+    assert_eq!(std::fs::read(&dk_path).unwrap(), before);
+}
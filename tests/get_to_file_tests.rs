@@ -0,0 +1,124 @@
+use assert_cmd::Command;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+use predicates::prelude::*;
+use secrecy::SecretString;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn seed(path: &Path) {
+    let entry = VaultEntry {
+        username: Some(SecretString::new("admin".into())),
+        ..VaultEntry::new("db", "s3cr3t")
+    };
+    save_vault_file(&[entry], path, "pw").expect("seed vault");
+}
+
+fn get(path: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .arg("get")
+        .arg("db")
+        .arg("--path")
+        .arg(path);
+    cmd
+}
+
+#[test]
+fn get_to_file_writes_exact_secret_with_private_permissions() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+    let out = dir.path().join("db.pass");
+
+    get(&path)
+        .arg("--to-file")
+        .arg(&out)
+        .arg("--reveal")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("s3cr3t").not())
+        .stderr(predicate::str::contains("Wrote password of db"));
+    assert_eq!(std::fs::read(&out).unwrap(), b"s3cr3t");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&out).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
+
+#[test]
+fn get_to_file_requires_reveal_and_never_targets_the_vault() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+    let out = dir.path().join("db.pass");
+
+    get(&path)
+        .arg("--to-file")
+        .arg(&out)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--reveal"));
+    assert!(!out.exists());
+
+    let before = std::fs::read(&path).unwrap();
+    get(&path)
+        .arg("--to-file")
+        .arg(&path)
+        .arg("--reveal")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("refusing to overwrite the vault"));
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+}
+
+#[cfg(unix)]
+#[test]
+fn get_to_fifo_writes_once_to_a_named_pipe() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+    let fifo = dir.path().join("db.fifo");
+    let status = std::process::Command::new("mkfifo")
+        .arg(&fifo)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let reader = {
+        let fifo = fifo.clone();
+        std::thread::spawn(move || std::fs::read(fifo).unwrap())
+    };
+    get(&path)
+        .args(["--field", "user", "--reveal", "--to-fifo"])
+        .arg(&fifo)
+        .assert()
+        .success();
+    assert_eq!(reader.join().unwrap(), b"admin");
+
+    // A regular file is not a pipe
+    get(&path)
+        .args(["--reveal", "--to-fifo"])
+        .arg(&path)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not a named pipe"));
+}
+
+#[test]
+fn get_to_file_writes_username_field() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    seed(&path);
+    let out = dir.path().join("db.user");
+
+    get(&path)
+        .args(["--field", "user", "--reveal", "--to-file"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Wrote user of db"));
+    assert_eq!(std::fs::read(&out).unwrap(), b"admin");
+}
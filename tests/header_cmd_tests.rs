@@ -24,10 +24,8 @@ fn header_on_valid_vault_prints_fields() {
     let pw = "pw";
     // Save a simple encrypted vault (empty entries also fine)
     let entries: Vec<VaultEntry> = vec![VaultEntry {
-        label: "lbl".into(),
         username: Some(SecretString::new("u".into())),
-        password: SecretString::new("p".into()),
-        notes: None,
+        ..VaultEntry::new("lbl", "p")
     }];
     save_vault_file(&entries, &path, pw).expect("save vault");
 
@@ -105,3 +103,33 @@ fn header_unknown_aead_fails() {
         .failure()
         .stderr(predicate::str::contains("unsupported aead"));
 }
+
+#[test]
+fn header_verify_reports_decrypt_ok_and_failed() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    save_vault_file(&[], &path, "pw").expect("save vault");
+
+    // A failed check drops the cached key, so the right password is asked for again
+    let mut bad = Command::cargo_bin("kevi").unwrap();
+    bad.env("KEVI_PASSWORD", "wrong")
+        .arg("header")
+        .arg("--verify")
+        .arg("--path")
+        .arg(&path);
+    bad.assert()
+        .failure()
+        .stdout(predicate::str::contains("KEVI header:"))
+        .stdout(predicate::str::contains("decrypt: FAILED"));
+
+    let mut ok = Command::cargo_bin("kevi").unwrap();
+    ok.env("KEVI_PASSWORD", "pw")
+        .arg("header")
+        .arg("--verify")
+        .arg("--path")
+        .arg(&path);
+    ok.assert()
+        .success()
+        .stdout(predicate::str::contains("KEVI header:"))
+        .stdout(predicate::str::contains("decrypt: OK"));
+}
@@ -0,0 +1,60 @@
+use assert_cmd::prelude::*;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+use predicates::prelude::*;
+use secrecy::SecretString;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn info_summarizes_vault_without_secrets() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    let entries = vec![
+        VaultEntry {
+            username: Some(SecretString::new("alice".into())),
+            notes: Some("personal".into()),
+            updated_at: Some(1_000),
+            ..VaultEntry::new("mail", "S3cret-Passw0rd!")
+        },
+        VaultEntry {
+            updated_at: Some(2_000),
+            ..VaultEntry::new("wifi", "hunter2")
+        },
+    ];
+    save_vault_file(&entries, &path, "pw").expect("save vault");
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .arg("info")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string());
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Argon2id"))
+        .stdout(predicate::str::contains("Entries: 2"))
+        .stdout(predicate::str::contains("with username: 1"))
+        .stdout(predicate::str::contains("with notes:    1"))
+        .stdout(predicate::str::contains("weakest:"))
+        .stdout(predicate::str::contains("oldest:        1000 ("))
+        .stdout(predicate::str::contains("newest:        2000 ("))
+        .stdout(predicate::str::contains("hunter2").not())
+        .stdout(predicate::str::contains("alice").not())
+        .stdout(predicate::str::contains("personal").not());
+}
+
+#[test]
+fn doctor_runs_without_password_and_reports_clipboard_checks() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("missing.ron");
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env_remove("KEVI_PASSWORD")
+        .arg("--no-color")
+        .arg("doctor")
+        .arg("--path")
+        .arg(&path);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("(not found)"))
+        .stdout(predicate::str::contains("lipboard").count(2));
+}
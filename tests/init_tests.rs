@@ -1,9 +1,11 @@
 use kevi::config::app_config::Config;
-use kevi::vault::handlers::Vault;
+use kevi::cryptography::primitives::{default_params, parse_kevi_header, KdfPreset};
+use kevi::vault::handlers::{InitKdf, Vault};
 use std::env;
 use std::fs;
 #[cfg(target_family = "unix")]
 use std::os::unix::fs::PermissionsExt;
+use std::time::Duration;
 use tempfile::tempdir;
 
 #[tokio::test]
@@ -17,7 +19,10 @@ async fn test_init_creates_encrypted_vault() {
 
     let config = Config::create(None, None).unwrap();
     let vault = Vault::create(&config);
-    vault.handle_init(Some(&path_str)).await.unwrap();
+    vault
+        .handle_init(Some(&path_str), InitKdf::Default)
+        .await
+        .unwrap();
 
     let bytes = fs::read(&path).unwrap();
     assert!(
@@ -32,3 +37,144 @@ async fn test_init_creates_encrypted_vault() {
         assert_eq!(mode, 0o600, "vault file permissions should be 0600 on Unix");
     }
 }
+
+#[tokio::test]
+async fn test_init_into_nested_missing_path_and_directory() {
+    let dir = tempdir().unwrap();
+    env::set_var("KEVI_PASSWORD", "initpw");
+    let config = Config::create(None, None).unwrap();
+    let vault = Vault::create(&config);
+
+    // Nested, non-existent parents are created
+    let nested = dir.path().join("a").join("b").join("vault.ron");
+    vault
+        .handle_init(Some(&nested.to_string_lossy()), InitKdf::Default)
+        .await
+        .unwrap();
+    assert!(fs::read(&nested).unwrap().starts_with(b"KEVI"));
+
+    #[cfg(target_family = "unix")]
+    {
+        let parent = nested.parent().unwrap();
+        let mode = fs::metadata(parent).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o700, "vault directory should be 0700 on Unix");
+    }
+
+    // An existing directory gets the default file name
+    let existing = dir.path().join("dir");
+    fs::create_dir(&existing).unwrap();
+    vault
+        .handle_init(Some(&existing.to_string_lossy()), InitKdf::Default)
+        .await
+        .unwrap();
+    assert!(fs::read(existing.join("vault.ron"))
+        .unwrap()
+        .starts_with(b"KEVI"));
+}
+
+#[tokio::test]
+async fn test_init_reports_uncreatable_parent() {
+    let dir = tempdir().unwrap();
+    env::set_var("KEVI_PASSWORD", "initpw");
+    let blocker = dir.path().join("file");
+    fs::write(&blocker, b"x").unwrap();
+    let target = blocker.join("sub").join("vault.ron");
+
+    let config = Config::create(None, None).unwrap();
+    let vault = Vault::create(&config);
+    let err = vault
+        .handle_init(Some(&target.to_string_lossy()), InitKdf::Default)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("cannot create vault directory"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_init_auto_kdf_stores_calibrated_params() {
+    let dir = tempdir().unwrap();
+    env::set_var("KEVI_PASSWORD", "initpw");
+    let path = dir.path().join("vault.ron");
+
+    let config = Config::create(None, None).unwrap();
+    let vault = Vault::create(&config);
+    // A 1ms target is below the floor, so calibration keeps the defaults
+    vault
+        .handle_init(
+            Some(&path.to_string_lossy()),
+            InitKdf::Calibrate(Duration::from_millis(1)),
+        )
+        .await
+        .unwrap();
+
+    let bytes = fs::read(&path).unwrap();
+    let (hdr, _) = parse_kevi_header(&bytes).unwrap();
+    assert_eq!((hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes), default_params());
+}
+
+#[test]
+fn init_preset_stores_preset_params_in_header() {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    use std::process::Command;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "initpw")
+        .args(["init", "--preset", "fast"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("KEVI header:"))
+        .stdout(predicate::str::contains("kdf: Argon2id"))
+        .stdout(predicate::str::contains("aead: AES-256-GCM"))
+        .stdout(predicate::str::contains(format!(
+            "argon2 m_cost_kib: {}",
+            KdfPreset::Fast.params().0
+        )));
+    let (hdr, _) = parse_kevi_header(&fs::read(&path).unwrap()).unwrap();
+    assert_eq!(
+        (hdr.m_cost_kib, hdr.t_cost, hdr.p_lanes),
+        KdfPreset::Fast.params()
+    );
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "initpw")
+        .args(["init", "--preset", "paranoid", "--auto-kdf"])
+        .arg(dir.path().join("other.ron"))
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn init_checks_password_confirmation_from_env() {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    use std::process::Command;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    let init = |confirm: &str| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", "initpw")
+            .env("KEVI_PASSWORD_CONFIRM", confirm)
+            .args(["init", "--preset", "fast"])
+            .arg(&path);
+        cmd.assert()
+    };
+
+    init("initpx")
+        .failure()
+        .stderr(predicate::str::contains("do not match"))
+        .stderr(predicate::str::contains("retry"));
+    assert!(!path.exists());
+
+    init("initpw").success();
+    assert!(path.exists());
+}
@@ -15,23 +15,14 @@ fn list_filters_with_query_and_emits_json() {
     // Seed vault
     let entries = vec![
         VaultEntry {
-            label: "alpha".into(),
             username: Some(SecretString::new("alice".into())),
-            password: SecretString::new("a".into()),
-            notes: None,
+            ..VaultEntry::new("alpha", "a")
         },
         VaultEntry {
-            label: "beta".into(),
             username: Some(SecretString::new("bob".into())),
-            password: SecretString::new("b".into()),
-            notes: None,
-        },
-        VaultEntry {
-            label: "gamma".into(),
-            username: None,
-            password: SecretString::new("c".into()),
-            notes: None,
+            ..VaultEntry::new("beta", "b")
         },
+        VaultEntry::new("gamma", "c"),
     ];
     save_vault_file(&entries, &path, pw).expect("seed vault");
 
@@ -70,3 +61,225 @@ fn list_filters_with_query_and_emits_json() {
     let arr2 = v2.as_array().unwrap();
     assert!(arr2.iter().all(|o| o.get("username").is_none()));
 }
+
+#[test]
+fn list_json_lines_emits_one_compact_object_per_line() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+
+    let entries: Vec<VaultEntry> = ["alpha", "beta"]
+        .iter()
+        .map(|label| VaultEntry {
+            username: Some(SecretString::new(format!("{label}-user").into())),
+            ..VaultEntry::new(*label, "secret")
+        })
+        .collect();
+    save_vault_file(&entries, &path, pw).expect("seed vault");
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", pw)
+        .arg("list")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string())
+        .arg("--show-users")
+        .arg("--json-lines");
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], r#"{"label":"alpha","username":"alpha-user"}"#);
+    let second: serde_json::Value = serde_json::from_str(lines[1]).expect("valid json");
+    assert_eq!(second["label"], "beta");
+    assert!(!out.contains("secret"));
+}
+
+#[test]
+fn list_porcelain_prints_bare_labels_and_nothing_when_empty() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+    let list = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", pw)
+            .env_remove("NO_COLOR")
+            .arg("list")
+            .arg("--path")
+            .arg(path.to_string_lossy().to_string())
+            .args(args);
+        cmd.assert().success().get_output().stdout.clone()
+    };
+
+    save_vault_file(&[], &path, pw).expect("seed empty vault");
+    assert_eq!(list(&["--porcelain"]), b"");
+    assert_eq!(list(&["-0"]), b"");
+
+    let entries: Vec<VaultEntry> = ["beta", "alpha"]
+        .iter()
+        .map(|label| VaultEntry {
+            username: Some(SecretString::new("u".into())),
+            ..VaultEntry::new(*label, "secret")
+        })
+        .collect();
+    save_vault_file(&entries, &path, pw).expect("seed vault");
+    assert_eq!(list(&["--porcelain"]), b"beta\nalpha\n");
+    assert_eq!(list(&["--print0", "--sort", "label"]), b"alpha\0beta\0");
+}
+
+#[test]
+fn list_missing_filters_incomplete_entries_and_info_counts_them() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+
+    let entry = |label: &str, user: Option<&str>, notes: Option<&str>| VaultEntry {
+        username: user.map(|u| SecretString::new(u.into())),
+        notes: notes.map(str::to_string),
+        ..VaultEntry::new(label, "secret")
+    };
+    let entries = vec![
+        entry("full", Some("alice"), Some("note")),
+        entry("no-user", Some(""), Some("note")),
+        entry("bare", None, None),
+    ];
+    save_vault_file(&entries, &path, pw).expect("seed vault");
+
+    let list = |missing: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", pw)
+            .arg("list")
+            .arg("--path")
+            .arg(&path)
+            .arg("--json");
+        for field in missing {
+            cmd.arg("--missing").arg(field);
+        }
+        let out = cmd.assert().success().get_output().stdout.clone();
+        let v: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+        v.as_array()
+            .unwrap()
+            .iter()
+            .map(|o| o["label"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(list(&["user"]), ["no-user", "bare"]);
+    assert_eq!(list(&["user", "notes"]), ["bare"]);
+    assert_eq!(list(&["custom"]).len(), 3);
+
+    let mut info = Command::cargo_bin("kevi").unwrap();
+    info.env("KEVI_PASSWORD", pw)
+        .arg("info")
+        .arg("--path")
+        .arg(&path)
+        .arg("--incomplete");
+    let out = info.assert().success().get_output().stdout.clone();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("Incomplete: 3"), "{out}");
+    assert!(out.contains("no username:   2"), "{out}");
+    assert!(out.contains("no notes:      1"), "{out}");
+}
+
+#[test]
+fn list_json_fields_selects_metadata_and_never_secrets() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+
+    let entries = vec![
+        VaultEntry {
+            username: Some(SecretString::new("alice".into())),
+            notes: Some("work".into()),
+            custom: [("pin".to_string(), SecretString::new("4242".into()))].into(),
+            created_at: Some(100),
+            updated_at: Some(200),
+            ..VaultEntry::new("alpha", "topsecret")
+        },
+        VaultEntry::new("beta", "topsecret"),
+    ];
+    save_vault_file(&entries, &path, pw).expect("seed vault");
+
+    let list = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", pw)
+            .arg("list")
+            .arg("--path")
+            .arg(&path)
+            .args(args);
+        cmd.assert()
+    };
+
+    let out = list(&["--json", "--json-fields", "label,updated_at,custom_fields"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+    assert_eq!(
+        v,
+        serde_json::json!([
+            {"label": "alpha", "updated_at": 200, "custom_fields": ["pin"]},
+            {"label": "beta", "updated_at": null, "custom_fields": []},
+        ])
+    );
+
+    let out = list(&["--json-lines", "--json-fields", "username,notes,created_at"])
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let out = String::from_utf8(out).unwrap();
+    assert_eq!(
+        out,
+        "{\"created_at\":100,\"notes\":\"work\",\"username\":\"alice\"}\n\
+         {\"created_at\":null,\"notes\":null,\"username\":null}\n"
+    );
+    assert!(!out.contains("topsecret") && !out.contains("4242"));
+
+    list(&["--json-fields", "password"]).failure();
+    list(&["--json-fields", "label"])
+        .failure()
+        .stderr(predicates::str::contains("--json-fields needs --json"));
+}
+
+#[test]
+fn username_secret_false_shows_usernames_without_show_users() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+
+    let entries = vec![VaultEntry {
+        username: Some(SecretString::new("alice".into())),
+        ..VaultEntry::new("alpha", "topsecret")
+    }];
+    save_vault_file(&entries, &path, pw).expect("seed vault");
+
+    let run = |secret: &str, args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", pw)
+            .env("KEVI_CONFIG_DIR", td.path())
+            .env("KEVI_USERNAME_SECRET", secret)
+            .args(args)
+            .arg("--path")
+            .arg(&path);
+        String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+    };
+
+    // Default: usernames stay hidden unless asked for
+    assert_eq!(run("true", &["list"]), "alpha\n");
+    assert_eq!(
+        run("true", &["list", "--json"]),
+        "[\n  {\n    \"label\": \"alpha\"\n  }\n]\n"
+    );
+    assert!(!run("true", &["search", "ali"]).contains("alice"));
+
+    assert_eq!(run("false", &["list"]), "alpha\talice\n");
+    let v: serde_json::Value =
+        serde_json::from_str(&run("false", &["list", "--json"])).expect("valid json");
+    assert_eq!(
+        v,
+        serde_json::json!([{"label": "alpha", "username": "alice"}])
+    );
+    assert!(run("false", &["search", "ali"]).contains("alice"));
+    // Porcelain output stays labels only
+    assert_eq!(run("false", &["list", "--porcelain"]), "alpha\n");
+}
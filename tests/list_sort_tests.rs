@@ -0,0 +1,139 @@
+use assert_cmd::prelude::*;
+use secrecy::SecretString;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+
+fn entry(label: &str, user: Option<&str>, created: u64, updated: u64) -> VaultEntry {
+    VaultEntry {
+        username: user.map(|u| SecretString::new(u.into())),
+        created_at: Some(created),
+        updated_at: Some(updated),
+        ..VaultEntry::new(label, "pw")
+    }
+}
+
+fn list(path: &std::path::Path, args: &[&str]) -> Vec<String> {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .arg("list")
+        .arg("--path")
+        .arg(path)
+        .args(args);
+    let out = cmd.assert().success().get_output().stdout.clone();
+    String::from_utf8(out)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+fn seed(path: &std::path::Path) {
+    let entries = vec![
+        entry("charlie", Some("Zed"), 300, 300),
+        entry("Alpha", Some("bob"), 100, 500),
+        entry("bravo", None, 200, 200),
+    ];
+    save_vault_file(&entries, path, "pw").expect("seed vault");
+}
+
+#[test]
+fn list_sorts_by_label_case_insensitively_and_reverses() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    assert_eq!(list(&path, &[]), ["charlie", "Alpha", "bravo"]);
+    assert_eq!(
+        list(&path, &["--sort", "label"]),
+        ["Alpha", "bravo", "charlie"]
+    );
+    assert_eq!(
+        list(&path, &["--sort", "label", "--reverse"]),
+        ["charlie", "bravo", "Alpha"]
+    );
+}
+
+#[test]
+fn list_sorts_by_user_and_timestamps() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    assert_eq!(
+        list(&path, &["--sort", "user", "--show-users"]),
+        ["bravo", "Alpha\tbob", "charlie\tZed"]
+    );
+    assert_eq!(
+        list(&path, &["--sort", "created"]),
+        ["Alpha", "bravo", "charlie"]
+    );
+    assert_eq!(
+        list(&path, &["--sort", "updated", "--reverse"]),
+        ["Alpha", "charlie", "bravo"]
+    );
+}
+
+#[test]
+fn list_sort_composes_with_query_and_json() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    let out = list(&path, &["--query", "a", "--sort", "label", "--json"]).join("\n");
+    let v: serde_json::Value = serde_json::from_str(&out).expect("valid json");
+    let labels: Vec<&str> = v
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|o| o["label"].as_str().unwrap())
+        .collect();
+    assert_eq!(labels, ["Alpha", "bravo", "charlie"]);
+}
+
+#[test]
+fn list_recent_falls_back_to_updated_without_tracking() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    // Tracking is off by default: reads leave the vault untouched
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .args(["show", "bravo", "--path"])
+        .arg(&path)
+        .assert()
+        .success();
+    assert_eq!(list(&path, &["--recent"]), ["Alpha", "charlie", "bravo"]);
+    assert_eq!(
+        list(&path, &["--recent", "--reverse"]),
+        ["bravo", "charlie", "Alpha"]
+    );
+}
+
+#[test]
+fn list_recent_puts_tracked_accesses_first() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_PASSWORD", "pw")
+        .env("KEVI_TRACK_ACCESS", "true")
+        .args(["show", "bravo", "--path"])
+        .arg(&path)
+        .assert()
+        .success();
+    assert_eq!(list(&path, &["--recent"]), ["bravo", "Alpha", "charlie"]);
+
+    let entries = kevi::vault::persistence::load_vault_file(&path, "pw").unwrap();
+    let bravo = entries.iter().find(|e| e.label == "bravo").unwrap();
+    assert!(bravo.last_accessed_at.is_some());
+    assert_eq!(bravo.updated_at, Some(200), "access is not an edit");
+    // Access stamps rewrite the vault without rotating backups
+    assert!(!td.path().join("vault.ron.1").exists());
+}
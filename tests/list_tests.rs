@@ -16,17 +16,10 @@ fn list_shows_labels_by_default_and_user_when_requested() {
     // Seed vault
     let entries = vec![
         VaultEntry {
-            label: "alpha".into(),
             username: Some(SecretString::new("alice".into())),
-            password: SecretString::new("aaa".into()),
-            notes: None,
-        },
-        VaultEntry {
-            label: "beta".into(),
-            username: None,
-            password: SecretString::new("bbb".into()),
-            notes: None,
+            ..VaultEntry::new("alpha", "aaa")
         },
+        VaultEntry::new("beta", "bbb"),
     ];
     save_vault_file(&entries, &path, pw).expect("seed vault");
 
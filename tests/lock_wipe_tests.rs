@@ -0,0 +1,129 @@
+use assert_cmd::Command;
+use kevi::filesystem::secure::{backup_path, wipe_file};
+use kevi::session_management::resolver::dk_session_file_for;
+use kevi::vault::persistence::{load_vault_file, save_vault_file};
+use predicates::prelude::*;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn kevi(config_dir: &Path, args: &[&str], vault: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_CONFIG_DIR", config_dir)
+        .env("KEVI_PASSWORD", "pw")
+        .args(args)
+        .arg("--path")
+        .arg(vault);
+    cmd
+}
+
+#[test]
+fn lock_wipe_sessions_clears_every_profile_and_backups_need_confirmation() {
+    let td = tempdir().unwrap();
+    let config_dir = td.path().join("config");
+    fs::create_dir_all(config_dir.join("kevi")).unwrap();
+    let home = td.path().join("home.ron");
+    let work = td.path().join("work.ron");
+    fs::write(
+        config_dir.join("kevi").join("config.toml"),
+        format!(
+            "[profiles.work]\nvault_path = {:?}\n",
+            work.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+    for vault in [&home, &work] {
+        save_vault_file(&[], vault, "pw").unwrap();
+        kevi(&config_dir, &["unlock"], vault).assert().success();
+        assert!(dk_session_file_for(vault).exists());
+    }
+    // Two saves leave a backup of the home vault
+    save_vault_file(&[], &home, "pw").unwrap();
+    kevi(&config_dir, &["add", "--label", "x", "--generate"], &home)
+        .assert()
+        .success();
+    let backup = backup_path(&home, 1);
+    assert!(backup.exists());
+
+    kevi(&config_dir, &["lock", "--wipe-sessions"], &home)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wiped 2 derived-key session(s)"));
+    assert!(!dk_session_file_for(&home).exists());
+    assert!(!dk_session_file_for(&work).exists());
+
+    // Without a terminal, wiping backups needs --yes
+    kevi(&config_dir, &["lock", "--wipe-backups"], &home)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--yes"));
+    assert!(backup.exists());
+
+    kevi(&config_dir, &["lock", "--wipe-backups", "--yes"], &home)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wiped"));
+    assert!(!backup.exists());
+    // The vault itself is never touched
+    assert_eq!(load_vault_file(&home, "pw").unwrap().len(), 1);
+}
+
+#[test]
+fn lock_wipe_sessions_expands_profile_paths() {
+    let td = tempdir().unwrap();
+    let config_dir = td.path().join("config");
+    let home_dir = td.path().join("home");
+    fs::create_dir_all(config_dir.join("kevi")).unwrap();
+    fs::create_dir_all(&home_dir).unwrap();
+    fs::write(
+        config_dir.join("kevi").join("config.toml"),
+        "[profiles.work]\nvault_path = \"~/work.ron\"\n",
+    )
+    .unwrap();
+    let home = td.path().join("home.ron");
+    let work = home_dir.join("work.ron");
+    for vault in [&home, &work] {
+        save_vault_file(&[], vault, "pw").unwrap();
+        kevi(&config_dir, &["unlock"], vault)
+            .env("HOME", &home_dir)
+            .assert()
+            .success();
+    }
+
+    kevi(&config_dir, &["lock", "--wipe-sessions"], &home)
+        .env("HOME", &home_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wiped 2 derived-key session(s)"));
+    assert!(!dk_session_file_for(&work).exists());
+}
+
+#[test]
+fn lock_clears_sessions_left_under_the_legacy_name() {
+    let td = tempdir().unwrap();
+    let config_dir = td.path().join("config");
+    let vault = td.path().join("vault.ron");
+    let legacy = td.path().join("vault.dksession");
+    save_vault_file(&[], &vault, "pw").unwrap();
+
+    fs::write(&legacy, "stale").unwrap();
+    kevi(&config_dir, &["lock"], &vault).assert().success();
+    assert!(!legacy.exists());
+
+    fs::write(&legacy, "stale").unwrap();
+    kevi(&config_dir, &["lock", "--wipe-sessions"], &vault)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wiped 1 derived-key session(s)"));
+    assert!(!legacy.exists());
+}
+
+#[test]
+fn wipe_file_zeroes_and_removes_only_what_exists() {
+    let td = tempdir().unwrap();
+    let f = td.path().join("secret.bin");
+    fs::write(&f, vec![0xAB; 20_000]).unwrap();
+    assert!(wipe_file(&f).unwrap());
+    assert!(!f.exists());
+    assert!(!wipe_file(&f).unwrap());
+}
@@ -0,0 +1,45 @@
+use assert_cmd::Command;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+use secrecy::SecretString;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn stderr_of(path: &Path, args: &[&str]) -> String {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "master-pw")
+        .args(args)
+        .arg("--path")
+        .arg(path);
+    let out = cmd.assert().success().get_output().clone();
+    String::from_utf8(out.stderr).unwrap()
+}
+
+#[test]
+fn verbose_flag_reports_cache_events_without_secrets() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    let entry = VaultEntry {
+        username: Some(SecretString::new("admin".into())),
+        ..VaultEntry::new("db", "s3cr3t-value")
+    };
+    save_vault_file(&[entry], &path, "master-pw").unwrap();
+
+    let miss = stderr_of(&path, &["-vv", "get", "db", "--no-copy", "--echo"]);
+    assert!(miss.contains("session cache miss"), "{miss}");
+    assert!(miss.contains("deriving key with Argon2id (m="), "{miss}");
+    assert!(miss.contains("caching derived key"), "{miss}");
+
+    // Silent by default
+    let quiet = stderr_of(&path, &["get", "db", "--no-copy", "--echo"]);
+    assert!(!quiet.contains("kevi["), "unexpected diagnostics: {quiet}");
+
+    let hit = stderr_of(&path, &["get", "db", "--no-copy", "--echo", "-v"]);
+    assert!(hit.contains("session cache hit"), "{hit}");
+    assert!(!hit.contains("deriving key"), "{hit}");
+
+    for log in [&miss, &hit] {
+        assert!(!log.contains("master-pw"));
+        assert!(!log.contains("s3cr3t-value"));
+    }
+}
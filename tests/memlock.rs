@@ -13,3 +13,30 @@ fn test_lock_and_unlock_ok_or_best_effort() {
         assert!(unlock.is_ok());
     }
 }
+
+#[cfg(all(target_os = "linux", feature = "memlock"))]
+fn vm_lck_kib() -> u64 {
+    std::fs::read_to_string("/proc/self/status")
+        .unwrap()
+        .lines()
+        .find_map(|l| l.strip_prefix("VmLck:"))
+        .and_then(|v| v.trim().trim_end_matches("kB").trim().parse().ok())
+        .unwrap()
+}
+
+#[test]
+fn test_derived_key_is_locked_until_drop() {
+    #[cfg(all(target_os = "linux", feature = "memlock"))]
+    {
+        use kevi::vault::ports::DerivedKey;
+        let before = vm_lck_kib();
+        let dk = DerivedKey::new(vec![7u8; 32]);
+        let during = vm_lck_kib();
+        drop(dk);
+        let after = vm_lck_kib();
+        // mlock may be denied (RLIMIT_MEMLOCK); then there is nothing to observe
+        if during > before {
+            assert_eq!(after, before);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use secrecy::ExposeSecret;
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::load_vault_file;
+
+#[test]
+fn migrate_from_plaintext_encrypts_in_place_and_keeps_backup() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let entries = vec![VaultEntry::new("legacy", "old-secret")];
+    let plaintext = ron::to_string(&entries).unwrap();
+    fs::write(&path, &plaintext).unwrap();
+
+    // Guard flag is required
+    let mut guarded = Command::cargo_bin("kevi").unwrap();
+    guarded
+        .env("KEVI_PASSWORD", "newpw")
+        .arg("migrate-from-plaintext")
+        .arg(path.to_string_lossy().to_string());
+    guarded
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--i-have-a-plaintext-vault"));
+    assert_eq!(fs::read_to_string(&path).unwrap(), plaintext);
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "newpw")
+        .arg("migrate-from-plaintext")
+        .arg(path.to_string_lossy().to_string())
+        .arg("--i-have-a-plaintext-vault");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Encrypted 1 entries"))
+        .stdout(predicate::str::contains("securely delete"));
+
+    assert!(fs::read(&path).unwrap().starts_with(b"KEVI"));
+    let loaded = load_vault_file(&path, "newpw").unwrap();
+    assert_eq!(loaded[0].password.expose_secret(), "old-secret");
+
+    let backup = td.path().join("vault.ron.plaintext.bak");
+    assert_eq!(fs::read_to_string(&backup).unwrap(), plaintext);
+    assert!(
+        !td.path().join("vault.ron.1").exists(),
+        "no rotated plaintext backup"
+    );
+
+    // Running again on an encrypted vault is refused
+    let mut again = Command::cargo_bin("kevi").unwrap();
+    again
+        .env("KEVI_PASSWORD", "newpw")
+        .arg("migrate-from-plaintext")
+        .arg(path.to_string_lossy().to_string())
+        .arg("--i-have-a-plaintext-vault");
+    again
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already an encrypted vault"));
+}
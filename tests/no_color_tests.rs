@@ -0,0 +1,85 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use ratatui::style::Color;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::cli::output::{set_no_color, status};
+use kevi::tui::theme::Theme;
+
+#[test]
+fn no_color_env_strips_emoji_from_status_messages() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("NO_COLOR", "1")
+        .arg("rm")
+        .arg("missing")
+        .arg("--yes")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string());
+    cmd.assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "no entry found with key 'missing'",
+        ))
+        .stderr(predicate::str::contains("❌").not());
+}
+
+#[test]
+fn no_color_flag_strips_emoji_from_errors() {
+    let td = tempdir().unwrap();
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env_remove("NO_COLOR")
+        .env("KEVI_CONFIG_DIR", td.path())
+        .arg("--no-color")
+        .arg("--profile")
+        .arg("does-not-exist")
+        .arg("list");
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::starts_with("Error:"));
+}
+
+#[test]
+fn plain_mode_uses_monochrome_theme() {
+    set_no_color(true);
+    let theme = Theme::default();
+    assert_eq!(theme.primary, Color::Reset);
+    assert_eq!(theme.selection, Color::Reset);
+    assert_eq!(status("✅", "done"), "done");
+    set_no_color(false);
+}
+
+#[test]
+fn ascii_flag_and_status_style_replace_emoji_prefixes() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let rm_missing = |args: &[&str], style: Option<&str>| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env_remove("NO_COLOR")
+            .env("KEVI_CONFIG_DIR", td.path())
+            .args(args)
+            .args(["rm", "missing", "--yes", "--path"])
+            .arg(&path);
+        match style {
+            Some(s) => cmd.env("KEVI_STATUS_STYLE", s),
+            None => cmd.env_remove("KEVI_STATUS_STYLE"),
+        };
+        cmd.assert().code(2)
+    };
+
+    rm_missing(&["--ascii"], None).stderr("[ERROR] Error: no entry found with key 'missing'\n");
+    rm_missing(&[], Some("none")).stderr("Error: no entry found with key 'missing'\n");
+    // --ascii wins over the configured style
+    rm_missing(&["--ascii"], Some("emoji")).stderr(predicate::str::starts_with("[ERROR]"));
+
+    std::fs::create_dir_all(td.path().join("kevi")).unwrap();
+    std::fs::write(
+        td.path().join("kevi/config.toml"),
+        "status_style = \"ascii\"\n",
+    )
+    .unwrap();
+    rm_missing(&[], None).stderr(predicate::str::starts_with("[ERROR]"));
+}
@@ -0,0 +1,126 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tempfile::tempdir;
+
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::{load_vault_file, save_vault_file};
+
+// A null stdin makes every run non-interactive
+fn kevi(path: &Path, args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.stdin(Stdio::null())
+        .args(args)
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string());
+    cmd
+}
+
+fn seed(path: &Path) {
+    let entry = VaultEntry::new("alpha", "aaa");
+    save_vault_file(&[entry], path, "pw").expect("seed vault");
+}
+
+#[test]
+fn add_without_label_fails_fast() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+    kevi(&path, &["add", "--generate"])
+        .env("KEVI_PASSWORD", "pw")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "missing --label in non-interactive mode",
+        ));
+}
+
+#[test]
+fn add_with_label_defaults_optional_fields() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+    kevi(&path, &["add", "--generate", "--label", "beta"])
+        .env("KEVI_PASSWORD", "pw")
+        .assert()
+        .success();
+    let entries = load_vault_file(&path, "pw").unwrap();
+    let beta = entries.iter().find(|e| e.label == "beta").unwrap();
+    assert!(beta.username.is_none());
+    assert!(beta.notes.is_none());
+}
+
+#[test]
+fn rm_without_yes_fails_fast() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+    kevi(&path, &["rm", "alpha"])
+        .env("KEVI_PASSWORD", "pw")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing --yes"))
+        .stdout(predicate::str::contains("Label:").not());
+    assert_eq!(load_vault_file(&path, "pw").unwrap().len(), 1);
+}
+
+#[test]
+fn rm_with_yes_prints_no_entry_details() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+    kevi(&path, &["rm", "alpha", "--yes"])
+        .env("KEVI_PASSWORD", "pw")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("moved to trash"))
+        .stdout(predicate::str::contains("Label:").not())
+        .stdout(predicate::str::contains("Password:").not());
+    assert!(load_vault_file(&path, "pw").unwrap().is_empty());
+}
+
+#[test]
+fn unlock_without_password_fails_fast() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+    kevi(&path, &["unlock"])
+        .env_remove("KEVI_PASSWORD")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "no password available (set KEVI_PASSWORD",
+        ));
+}
+
+#[test]
+fn init_without_password_fails_fast() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env_remove("KEVI_PASSWORD")
+        .arg("init")
+        .arg(path.to_string_lossy().to_string())
+        .stdin(Stdio::null())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no password available"));
+    assert!(!path.exists());
+}
+
+#[test]
+fn no_prompt_fails_cleanly_without_password() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+    for args in [&["--no-prompt", "list"][..], &["get", "alpha", "--no-copy"]] {
+        kevi(&path, args)
+            .env_remove("KEVI_PASSWORD")
+            .env("KEVI_NO_PROMPT", "1")
+            .assert()
+            .code(4)
+            .stderr(predicate::str::contains("no password available"))
+            .stderr(predicate::str::contains("panicked").not());
+    }
+}
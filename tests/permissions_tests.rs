@@ -0,0 +1,96 @@
+#![cfg(unix)]
+
+use assert_cmd::Command;
+use kevi::filesystem::secure::{audit_permissions, backup_path, fix_permissions, BackupNaming};
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+use predicates::prelude::*;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn chmod(path: &Path, mode: u32) {
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+}
+
+fn mode(path: &Path) -> u32 {
+    fs::metadata(path).unwrap().permissions().mode() & 0o777
+}
+
+#[test]
+fn audit_and_fix_cover_vault_backups_and_directory() {
+    let td = tempdir().unwrap();
+    let dir = td.path().join("vaults");
+    fs::create_dir(&dir).unwrap();
+    let vault = dir.join("vault.ron");
+    fs::write(&vault, b"x").unwrap();
+    fs::write(backup_path(&vault, 1), b"x").unwrap();
+    chmod(&vault, 0o644);
+    chmod(&backup_path(&vault, 1), 0o640);
+    chmod(&dir, 0o755);
+
+    let loose: Vec<_> = audit_permissions(&vault, &BackupNaming::default())
+        .into_iter()
+        .map(|l| (l.path, l.mode, l.wanted))
+        .collect();
+    assert_eq!(
+        loose,
+        vec![
+            (vault.clone(), 0o644, 0o600),
+            (backup_path(&vault, 1), 0o640, 0o600),
+            (dir.clone(), 0o755, 0o700),
+        ]
+    );
+
+    assert_eq!(
+        fix_permissions(&vault, &BackupNaming::default())
+            .unwrap()
+            .len(),
+        3
+    );
+    assert!(audit_permissions(&vault, &BackupNaming::default()).is_empty());
+    assert_eq!(mode(&vault), 0o600);
+    assert_eq!(mode(&backup_path(&vault, 1)), 0o600);
+    assert_eq!(mode(&dir), 0o700);
+}
+
+#[test]
+fn loose_vault_warns_on_read_and_fix_perms_repairs_it() {
+    let td = tempdir().unwrap();
+    let vault = td.path().join("vault.ron");
+    let entry = VaultEntry::new("a", "p");
+    save_vault_file(&[entry], &vault, "pw").unwrap();
+    chmod(td.path(), 0o700);
+    chmod(&vault, 0o644);
+
+    let kevi = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", "pw")
+            .args(args)
+            .arg("--path")
+            .arg(&vault);
+        cmd
+    };
+
+    kevi(&["list"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("mode 644"))
+        .stderr(predicate::str::contains("kevi fix-perms"));
+    kevi(&["doctor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("expected 600"));
+
+    kevi(&["fix-perms"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("644 -> 600"));
+    assert_eq!(mode(&vault), 0o600);
+
+    kevi(&["list"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("fix-perms").not());
+}
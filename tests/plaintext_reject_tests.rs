@@ -4,7 +4,6 @@ use kevi::vault::codec::RonCodec;
 use kevi::vault::models::VaultEntry;
 use kevi::vault::persistence::load_vault_file;
 use kevi::vault::service::VaultService;
-use secrecy::SecretString;
 use std::fs;
 use std::sync::Arc;
 use tempfile::tempdir;
@@ -15,12 +14,7 @@ fn store_rejects_plaintext_vault_files() {
     let path = td.path().join("vault.ron");
 
     // Write a plaintext RON file (no KEVI header)
-    let entries = vec![VaultEntry {
-        label: "plain".into(),
-        username: None,
-        password: SecretString::new("pw".into()),
-        notes: None,
-    }];
+    let entries = vec![VaultEntry::new("plain", "pw")];
     let ron = ron::to_string(&entries).unwrap();
     fs::write(&path, ron).unwrap();
 
@@ -37,12 +31,7 @@ fn service_rejects_plaintext_vault_files() {
     let path = td.path().join("vault.ron");
 
     // Write a plaintext RON file (no KEVI header)
-    let entries = vec![VaultEntry {
-        label: "plain".into(),
-        username: None,
-        password: SecretString::new("pw".into()),
-        notes: None,
-    }];
+    let entries = vec![VaultEntry::new("plain", "pw")];
     let ron = ron::to_string(&entries).unwrap();
     fs::write(&path, ron).unwrap();
 
@@ -58,3 +47,46 @@ fn service_rejects_plaintext_vault_files() {
     let err = format!("{}", res.unwrap_err());
     assert!(err.contains("missing KEVI header"));
 }
+
+fn service_for(path: &std::path::Path) -> VaultService {
+    std::env::set_var("KEVI_PASSWORD", "pw");
+    let store = Arc::new(FileByteStore::new(path.to_path_buf()));
+    let codec = Arc::new(RonCodec);
+    let resolver = Arc::new(CachedKeyResolver::new(path.to_path_buf()));
+    VaultService::new(store, codec, resolver)
+}
+
+#[test]
+fn zero_byte_vault_loads_as_empty_and_initializes_on_save() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    fs::write(&path, b"").unwrap();
+
+    let svc = service_for(&path);
+    assert!(svc.load().unwrap().is_empty());
+    svc.save(&[]).unwrap();
+    assert!(fs::read(&path).unwrap().starts_with(b"KEVI"));
+}
+
+#[test]
+fn truncated_vault_is_refused_not_reinitialized() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    fs::write(&path, b"KEV").unwrap();
+
+    let svc = service_for(&path);
+    let err = format!("{}", svc.load().unwrap_err());
+    assert!(err.contains("truncated or corrupt"), "{err}");
+    assert!(err.contains("backup"), "{err}");
+
+    let err = format!("{}", svc.save(&[]).unwrap_err());
+    assert!(err.contains("truncated or corrupt"), "{err}");
+    assert_eq!(
+        fs::read(&path).unwrap(),
+        b"KEV",
+        "file must be left untouched"
+    );
+
+    let err = format!("{}", load_vault_file(&path, "pw").unwrap_err());
+    assert!(err.contains("truncated or corrupt"), "{err}");
+}
@@ -0,0 +1,78 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::vault::handlers::resolve_entry;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+
+fn entry(label: &str, password: &str) -> VaultEntry {
+    VaultEntry::new(label, password)
+}
+
+#[test]
+fn resolve_entry_prefers_exact_then_unique_prefix_then_substring() {
+    let entries = vec![
+        entry("github", "1"),
+        entry("github-work", "2"),
+        entry("gitlab", "3"),
+        entry("mail", "4"),
+    ];
+    let label = |k: &str, exact: bool| {
+        resolve_entry(&entries, k, exact)
+            .unwrap()
+            .map(|e| e.label.clone())
+    };
+
+    assert_eq!(label("github", false).as_deref(), Some("github"));
+    assert_eq!(label("gitl", false).as_deref(), Some("gitlab"));
+    assert_eq!(label("GITL", false).as_deref(), Some("gitlab"));
+    assert_eq!(label("ail", false).as_deref(), Some("mail"));
+    assert_eq!(label("nope", false), None);
+    assert_eq!(label("gitl", true), None);
+
+    let err = resolve_entry(&entries, "git", false).unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains("github") && msg.contains("gitlab"), "{msg}");
+}
+
+#[test]
+fn cli_get_by_prefix_and_exact_flag() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    save_vault_file(
+        &[entry("github", "gh-pw"), entry("gitlab", "gl-pw")],
+        &path,
+        "pw",
+    )
+    .expect("seed vault");
+
+    let get = |key: &str, exact: bool| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", "pw")
+            .arg("get")
+            .arg(key)
+            .arg("--path")
+            .arg(path.to_string_lossy().to_string())
+            .arg("--no-copy")
+            .arg("--echo");
+        if exact {
+            cmd.arg("--exact");
+        }
+        cmd
+    };
+
+    get("gitl", false)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("gl-pw"));
+    get("git", false)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("matches several entries"));
+    get("gitl", true)
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("no entry found with key 'gitl'"));
+}
@@ -66,3 +66,129 @@ fn profile_add_list_default_flow() {
     assert!(content.contains("[profiles.work]"));
     assert!(content.contains("default_profile = \"work\""));
 }
+
+#[test]
+fn profile_current_reports_which_rule_won() {
+    let td = tempdir().unwrap();
+    let config_dir = td.path().join("config");
+    let data_dir = td.path().join("data");
+    let current = |args: &[&str], env_vault: Option<&str>| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_CONFIG_DIR", &config_dir)
+            .env("KEVI_DATA_DIR", &data_dir)
+            .env_remove("KEVI_VAULT_PATH");
+        if let Some(v) = env_vault {
+            cmd.env("KEVI_VAULT_PATH", v);
+        }
+        cmd.args(args).args(["profile", "current"]);
+        cmd.assert().success()
+    };
+
+    current(&[], None)
+        .stdout(predicate::str::contains("profile: (none)"))
+        .stdout(predicate::str::contains("source: built-in default"));
+
+    fs::create_dir_all(config_dir.join("kevi")).unwrap();
+    fs::write(
+        config_dir.join("kevi").join("config.toml"),
+        "vault_path = \"/tmp/file.ron\"\ndefault_profile = \"work\"\n\n\
+         [profiles.work]\nvault_path = \"/tmp/work.ron\"\n\n\
+         [profiles.home]\nvault_path = \"/tmp/home.ron\"\n",
+    )
+    .unwrap();
+
+    current(&[], None)
+        .stdout(predicate::str::contains("profile: work"))
+        .stdout(predicate::str::contains("vault_path: /tmp/work.ron"))
+        .stdout(predicate::str::contains("source: default_profile"));
+    current(&[], Some("/tmp/env.ron"))
+        .stdout(predicate::str::contains("vault_path: /tmp/env.ron"))
+        .stdout(predicate::str::contains("source: KEVI_VAULT_PATH"));
+    current(&["--profile", "home"], Some("/tmp/env.ron"))
+        .stdout(predicate::str::contains("profile: home"))
+        .stdout(predicate::str::contains("source: --profile"));
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_CONFIG_DIR", &config_dir)
+        .args(["profile", "current", "--path", "/tmp/cli.ron"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("vault_path: /tmp/cli.ron"))
+        .stdout(predicate::str::contains("source: --path"));
+}
+
+#[test]
+fn explicit_config_file_overrides_config_dir() {
+    let td = tempdir().unwrap();
+    let config_dir = td.path().join("config");
+    let explicit = td.path().join("project").join("kevi.toml");
+    fs::create_dir_all(config_dir.join("kevi")).unwrap();
+    fs::write(
+        config_dir.join("kevi/config.toml"),
+        "[profiles.home]\nvault_path = \"/tmp/home-vault.ron\"\n",
+    )
+    .unwrap();
+
+    // Profile commands write to the explicit file and leave the directory one alone
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_CONFIG_DIR", &config_dir)
+        .arg("--config")
+        .arg(&explicit)
+        .args(["profile", "add", "work", "--path", "/tmp/work-vault.ron"])
+        .assert()
+        .success();
+    let written = fs::read_to_string(&explicit).unwrap();
+    assert!(written.contains("[profiles.work]"));
+    assert!(!written.contains("home"));
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_CONFIG_DIR", &config_dir)
+        .args(["profile", "list", "--config"])
+        .arg(&explicit)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("work -> /tmp/work-vault.ron"))
+        .stdout(predicate::str::contains("home").not());
+
+    // Config::create resolves profiles from the same file
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_CONFIG_DIR", &config_dir)
+        .env_remove("KEVI_VAULT_PATH")
+        .args(["--profile", "work", "profile", "current"])
+        .arg("--config")
+        .arg(&explicit)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("/tmp/work-vault.ron"));
+}
+
+#[test]
+fn explicit_config_file_must_exist_and_parse() {
+    let td = tempdir().unwrap();
+    let missing = td.path().join("missing.toml");
+    let broken = td.path().join("broken.toml");
+    fs::write(&broken, "vault_path = [\n").unwrap();
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_CONFIG_DIR", td.path())
+        .env("KEVI_VAULT_PATH", td.path().join("vault.ron"))
+        .arg("--config")
+        .arg(&missing)
+        .arg("list")
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("cannot read config file"));
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_CONFIG_DIR", td.path())
+        .args(["profile", "list", "--config"])
+        .arg(&broken)
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("invalid config file"));
+}
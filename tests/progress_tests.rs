@@ -0,0 +1,35 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::cli::output::with_spinner;
+
+#[test]
+fn with_spinner_returns_closure_result() {
+    assert_eq!(with_spinner("Deriving key…", || 42), 42);
+}
+
+#[test]
+fn no_spinner_output_when_stderr_is_not_a_tty() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+
+    let mut init = Command::cargo_bin("kevi").unwrap();
+    init.env("KEVI_PASSWORD", "pw")
+        .arg("init")
+        .arg(path.to_string_lossy().to_string());
+    init.assert()
+        .success()
+        .stderr(predicate::str::contains("Deriving key").not());
+
+    let mut list = Command::cargo_bin("kevi").unwrap();
+    list.env("KEVI_PASSWORD", "pw")
+        .arg("list")
+        .arg("--json")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string());
+    list.assert()
+        .success()
+        .stderr(predicate::str::contains("Deriving key").not());
+}
@@ -0,0 +1,104 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use tempfile::tempdir;
+
+use kevi::cryptography::primitives::encrypt_vault;
+use kevi::vault::codec::RonCodec;
+use kevi::vault::persistence::load_vault_file;
+use kevi::vault::ports::VaultCodec;
+
+// Second entry is missing its password field
+const DAMAGED_LIST: &str = r#"[
+    /*[0]*/ (
+        label: "good",
+        username: Some("u1"),
+        password: "pw-one",
+        notes: None,
+    ),
+    /*[1]*/ (
+        label: "broken",
+        username: Some("u2"),
+        notes: Some("a, b (c)"),
+    ),
+    /*[2]*/ (
+        label: "also-good",
+        username: None,
+        password: "pw-three",
+        notes: None,
+    ),
+]"#;
+
+#[test]
+fn strict_decode_reports_line_without_secrets() {
+    let bad = r#"[(label: "x", username: None, password: 12345678, notes: None)]"#;
+    let err = RonCodec.decode(bad.as_bytes()).unwrap_err().to_string();
+    assert!(err.contains("line 1, column"), "got: {err}");
+    assert!(err.contains("kevi repair"));
+
+    let secret = r#"[(label: "x", username: None, password: "hunter2", notes: 5)]"#;
+    let err = RonCodec.decode(secret.as_bytes()).unwrap_err().to_string();
+    assert!(!err.contains("hunter2"), "got: {err}");
+}
+
+#[test]
+fn lenient_decode_skips_and_reports_bad_entries() {
+    let (doc, issues) = RonCodec.decode_lenient(DAMAGED_LIST.as_bytes()).unwrap();
+    let labels: Vec<&str> = doc.entries.iter().map(|e| e.label.as_str()).collect();
+    assert_eq!(labels, ["good", "also-good"]);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].section, "entries");
+    assert_eq!(issues[0].index, 1);
+    assert_eq!(issues[0].line, 8);
+    assert_eq!(issues[0].label.as_deref(), Some("broken"));
+    assert!(issues[0].error.contains("password"), "{}", issues[0].error);
+}
+
+#[test]
+fn lenient_decode_handles_document_layout() {
+    let content = r#"(
+    entries: [
+        (label: "keep", username: None, password: "p", notes: None),
+    ],
+    trash: [
+        (entry: (label: "old", username: None, password: "p", notes: None), deleted_at: 5),
+        (entry: (label: "bad", username: None, password: "p", notes: None), deleted_at: "soon"),
+    ],
+)"#;
+    let (doc, issues) = RonCodec.decode_lenient(content.as_bytes()).unwrap();
+    assert_eq!(doc.entries.len(), 1);
+    assert_eq!(doc.trash.len(), 1);
+    assert_eq!(issues.len(), 1);
+    assert_eq!(issues[0].section, "trash");
+    assert!(!issues[0].error.contains("soon"));
+}
+
+#[test]
+fn repair_command_saves_cleaned_vault_and_keeps_backup() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    fs::write(&path, encrypt_vault(DAMAGED_LIST.as_bytes(), "pw").unwrap()).unwrap();
+
+    let mut list = Command::cargo_bin("kevi").unwrap();
+    list.env("KEVI_PASSWORD", "pw")
+        .args(["list", "--path"])
+        .arg(&path);
+    list.assert()
+        .failure()
+        .stderr(predicate::str::contains("line 12").and(predicate::str::contains("kevi repair")));
+
+    let mut repair = Command::cargo_bin("kevi").unwrap();
+    repair
+        .env("KEVI_PASSWORD", "pw")
+        .args(["repair", "--yes", "--path"])
+        .arg(&path);
+    repair
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("label 'broken'"))
+        .stdout(predicate::str::contains("Recovered 2 entries"));
+
+    let entries = load_vault_file(&path, "pw").unwrap();
+    assert_eq!(entries.len(), 2);
+    assert!(td.path().join("vault.ron.1").exists());
+}
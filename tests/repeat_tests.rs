@@ -0,0 +1,88 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use secrecy::SecretString;
+use std::fs;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::session_management::last_get::{last_get_file_for, load_last_get, LastGet};
+use kevi::vault::handlers::GetField;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+
+fn seed(path: &std::path::Path) {
+    let entry = VaultEntry {
+        username: Some(SecretString::new("octo".into())),
+        ..VaultEntry::new("github", "s3cr3t-pw")
+    };
+    save_vault_file(&[entry], path, "pw").expect("seed vault");
+}
+
+#[test]
+fn get_records_parameters_and_repeat_replays_them() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    let mut get = Command::cargo_bin("kevi").unwrap();
+    get.env("KEVI_PASSWORD", "pw")
+        .args(["get", "git", "--field", "user", "--ttl", "5s", "--path"])
+        .arg(&path);
+    get.assert().success();
+
+    let state = last_get_file_for(&path);
+    let last = load_last_get(&state).unwrap().expect("last get recorded");
+    assert_eq!(
+        last,
+        LastGet {
+            label: "github".into(),
+            field: GetField::User,
+            ttl: Some(5),
+        }
+    );
+    let raw = fs::read_to_string(&state).unwrap();
+    assert!(!raw.contains("octo"));
+    assert!(!raw.contains("s3cr3t-pw"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&state).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
+
+    let mut repeat = Command::cargo_bin("kevi").unwrap();
+    repeat
+        .env("KEVI_PASSWORD", "pw")
+        .args(["repeat", "--path"])
+        .arg(&path);
+    repeat.assert().success().stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn repeat_without_prior_get_fails() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .args(["repeat", "--path"])
+        .arg(&path);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("nothing to repeat"));
+}
+
+#[test]
+fn no_copy_get_is_not_recorded() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .args(["get", "github", "--no-copy", "--echo", "--path"])
+        .arg(&path);
+    cmd.assert().success();
+    assert!(!last_get_file_for(&path).exists());
+}
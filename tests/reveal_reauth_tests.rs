@@ -0,0 +1,62 @@
+use assert_cmd::Command;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+use predicates::prelude::*;
+use secrecy::SecretString;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn kevi(path: &Path, password: Option<&str>, args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env_remove("KEVI_PASSWORD")
+        .env("KEVI_REQUIRE_REVEAL_REAUTH", "true")
+        .args(args)
+        .arg("--path")
+        .arg(path);
+    if let Some(pw) = password {
+        cmd.env("KEVI_PASSWORD", pw);
+    }
+    cmd
+}
+
+#[test]
+fn reveal_needs_the_master_password_even_when_unlocked() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let entry = VaultEntry {
+        username: Some(SecretString::new("admin".into())),
+        ..VaultEntry::new("db", "s3cr3t")
+    };
+    save_vault_file(&[entry], &path, "pw").unwrap();
+    kevi(&path, Some("pw"), &["unlock"]).assert().success();
+
+    // The session still serves masked views and echo/copy flows
+    kevi(&path, None, &["show", "db"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Password: ********"));
+    kevi(&path, None, &["get", "db", "--no-copy", "--echo"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("s3cr3t"));
+
+    // Revealing asks again and does not fall back to the session
+    for args in [
+        &["show", "db", "--reveal-password"][..],
+        &["get", "db", "--field", "all", "--reveal"][..],
+    ] {
+        kevi(&path, None, args)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("s3cr3t").not())
+            .stderr(predicate::str::contains("re-authentication required"));
+        kevi(&path, Some("wrong"), args)
+            .assert()
+            .failure()
+            .stdout(predicate::str::contains("s3cr3t").not());
+        kevi(&path, Some("pw"), args)
+            .assert()
+            .success()
+            .stdout(predicate::str::contains("s3cr3t"));
+    }
+}
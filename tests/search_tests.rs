@@ -0,0 +1,84 @@
+use assert_cmd::Command;
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::save_vault_file;
+use predicates::prelude::*;
+use secrecy::SecretString;
+use tempfile::tempdir;
+
+fn entry(label: &str, user: &str, notes: Option<&str>, custom: &[&str]) -> VaultEntry {
+    VaultEntry {
+        username: Some(SecretString::new(user.into())),
+        notes: notes.map(Into::into),
+        custom: custom
+            .iter()
+            .map(|k| (k.to_string(), SecretString::new("custom-secret".into())))
+            .collect(),
+        ..VaultEntry::new(label, "hunter2-secret")
+    }
+}
+
+fn seed(path: &std::path::Path) {
+    let entries = vec![
+        entry("mail-backup", "bob", None, &[]),
+        entry("bank", "mail@example.com", Some("uses mail 2fa"), &[]),
+        entry("mail", "alice", None, &[]),
+        entry("forum", "carol", None, &["mail_pin"]),
+        entry("shop", "dave", None, &[]),
+    ];
+    save_vault_file(&entries, path, "pw").unwrap();
+}
+
+fn kevi(path: &std::path::Path, args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .arg("search")
+        .args(args)
+        .arg("--path")
+        .arg(path);
+    cmd
+}
+
+#[test]
+fn search_ranks_matches_and_names_the_fields() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    let out = kevi(&path, &["MAIL"]).assert().success();
+    let stdout = String::from_utf8(out.get_output().stdout.clone()).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            "mail\tlabel",
+            "mail-backup\tlabel",
+            "bank\tuser, notes",
+            "forum\tcustom",
+        ]
+    );
+    assert!(!stdout.contains("secret"));
+    assert!(!stdout.contains("alice"));
+}
+
+#[test]
+fn search_in_restricts_fields_and_show_users_adds_usernames() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    seed(&path);
+
+    kevi(&path, &["mail", "--in", "notes,custom", "--show-users"])
+        .assert()
+        .success()
+        .stdout("forum\tcustom\tcarol\nbank\tnotes\tmail@example.com\n");
+
+    kevi(&path, &["zzz"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No entries match 'zzz'"));
+
+    // Custom values and passwords are never searched
+    kevi(&path, &["secret", "--json"])
+        .assert()
+        .success()
+        .stdout("[]\n");
+}
@@ -5,10 +5,9 @@ use secrecy::{ExposeSecret, SecretString};
 fn serde_round_trip_username_and_password() {
     // Build an entry with a secret username and password
     let entry = VaultEntry {
-        label: "label".to_string(),
         username: Some(SecretString::new("user123".into())),
-        password: SecretString::new("p@ssw0rd".into()),
         notes: Some("n".to_string()),
+        ..VaultEntry::new("label", "p@ssw0rd")
     };
 
     // Serialize to RON and deserialize back
@@ -0,0 +1,40 @@
+#![cfg(feature = "testing")]
+
+use assert_cmd::prelude::*;
+use secrecy::ExposeSecret;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::vault::persistence::load_vault_file;
+
+fn add_generated(path: &Path, seed: &str) -> String {
+    let pw = "pw";
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", pw)
+        .env("KEVI_TEST_SEED", seed)
+        .arg("add")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string())
+        .arg("--generate")
+        .arg("--label")
+        .arg("seeded")
+        .arg("--user")
+        .arg("")
+        .arg("--notes")
+        .arg("");
+    cmd.assert().success();
+
+    let entries = load_vault_file(path, pw).expect("load vault");
+    entries[0].password.expose_secret().to_string()
+}
+
+#[test]
+fn cli_add_generate_is_deterministic_with_test_seed() {
+    let td = tempdir().unwrap();
+    let a = add_generated(&td.path().join("a.ron"), "7");
+    let b = add_generated(&td.path().join("b.ron"), "7");
+    let c = add_generated(&td.path().join("c.ron"), "8");
+    assert_eq!(a, b, "same seed must generate the same password");
+    assert_ne!(a, c, "different seeds should diverge");
+}
@@ -1,7 +1,8 @@
-use kevi::filesystem::store::FileByteStore;
-use kevi::session_management::resolver::CachedKeyResolver;
+use kevi::filesystem::store::{FileByteStore, InMemoryByteStore};
+use kevi::session_management::resolver::{CachedKeyResolver, EphemeralKeyResolver};
 use kevi::vault::codec::RonCodec;
-use kevi::vault::models::VaultEntry;
+use kevi::vault::models::{VaultDocument, VaultEntry, SCHEMA_VERSION};
+use kevi::vault::ports::{ByteStore, VaultCodec};
 use kevi::vault::service::VaultService;
 use secrecy::SecretString;
 use std::env;
@@ -25,10 +26,8 @@ fn service_add_and_load_round_trip() {
 
     // Add entry
     let entry = VaultEntry {
-        label: "svc_label".to_string(),
         username: Some(SecretString::new("u".into())),
-        password: SecretString::new("pw".into()),
-        notes: None,
+        ..VaultEntry::new("svc_label", "pw")
     };
     service.add_entry(entry).expect("add ok");
 
@@ -36,6 +35,8 @@ fn service_add_and_load_round_trip() {
     let loaded = service.load().expect("reload ok");
     assert_eq!(loaded.len(), 1);
     assert_eq!(loaded[0].label, "svc_label");
+    assert!(loaded[0].created_at.is_some());
+    assert_eq!(loaded[0].created_at, loaded[0].updated_at);
 
     // File should be encrypted with KEVI header
     let bytes = std::fs::read(&path).unwrap();
@@ -44,37 +45,325 @@ fn service_add_and_load_round_trip() {
 
 #[test]
 fn service_remove_entry() {
+    let store = Arc::new(InMemoryByteStore::new());
+    let codec = Arc::new(RonCodec);
+    let resolver = Arc::new(EphemeralKeyResolver::new().unwrap());
+    let service = VaultService::new(store, codec, resolver);
+
+    // Add two entries
+    service.add_entry(VaultEntry::new("a", "1")).unwrap();
+    service.add_entry(VaultEntry::new("b", "2")).unwrap();
+
+    // Remove one
+    let removed = service.remove_entry("a").unwrap();
+    assert!(removed);
+    let after = service.load().unwrap();
+    assert_eq!(after.len(), 1);
+    assert_eq!(after[0].label, "b");
+}
+
+#[test]
+fn service_remove_moves_entry_to_trash_and_restores() {
     let dir = tempdir().unwrap();
     let path = dir.path().join("vault.ron");
     env::set_var("KEVI_PASSWORD", "svcpass");
 
-    let store = Arc::new(FileByteStore::new(path));
+    let store = Arc::new(FileByteStore::new(path.clone()));
     let codec = Arc::new(RonCodec);
-    let resolver = Arc::new(CachedKeyResolver::new(dir.path().join("vault.ron")));
+    let resolver = Arc::new(CachedKeyResolver::new(path));
     let service = VaultService::new(store, codec, resolver);
 
-    // Add two entries
+    service.add_entry(VaultEntry::new("a", "1")).unwrap();
+
+    assert!(service.remove_entry("a").unwrap());
+    let doc = service.load_document().unwrap();
+    assert!(doc.entries.is_empty());
+    assert_eq!(doc.trash.len(), 1);
+    assert_eq!(doc.trash[0].entry.label, "a");
+
+    // Saving live entries keeps the trash intact
+    service.save(&[]).unwrap();
+    assert_eq!(service.load_document().unwrap().trash.len(), 1);
+
+    assert!(service.restore_entry("a").unwrap());
+    let doc = service.load_document().unwrap();
+    assert_eq!(doc.entries.len(), 1);
+    assert!(doc.trash.is_empty());
+    assert!(!service.restore_entry("a").unwrap());
+}
+
+#[test]
+fn service_trash_retention_zero_deletes_permanently() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    env::set_var("KEVI_PASSWORD", "svcpass");
+
+    let store = Arc::new(FileByteStore::new(path.clone()));
+    let codec = Arc::new(RonCodec);
+    let resolver = Arc::new(CachedKeyResolver::new(path));
+    let service = VaultService::new(store, codec, resolver).with_trash_retention_days(0);
+
+    service.add_entry(VaultEntry::new("a", "1")).unwrap();
+    assert!(service.remove_entry("a").unwrap());
+    let doc = service.load_document().unwrap();
+    assert!(doc.entries.is_empty());
+    assert!(doc.trash.is_empty());
+}
+
+#[test]
+fn service_add_entry_rejects_control_characters_in_label() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    env::set_var("KEVI_PASSWORD", "svcpass");
+
+    let store = Arc::new(FileByteStore::new(path.clone()));
+    let codec = Arc::new(RonCodec);
+    let resolver = Arc::new(CachedKeyResolver::new(path));
+    let service = VaultService::new(store, codec, resolver);
+
+    let err = service.add_entry(VaultEntry::new("a\tb", "1")).unwrap_err();
+    assert!(err.to_string().contains("control characters"));
+    assert!(service.load().unwrap().is_empty());
+}
+
+fn entry(label: &str) -> VaultEntry {
+    VaultEntry::new(label, "pw")
+}
+
+#[test]
+fn service_batch_error_leaves_vault_unchanged() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    env::set_var("KEVI_PASSWORD", "svcpass");
+
+    let store = Arc::new(FileByteStore::new(path.clone()));
+    let codec = Arc::new(RonCodec);
+    let resolver = Arc::new(CachedKeyResolver::new(path.clone()));
+    let service = VaultService::new(store, codec, resolver);
+
+    service.add_entry(entry("a")).unwrap();
+    let before = std::fs::read(&path).unwrap();
+
+    let err = service
+        .batch(|entries| {
+            entries.clear();
+            entries.push(entry("b"));
+            anyhow::bail!("abort")
+        })
+        .unwrap_err();
+    assert_eq!(err.to_string(), "abort");
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+
+    // Validation failures abort the batch too
+    let err = service
+        .batch(|entries| {
+            entries.push(entry("c"));
+            entries.push(entry("c"));
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("duplicate label 'c'"));
+    assert_eq!(std::fs::read(&path).unwrap(), before);
+
     service
-        .add_entry(VaultEntry {
-            label: "a".into(),
-            username: None,
-            password: SecretString::new("1".into()),
-            notes: None,
+        .batch(|entries| {
+            entries.retain(|e| e.label != "a");
+            entries.push(entry("b"));
+            entries.push(entry("c"));
+            Ok(())
         })
         .unwrap();
+    let labels: Vec<_> = service
+        .load()
+        .unwrap()
+        .into_iter()
+        .map(|e| e.label)
+        .collect();
+    assert_eq!(labels, ["b", "c"]);
+}
+
+#[test]
+fn in_memory_service_round_trips_without_touching_disk() {
+    let store = Arc::new(InMemoryByteStore::new());
+    let codec = Arc::new(RonCodec);
+    let resolver = Arc::new(EphemeralKeyResolver::new().unwrap());
+    let service = VaultService::new(store.clone(), codec, resolver);
+
+    assert!(service.load().unwrap().is_empty());
+    service
+        .add_entry(VaultEntry::new("in-memory-label", "pw"))
+        .unwrap();
+
+    let loaded = service.load().unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].label, "in-memory-label");
+    // The store holds the sealed vault, not plaintext
+    let bytes = store.snapshot();
+    assert!(bytes.starts_with(b"KEVI"));
+    assert!(!bytes.windows(15).any(|w| w == b"in-memory-label"));
+}
+
+#[test]
+fn stream_encryption_writes_chunked_vaults_that_either_mode_reads() {
+    use kevi::cryptography::primitives::{
+        parse_kevi_header, HEADER_VERSION, HEADER_VERSION_STREAM,
+    };
+    let store = Arc::new(InMemoryByteStore::new());
+    let resolver = Arc::new(EphemeralKeyResolver::new().unwrap());
+    let chunked = VaultService::new(store.clone(), Arc::new(RonCodec), resolver.clone())
+        .with_stream_encryption(true);
+    let mut big = entry("big");
+    big.notes = Some("n".repeat(200 * 1024));
+    chunked.add_entry(big).unwrap();
+    chunked.add_entry(entry("small")).unwrap();
+    let version = |bytes: Vec<u8>| parse_kevi_header(&bytes).unwrap().0.version;
+    assert_eq!(version(store.snapshot()), HEADER_VERSION_STREAM);
+
+    // A service without the option reads it and saves the single-shot format
+    let plain = VaultService::new(store.clone(), Arc::new(RonCodec), resolver);
+    assert_eq!(plain.load().unwrap().len(), 2);
+    plain.remove_entry("small").unwrap();
+    assert_eq!(version(store.snapshot()), HEADER_VERSION);
+    let loaded = chunked.load().unwrap();
+    assert_eq!(loaded[0].notes.as_ref().unwrap().len(), 200 * 1024);
+}
+
+/// Counts header resolutions, so tests can check no key is derived twice.
+struct CountingResolver {
+    inner: EphemeralKeyResolver,
+    calls: std::sync::atomic::AtomicUsize,
+}
+
+impl kevi::vault::ports::KeyResolver for CountingResolver {
+    fn resolve_for_header(
+        &self,
+        hdr: &kevi::cryptography::primitives::KeviHeader,
+    ) -> anyhow::Result<kevi::vault::ports::DerivedKey> {
+        self.calls
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.inner.resolve_for_header(hdr)
+    }
+
+    fn resolve_for_new_vault(
+        &self,
+        params: kevi::vault::ports::HeaderParams,
+        salt: [u8; 16],
+    ) -> anyhow::Result<kevi::vault::ports::DerivedKey> {
+        self.inner.resolve_for_new_vault(params, salt)
+    }
+}
+
+#[test]
+fn damaged_vault_falls_back_to_a_backup_under_the_same_key_only() {
+    use kevi::vault::ports::{ByteStore, KeyResolver};
+    let resolver = Arc::new(CountingResolver {
+        inner: EphemeralKeyResolver::new().unwrap(),
+        calls: Default::default(),
+    });
+    let store = Arc::new(InMemoryByteStore::new());
+    let writer = VaultService::new(store.clone(), Arc::new(RonCodec), resolver.clone());
+    writer.add_entry(entry("old")).unwrap();
+    let intact = store.snapshot();
+    writer.add_entry(entry("new")).unwrap();
+    let mut damaged = store.snapshot();
+    *damaged.last_mut().unwrap() ^= 0xff;
+
+    // Same key but another salt: it would decrypt, yet it is not this vault's backup
+    let other = Arc::new(InMemoryByteStore::new());
+    VaultService::new(other.clone(), Arc::new(RonCodec), resolver.clone())
+        .add_entry(entry("other"))
+        .unwrap();
+
+    let backups: Vec<(String, Arc<dyn ByteStore>)> = vec![
+        ("other".into(), other),
+        (
+            "intact".into(),
+            Arc::new(InMemoryByteStore::with_bytes(intact)),
+        ),
+    ];
+    let reader = VaultService::new(
+        Arc::new(InMemoryByteStore::with_bytes(damaged)),
+        Arc::new(RonCodec),
+        resolver.clone() as Arc<dyn KeyResolver>,
+    )
+    .with_backups(backups, true);
+    resolver
+        .calls
+        .store(0, std::sync::atomic::Ordering::Relaxed);
+    let loaded = reader.load().unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].label, "old");
+    assert_eq!(
+        resolver.calls.load(std::sync::atomic::Ordering::Relaxed),
+        1,
+        "the vault's key is reused for its backups"
+    );
+}
+
+#[test]
+fn vault_from_a_newer_schema_is_read_only() {
+    let store = Arc::new(InMemoryByteStore::new());
+    let resolver = Arc::new(EphemeralKeyResolver::new().unwrap());
+    let service = VaultService::new(store.clone(), Arc::new(RonCodec), resolver);
+    service.add_entry(entry("a")).unwrap();
+
+    // Re-seal the vault as if a future kevi had written it
+    let doc = VaultDocument {
+        entries: service.load().unwrap(),
+        ..Default::default()
+    };
+    let plain = String::from_utf8(RonCodec.encode(&doc).unwrap()).unwrap();
+    let newer = plain.replace(
+        &format!("schema_version: {SCHEMA_VERSION}"),
+        "schema_version: 99",
+    );
+    assert_ne!(newer, plain);
+    store
+        .write(&service.seal_with_vault_key(newer.as_bytes()).unwrap())
+        .unwrap();
+    let sealed = store.snapshot();
+
+    // Saving without loading first still checks the stored schema
+    let err = service.save(&[entry("b")]).unwrap_err();
+    assert!(err.to_string().contains("newer kevi"), "{err}");
+
+    assert_eq!(service.load().unwrap().len(), 1);
+    let err = service.add_entry(entry("b")).unwrap_err();
+    assert!(err.to_string().contains("newer kevi"), "{err}");
+    assert_eq!(store.snapshot(), sealed);
+}
+
+#[test]
+fn service_batch_validates_only_the_labels_it_adds() {
+    let store = Arc::new(InMemoryByteStore::new());
+    let resolver = Arc::new(EphemeralKeyResolver::new().unwrap());
+    let service = VaultService::new(store, Arc::new(RonCodec), resolver);
+    // Written by an older version without label checks
     service
-        .add_entry(VaultEntry {
-            label: "b".into(),
-            username: None,
-            password: SecretString::new("2".into()),
-            notes: None,
+        .save(&[entry("old\tlabel"), entry("dup"), entry("dup")])
+        .unwrap();
+
+    service
+        .batch(|entries| {
+            entries.push(entry("new"));
+            Ok(())
         })
         .unwrap();
+    assert_eq!(service.load().unwrap().len(), 4);
 
-    // Remove one
-    let removed = service.remove_entry("a").unwrap();
-    assert!(removed);
-    let after = service.load().unwrap();
-    assert_eq!(after.len(), 1);
-    assert_eq!(after[0].label, "b");
+    let err = service
+        .batch(|entries| {
+            entries.push(entry("new"));
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("duplicate label 'new'"), "{err}");
+    let err = service
+        .batch(|entries| {
+            entries[3].label = "dup".into();
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(err.to_string().contains("duplicate label 'dup'"), "{err}");
+    assert_eq!(service.load().unwrap().len(), 4);
 }
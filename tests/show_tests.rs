@@ -11,10 +11,9 @@ fn show_command_prints_details_masked_by_default() {
     let pw = "pw";
 
     let entries = vec![VaultEntry {
-        label: "mysite".into(),
         username: Some(SecretString::new("alice".into())),
-        password: SecretString::new("secret123".into()),
         notes: Some("noteZ".into()),
+        ..VaultEntry::new("mysite", "secret123")
     }];
     save_vault_file(&entries, &path, pw).unwrap();
 
@@ -42,10 +41,9 @@ fn show_command_reveals_password_with_flag() {
     let pw = "pw";
 
     let entries = vec![VaultEntry {
-        label: "mysite".into(),
         username: Some(SecretString::new("alice".into())),
-        password: SecretString::new("secret123".into()),
         notes: Some("noteZ".into()),
+        ..VaultEntry::new("mysite", "secret123")
     }];
     save_vault_file(&entries, &path, pw).unwrap();
 
@@ -65,3 +63,66 @@ fn show_command_reveals_password_with_flag() {
     assert!(out.contains("Notes:    noteZ"));
     assert!(out.contains("Password: secret123"));
 }
+
+#[test]
+fn show_command_copy_password_keeps_password_masked() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let pw = "pw";
+
+    let entries = vec![VaultEntry {
+        username: Some(SecretString::new("alice".into())),
+        ..VaultEntry::new("mysite", "secret123")
+    }];
+    save_vault_file(&entries, &path, pw).unwrap();
+
+    // Clipboard may be unavailable in CI; the command must still succeed without leaking.
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", pw)
+        .arg("show")
+        .arg("mysite")
+        .arg("--copy-password")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string());
+
+    let assert = cmd.assert().success();
+    let out = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+
+    assert!(out.contains("Label:    mysite"));
+    assert!(out.contains("Username: alice"));
+    assert!(out.contains("Password: ********"));
+    assert!(!out.contains("secret123"));
+}
+
+#[test]
+fn show_json_includes_username_and_masks_password_unless_revealed() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let entries = vec![VaultEntry {
+        username: Some(SecretString::new("alice".into())),
+        ..VaultEntry::new("mysite", "secret123")
+    }];
+    save_vault_file(&entries, &path, "pw").unwrap();
+
+    let show = |extra: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", "pw")
+            .args(["show", "mysite", "--json", "--path"])
+            .arg(&path)
+            .args(extra);
+        let out = cmd.assert().success().get_output().stdout.clone();
+        serde_json::from_slice::<serde_json::Value>(&out).expect("stdout is JSON")
+    };
+
+    let masked = show(&[]);
+    assert_eq!(masked["label"], "mysite");
+    assert_eq!(masked["username"], "alice");
+    assert!(masked.get("password").is_none());
+
+    let revealed = show(&["--reveal-password"]);
+    assert_eq!(revealed["password"], "secret123");
+
+    // Copy status goes to stderr so stdout stays parseable
+    let copied = show(&["--copy-user"]);
+    assert_eq!(copied["username"], "alice");
+}
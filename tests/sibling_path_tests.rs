@@ -0,0 +1,107 @@
+use kevi::filesystem::secure::{
+    backup_path, sibling_path, write_with_backups_n, write_with_backups_named, BackupNaming,
+};
+use kevi::filesystem::store::FileByteStore;
+use kevi::session_management::last_get::last_get_file_for;
+use kevi::session_management::resolver::dk_session_file_for;
+use kevi::vault::ports::ByteStore;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::tempdir;
+
+#[test]
+fn sidecar_paths_append_to_the_full_file_name() {
+    for (vault, stem) in [("secrets", "secrets"), ("vault.prod.ron", "vault.prod.ron")] {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join(vault);
+        let sib = |s: &str| dir.path().join(format!("{stem}.{s}"));
+        assert_eq!(
+            dk_session_file_for(&path),
+            dir.path()
+                .canonicalize()
+                .unwrap()
+                .join(format!("{stem}.dksession"))
+        );
+        assert_eq!(last_get_file_for(&path), sib("lastget"));
+        assert_eq!(backup_path(&path, 1), sib("1"));
+        assert_eq!(sibling_path(&path, "tmp"), sib("tmp"));
+        for p in [dk_session_file_for(&path), backup_path(&path, 2)] {
+            assert_ne!(p, path);
+            assert_eq!(p.parent(), path.parent());
+        }
+    }
+}
+
+#[test]
+fn backups_of_extensionless_and_multi_dot_vaults_are_siblings() {
+    let dir = tempdir().unwrap();
+    for name in ["secrets", "vault.prod.ron"] {
+        let path = dir.path().join(name);
+        write_with_backups_n(&path, b"one", 2).unwrap();
+        write_with_backups_n(&path, b"two", 2).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"two");
+        assert_eq!(fs::read(backup_path(&path, 1)).unwrap(), b"one");
+    }
+    // Neither vault clobbered the other's files
+    let mut names: Vec<PathBuf> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| PathBuf::from(e.unwrap().file_name()))
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        ["secrets", "secrets.1", "vault.prod.ron", "vault.prod.ron.1"].map(PathBuf::from)
+    );
+}
+
+#[test]
+fn backup_naming_supports_a_suffix_and_hidden_files() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    let suffixed = BackupNaming {
+        hidden: false,
+        suffix: Some("bak".into()),
+    };
+    assert_eq!(suffixed.path(&path, 1), dir.path().join("vault.ron.bak.1"));
+    // Surrounding dots are tolerated and an empty suffix means the default
+    let dotted = BackupNaming {
+        hidden: false,
+        suffix: Some(".bak.".into()),
+    };
+    assert_eq!(dotted.path(&path, 2), dir.path().join("vault.ron.bak.2"));
+    let empty = BackupNaming {
+        hidden: false,
+        suffix: Some(String::new()),
+    };
+    assert_eq!(empty.path(&path, 1), backup_path(&path, 1));
+
+    write_with_backups_named(&path, b"one", 2, &suffixed).unwrap();
+    write_with_backups_named(&path, b"two", 2, &suffixed).unwrap();
+    write_with_backups_named(&path, b"three", 2, &suffixed).unwrap();
+    assert_eq!(fs::read(suffixed.path(&path, 1)).unwrap(), b"two");
+    assert_eq!(fs::read(suffixed.path(&path, 2)).unwrap(), b"one");
+    assert!(!backup_path(&path, 1).exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn hidden_backups_are_dot_prefixed_siblings() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    let hidden = BackupNaming {
+        hidden: true,
+        suffix: None,
+    };
+    assert_eq!(hidden.path(&path, 1), dir.path().join(".vault.ron.1"));
+
+    let store = FileByteStore::new_with_backups(path.clone(), 1).with_backup_naming(hidden);
+    store.write(b"one").unwrap();
+    store.write(b"two").unwrap();
+    let mut names: Vec<PathBuf> = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| PathBuf::from(e.unwrap().file_name()))
+        .collect();
+    names.sort();
+    assert_eq!(names, [".vault.ron.1", "vault.ron"].map(PathBuf::from));
+    assert_eq!(fs::read(dir.path().join(".vault.ron.1")).unwrap(), b"one");
+}
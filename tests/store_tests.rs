@@ -10,10 +10,8 @@ fn test_add_and_get_entry() {
     let pw = "testpw";
 
     let entry = VaultEntry {
-        label: "testsite".into(),
         username: Some(SecretString::new("tester".into())),
-        password: SecretString::new("1234".into()),
-        notes: None,
+        ..VaultEntry::new("testsite", "1234")
     };
 
     let vault = vec![entry.clone()];
@@ -35,20 +33,7 @@ fn test_remove_entry() {
     let _path = dir.path().join("vault.ron");
     let pw = "testpw";
 
-    let mut vault = vec![
-        VaultEntry {
-            label: "one".into(),
-            username: None,
-            password: SecretString::new("p1".into()),
-            notes: None,
-        },
-        VaultEntry {
-            label: "two".into(),
-            username: None,
-            password: SecretString::new("p2".into()),
-            notes: None,
-        },
-    ];
+    let mut vault = vec![VaultEntry::new("one", "p1"), VaultEntry::new("two", "p2")];
     save_vault_file(&vault, &_path, pw).unwrap();
 
     vault.retain(|e| e.label != "one");
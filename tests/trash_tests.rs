@@ -0,0 +1,89 @@
+use assert_cmd::prelude::*;
+use predicates::prelude::*;
+use std::path::Path;
+use std::process::Command;
+use tempfile::tempdir;
+
+use kevi::vault::models::VaultEntry;
+use kevi::vault::persistence::{load_vault_file, save_vault_file};
+
+fn kevi(path: &Path, args: &[&str]) -> Command {
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .args(args)
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string());
+    cmd
+}
+
+#[test]
+fn rm_trash_restore_and_empty_round_trip() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let entries = vec![VaultEntry::new("alpha", "aaa")];
+    save_vault_file(&entries, &path, "pw").expect("seed vault");
+
+    kevi(&path, &["rm", "alpha", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("moved to trash"));
+    assert!(load_vault_file(&path, "pw").unwrap().is_empty());
+
+    kevi(&path, &["trash", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("alpha\tdeleted"));
+
+    kevi(&path, &["trash", "restore", "alpha"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("restored"));
+    let restored = load_vault_file(&path, "pw").unwrap();
+    assert_eq!(restored.len(), 1);
+    assert_eq!(restored[0].label, "alpha");
+
+    kevi(&path, &["rm", "alpha", "--yes"]).assert().success();
+    kevi(&path, &["trash", "empty", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Permanently deleted 1 trashed entry",
+        ));
+    kevi(&path, &["trash", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(trash is empty)"));
+}
+
+#[test]
+fn rm_several_keys_in_one_save_and_reports_missing() {
+    let td = tempdir().unwrap();
+    let path = td.path().join("vault.ron");
+    let entry = |label: &str| VaultEntry::new(label, "x");
+    let entries = vec![entry("gh"), entry("gl"), entry("email"), entry("keep")];
+    save_vault_file(&entries, &path, "pw").expect("seed vault");
+
+    kevi(
+        &path,
+        &["rm", "gh", "gl", "nope", "email", "--yes", "--exact"],
+    )
+    .assert()
+    .code(2)
+    .stdout(predicate::str::contains("Entry 'gh' moved to trash"))
+    .stdout(predicate::str::contains("Entry 'gl' moved to trash"))
+    .stdout(predicate::str::contains("Entry 'email' moved to trash"))
+    .stderr(predicate::str::contains("no entry found with key 'nope'"));
+    let left = load_vault_file(&path, "pw").unwrap();
+    assert_eq!(left.len(), 1);
+    assert_eq!(left[0].label, "keep");
+
+    // A single write rotated the vault once: only one backup exists
+    assert!(path.with_file_name("vault.ron.1").exists());
+    assert!(!path.with_file_name("vault.ron.2").exists());
+
+    kevi(&path, &["trash", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("gh\tdeleted"))
+        .stdout(predicate::str::contains("email\tdeleted"));
+}
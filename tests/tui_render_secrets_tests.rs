@@ -10,10 +10,9 @@ use secrecy::SecretString;
 
 fn make(label: &str, user: Option<&str>, pw: &str, notes: Option<&str>) -> VaultEntry {
     VaultEntry {
-        label: label.into(),
         username: user.map(|u| SecretString::new(u.into())),
-        password: SecretString::new(pw.to_string().into()),
         notes: notes.map(|n| n.into()),
+        ..VaultEntry::new(label, pw)
     }
 }
 
@@ -98,3 +97,25 @@ fn confirm_delete_overlay_shows_label_only() {
     assert!(!all.contains("secret123"));
     assert!(!all.contains("noteZ"));
 }
+
+#[test]
+fn details_view_renders_markdown_notes_when_enabled() {
+    let notes = "## Recovery\n- **pin** 1234";
+    let mut app = App::new(vec![make("alpha", None, "pw", Some(notes))]);
+    app.enter_details();
+
+    let backend = TestBackend::new(60, 12);
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal.draw(|f| render_details(f, &app)).unwrap();
+    let plain = buffer_to_string(&terminal);
+    assert!(plain.contains("## Recovery"));
+    assert!(plain.contains("- **pin** 1234"));
+
+    app.render_markdown = true;
+    terminal.draw(|f| render_details(f, &app)).unwrap();
+    let rendered = buffer_to_string(&terminal);
+    assert!(rendered.contains("Recovery"));
+    assert!(!rendered.contains("##"));
+    assert!(rendered.contains("• pin 1234"));
+    assert!(!rendered.contains("**pin**"));
+}
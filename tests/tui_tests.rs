@@ -4,15 +4,9 @@ use ratatui::Terminal;
 use kevi::tui::app::App;
 use kevi::tui::views::list::render_list;
 use kevi::vault::models::VaultEntry;
-use secrecy::SecretString;
 
 fn make(label: &str, pw: &str) -> VaultEntry {
-    VaultEntry {
-        label: label.into(),
-        username: None,
-        password: SecretString::new(pw.to_string().into()),
-        notes: None,
-    }
+    VaultEntry::new(label, pw)
 }
 
 #[test]
@@ -44,3 +38,72 @@ fn tui_renders_labels_and_never_secrets() {
     assert!(!all.contains("secret123"));
     assert!(!all.contains("topsecret"));
 }
+
+#[test]
+fn theme_resolves_known_names_and_rejects_unknown() {
+    use kevi::tui::theme::Theme;
+    use ratatui::style::Color;
+
+    std::env::remove_var("NO_COLOR");
+    assert_eq!(Theme::resolve(None).unwrap().primary, Theme::dark().primary);
+    assert_eq!(Theme::resolve(Some("light")).unwrap().fg, Color::Black);
+    assert_eq!(
+        Theme::resolve(Some("high-contrast")).unwrap().primary,
+        Color::Yellow
+    );
+    let err = Theme::resolve(Some("neon")).unwrap_err();
+    assert!(format!("{err}").contains("unknown theme"));
+}
+
+#[test]
+fn palette_overlay_renders_input_and_help() {
+    use kevi::tui::views::palette::render_palette;
+
+    let mut app = App::new(vec![make("alpha", "secret123")]);
+    app.open_palette();
+    for c in "theme light".chars() {
+        app.push_palette(c);
+    }
+
+    let backend = TestBackend::new(120, 10);
+    let mut terminal = Terminal::new(backend).unwrap();
+    terminal
+        .draw(|f| {
+            render_list(f, &app);
+            render_palette(f, &app);
+        })
+        .unwrap();
+
+    let buf = terminal.backend().buffer().clone();
+    let mut all = String::new();
+    for y in 0..buf.area.height {
+        for x in 0..buf.area.width {
+            all.push_str(buf.cell((x, y)).unwrap().symbol());
+        }
+        all.push('\n');
+    }
+    assert!(all.contains(":theme light"));
+    assert!(all.contains("copy password|user|notes"));
+    assert!(!all.contains("secret123"));
+}
+
+#[test]
+fn share_confirmation_warns_about_plaintext_without_showing_it() {
+    use kevi::tui::app::View;
+    use kevi::tui::views::confirm::render_confirm_share;
+
+    let mut app = App::new(vec![make("svc-account", "hunter2")]);
+    app.enter_details();
+    app.enter_confirm_share();
+    assert_eq!(app.view, View::ConfirmShare);
+
+    let mut terminal = Terminal::new(TestBackend::new(100, 3)).unwrap();
+    terminal.draw(|f| render_confirm_share(f, &app)).unwrap();
+    let buf = terminal.backend().buffer().clone();
+    let text: String = buf.content().iter().map(|c| c.symbol()).collect();
+    assert!(text.contains("Copy 'svc-account' as PLAINTEXT JSON"));
+    assert!(!text.contains("hunter2"));
+
+    app.cancel_confirm_share();
+    assert_eq!(app.view, View::Details);
+}
@@ -1,7 +1,7 @@
 use kevi::config::app_config::Config;
 use kevi::filesystem::store::FileByteStore;
 use kevi::session_management::resolver::{
-    dk_session_file_for, CachedKeyResolver, DerivedKeyStored,
+    dk_session_file_for, save_derived_key_session, CachedKeyResolver, DerivedKeyStored,
 };
 use kevi::session_management::session::load;
 use kevi::vault::codec::RonCodec;
@@ -27,10 +27,8 @@ fn service_uses_cached_derived_key_session() {
 
     // Save an entry (will derive and cache derived key)
     let entry = VaultEntry {
-        label: "cached".into(),
         username: Some(SecretString::new("u".into())),
-        password: SecretString::new("pw!".into()),
-        notes: None,
+        ..VaultEntry::new("cached", "pw!")
     };
     service.save(&[entry]).expect("save using cache");
 
@@ -59,7 +57,10 @@ async fn vault_handle_unlock_and_lock_manage_session() {
 
     // Provide password via env to avoid prompt
     env::set_var("KEVI_PASSWORD", "pw");
-    vault.handle_unlock(Some(30)).await.expect("unlock ok");
+    vault
+        .handle_unlock(Some(30), false, false, false)
+        .await
+        .expect("unlock ok");
     let dk_path = dk_session_file_for(&path);
     assert!(
         dk_path.exists(),
@@ -76,3 +77,310 @@ async fn vault_handle_unlock_and_lock_manage_session() {
         "dk session file should be removed after lock"
     );
 }
+
+#[test]
+fn unlock_cli_accepts_human_duration_ttl() {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    use std::process::Command;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    kevi::vault::persistence::save_vault_file(&[], &path, "pw").expect("init empty vault");
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "pw")
+        .arg("unlock")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string())
+        .arg("--ttl")
+        .arg("1m30s");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Unlocked for 90s"))
+        .stdout(predicate::str::is_match(r"bound to header [0-9a-f]{8}\)").unwrap());
+
+    let mut bad = Command::cargo_bin("kevi").unwrap();
+    bad.arg("unlock")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string())
+        .arg("--ttl")
+        .arg("5x");
+    bad.assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid duration unit"));
+}
+
+#[test]
+fn unlock_with_wrong_password_writes_no_session() {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    use std::process::Command;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    kevi::vault::persistence::save_vault_file(&[], &path, "pw").expect("init empty vault");
+
+    let mut cmd = Command::cargo_bin("kevi").unwrap();
+    cmd.env("KEVI_PASSWORD", "typo")
+        .arg("unlock")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string());
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("wrong password, not unlocked"));
+    assert!(
+        !dk_session_file_for(&path).exists(),
+        "no dk session may be cached for a wrong password"
+    );
+}
+
+#[test]
+fn dk_session_path_is_canonical_and_distinct_from_backups() {
+    use kevi::filesystem::secure::backup_path;
+
+    let dir = tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("sub")).unwrap();
+    let path = dir.path().join("vault.ron");
+    let dotted = dir.path().join("sub").join("..").join("vault.ron");
+    assert_eq!(dk_session_file_for(&dotted), dk_session_file_for(&path));
+
+    let main = dk_session_file_for(&path);
+    for n in 1..=3 {
+        assert_ne!(dk_session_file_for(&backup_path(&path, n)), main);
+    }
+}
+
+#[test]
+fn restored_backup_with_other_header_does_not_reuse_session() {
+    use assert_cmd::prelude::*;
+    use kevi::filesystem::secure::backup_path;
+    use std::process::Command;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    kevi::vault::persistence::save_vault_file(&[], &path, "new").expect("init vault");
+    // A backup from an earlier vault generation: different password and salt
+    let backup = backup_path(&path, 1);
+    kevi::vault::persistence::save_vault_file(&[], &backup, "old").expect("init backup");
+
+    let kevi = |pw: Option<&str>, cmd: &str, vault: &std::path::Path| {
+        let mut c = Command::cargo_bin("kevi").unwrap();
+        c.env_remove("KEVI_PASSWORD");
+        if let Some(pw) = pw {
+            c.env("KEVI_PASSWORD", pw);
+        }
+        c.arg(cmd).arg("--path").arg(vault);
+        c.assert()
+    };
+
+    kevi(Some("new"), "unlock", &path).success();
+    assert!(dk_session_file_for(&path).exists());
+    // The backup never sees the main vault's session
+    kevi(None, "list", &backup).failure();
+
+    std::fs::copy(&backup, &path).unwrap();
+    kevi(None, "list", &path).failure();
+    kevi(Some("old"), "list", &path).success();
+}
+
+#[test]
+fn short_cached_key_is_a_cache_miss_and_rederives() {
+    use assert_cmd::prelude::*;
+    use std::process::Command;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    kevi::vault::persistence::save_vault_file(&[], &path, "pw").expect("init vault");
+    let sess = dk_session_file_for(&path);
+
+    let kevi = |pw: Option<&str>| {
+        let mut c = Command::cargo_bin("kevi").unwrap();
+        c.env_remove("KEVI_PASSWORD");
+        if let Some(pw) = pw {
+            c.env("KEVI_PASSWORD", pw);
+        }
+        c.arg("list").arg("--path").arg(&path);
+        c.assert()
+    };
+    let truncate_session = || {
+        let stored: DerivedKeyStored = load(&sess).unwrap().expect("session present");
+        let short = secrecy::SecretBox::new(Box::new(vec![1u8; 7]));
+        save_derived_key_session(
+            &sess,
+            &stored.header_fingerprint_hex,
+            &short,
+            std::time::Duration::from_secs(60),
+        )
+        .unwrap();
+    };
+
+    kevi(Some("pw")).success();
+    truncate_session();
+    // Without a password the short key must not be used (or panic); the session goes away
+    kevi(None).failure();
+    assert!(!sess.exists(), "corrupt session should be removed");
+
+    kevi(Some("pw")).success();
+    truncate_session();
+    // With a password the key is derived again and a full-length session written back
+    kevi(Some("pw")).success();
+    let stored: DerivedKeyStored = load(&sess).unwrap().expect("session rewritten");
+    assert!(stored.key_b64.len() > 12);
+    kevi(None).success();
+}
+
+#[test]
+fn sliding_session_renews_on_use_up_to_the_cap() {
+    use assert_cmd::prelude::*;
+    use std::process::Command;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    kevi::vault::persistence::save_vault_file(&[], &path, "pw").expect("init vault");
+    let sess = dk_session_file_for(&path);
+
+    let kevi = |envs: &[(&str, &str)]| {
+        let mut c = Command::cargo_bin("kevi").unwrap();
+        c.env_remove("KEVI_PASSWORD")
+            .env("KEVI_UNLOCK_TTL", "60")
+            .envs(envs.iter().copied())
+            .arg("list")
+            .arg("--path")
+            .arg(&path);
+        c.assert().success();
+    };
+    let expires_at = || {
+        let text = std::fs::read_to_string(&sess).unwrap();
+        let rest = &text[text.find("expires_at_unix:").unwrap() + 16..];
+        rest[..rest.find(',').unwrap()]
+            .trim()
+            .parse::<u64>()
+            .unwrap()
+    };
+
+    kevi(&[("KEVI_PASSWORD", "pw")]);
+    let first = expires_at();
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+
+    // Fixed expiry by default: a cache hit leaves the session alone
+    kevi(&[]);
+    assert_eq!(expires_at(), first);
+
+    kevi(&[("KEVI_SESSION_SLIDING", "true")]);
+    let renewed = expires_at();
+    assert!(renewed > first, "cache hit should extend the session");
+    let stored: DerivedKeyStored = load(&sess).unwrap().expect("session present");
+    assert_eq!(stored.ttl_secs, Some(60));
+
+    // Past the lifetime cap the session is no longer extended
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    kevi(&[("KEVI_SESSION_SLIDING", "true"), ("KEVI_SESSION_MAX", "1")]);
+    assert_eq!(expires_at(), renewed);
+}
+
+#[test]
+fn unlock_force_derive_time_reports_kdf_duration_even_when_unlocked() {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    use std::process::Command;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    kevi::vault::persistence::save_vault_file(&[], &path, "pw").expect("init empty vault");
+
+    // The second run has a valid session and must still derive
+    for _ in 0..2 {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_PASSWORD", "pw")
+            .arg("unlock")
+            .arg("--path")
+            .arg(path.to_string_lossy().to_string())
+            .arg("--force-derive")
+            .arg("--time");
+        cmd.assert()
+            .success()
+            .stdout(predicate::str::is_match(r"Key derivation took \d+ ms").unwrap())
+            .stdout(predicate::str::contains("Unlocked for"));
+        assert!(dk_session_file_for(&path).exists());
+    }
+
+    let mut bare = Command::cargo_bin("kevi").unwrap();
+    bare.arg("unlock")
+        .arg("--path")
+        .arg(path.to_string_lossy().to_string())
+        .arg("--time");
+    bare.assert()
+        .failure()
+        .stderr(predicate::str::contains("--force-derive"));
+}
+
+#[test]
+fn unlock_is_a_no_op_while_a_session_is_valid() {
+    use assert_cmd::prelude::*;
+    use predicates::prelude::*;
+    use std::process::Command;
+
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("vault.ron");
+    kevi::vault::persistence::save_vault_file(&[], &path, "pw").expect("init empty vault");
+    let unlock = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env_remove("KEVI_PASSWORD")
+            .arg("--no-prompt")
+            .arg("unlock")
+            .arg("--path")
+            .arg(&path)
+            .args(args);
+        cmd
+    };
+
+    let mut first = unlock(&["--ttl", "10m"]);
+    first.env("KEVI_PASSWORD", "pw");
+    first
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unlocked for 600s"));
+    let dk_path = dk_session_file_for(&path);
+    let before = std::fs::read(&dk_path).unwrap();
+    let unlocked: DerivedKeyStored = load(&dk_path).unwrap().unwrap();
+
+    // No password is available, so these can only succeed without deriving
+    unlock(&["--ttl", "1m"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Already unlocked \((9|10)m remaining\)").unwrap());
+    assert_eq!(
+        std::fs::read(&dk_path).unwrap(),
+        before,
+        "TTL not shortened"
+    );
+
+    unlock(&["--extend", "--ttl", "2h"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Session extended to 7200s"));
+    let extended: DerivedKeyStored = load(&dk_path).unwrap().unwrap();
+    assert_eq!(extended.ttl_secs, Some(7200));
+    assert_eq!(
+        extended.unlocked_at_unix, unlocked.unlocked_at_unix,
+        "extending keeps the unlock time"
+    );
+
+    // The lifetime cap counts from the original unlock
+    unlock(&["--extend", "--ttl", "2h"])
+        .env("KEVI_SESSION_MAX", "3600")
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::is_match(
+                r"Session extended to 3[56]\d\ds, the most session_max_secs allows",
+            )
+            .unwrap(),
+        );
+
+    unlock(&["--force-derive"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("KEVI_PASSWORD"));
+}
@@ -1,5 +1,6 @@
 use kevi::config::app_config::Config;
-use kevi::vault::handlers::{GetField, Vault};
+use kevi::vault::format::EntryFormat;
+use kevi::vault::handlers::{GetField, GetOptions, Vault};
 use kevi::vault::models::VaultEntry;
 use kevi::vault::persistence::{load_vault_file, save_vault_file};
 use secrecy::SecretString;
@@ -17,10 +18,9 @@ async fn test_handle_get_existing_entry() {
     let path = setup_vault_path("vault.ron");
     let pw = "testpw";
     let entry = VaultEntry {
-        label: "gettest".into(),
         username: Some(SecretString::new("user".into())),
-        password: SecretString::new("secret".into()),
         notes: Some("note".into()),
+        ..VaultEntry::new("gettest", "secret")
     };
 
     save_vault_file(slice::from_ref(&entry), &path, pw).unwrap();
@@ -28,7 +28,28 @@ async fn test_handle_get_existing_entry() {
     let vault = Vault::create(&config);
     env::set_var("KEVI_PASSWORD", pw);
     let result = vault
-        .handle_get("gettest", GetField::Password, true, None, false, false)
+        .handle_get(
+            "gettest",
+            GetOptions {
+                field: GetField::Password,
+                field_fallback: Vec::new(),
+                no_copy: true,
+                ttl: None,
+                echo: false,
+                once: false,
+                exact: false,
+                format: EntryFormat::Dotenv,
+                reveal: false,
+                raw: false,
+                print0: false,
+                wait: false,
+                verbose: false,
+                to_file: None,
+                to_fifo: None,
+                clear_on_paste: false,
+                autotype: false,
+            },
+        )
         .await;
     assert!(result.is_ok());
 }
@@ -37,18 +58,13 @@ async fn test_handle_get_existing_entry() {
 async fn test_handle_rm_existing_entry() {
     let path = setup_vault_path("vault.ron");
     let pw = "testpw";
-    let entry = VaultEntry {
-        label: "rmtest".into(),
-        username: None,
-        password: SecretString::new("pw".into()),
-        notes: None,
-    };
+    let entry = VaultEntry::new("rmtest", "pw");
 
     save_vault_file(slice::from_ref(&entry), &path, pw).unwrap();
     let config = Config::create(Some(path.clone()), None).unwrap();
     let vault = Vault::create(&config);
     env::set_var("KEVI_PASSWORD", pw);
-    let result = vault.handle_rm("rmtest", true).await;
+    let result = vault.handle_rm(&["rmtest".to_string()], true, false).await;
     assert!(result.is_ok());
 
     let loaded = load_vault_file(&path, pw).unwrap();
@@ -0,0 +1,100 @@
+use assert_cmd::Command;
+use tempfile::tempdir;
+
+#[test]
+fn where_prints_resolved_paths_as_text_and_json() {
+    let td = tempdir().unwrap();
+    let config_dir = td.path().join("config");
+    let data_dir = td.path().join("data");
+
+    let kevi = || {
+        let mut cmd = Command::cargo_bin("kevi").unwrap();
+        cmd.env("KEVI_CONFIG_DIR", &config_dir)
+            .env("KEVI_DATA_DIR", &data_dir)
+            .env_remove("KEVI_VAULT_PATH")
+            .arg("where");
+        cmd
+    };
+
+    let out = kevi().assert().success().get_output().stdout.clone();
+    let out = String::from_utf8(out).unwrap();
+    let vault = data_dir.join("kevi").join("vault.ron");
+    assert!(
+        out.contains(&format!(
+            "config_file: {}\n",
+            config_dir.join("kevi").join("config.toml").display()
+        )),
+        "{out}"
+    );
+    assert!(
+        out.contains(&format!("vault_path: {}\n", vault.display())),
+        "{out}"
+    );
+    assert!(
+        out.contains(&format!("data_dir: {}\n", data_dir.join("kevi").display())),
+        "{out}"
+    );
+
+    let out = kevi()
+        .args(["--json", "--path"])
+        .arg(td.path().join("other.ron"))
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let v: serde_json::Value = serde_json::from_slice(&out).expect("valid json");
+    assert_eq!(
+        v["vault_path"],
+        td.path().join("other.ron").display().to_string()
+    );
+    assert!(v["session_path"]
+        .as_str()
+        .unwrap()
+        .ends_with("other.ron.dksession"));
+    assert_eq!(
+        v["config_dir"],
+        config_dir.join("kevi").display().to_string()
+    );
+
+    // Nothing was created
+    assert!(!config_dir.exists() && !data_dir.exists());
+}
+
+#[cfg(target_family = "unix")]
+#[test]
+fn open_config_runs_the_editor_on_the_config_file() {
+    let td = tempdir().unwrap();
+    let config_dir = td.path().join("config");
+
+    // `touch` stands in for an editor; the directory must exist for it to succeed
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_CONFIG_DIR", &config_dir)
+        .env_remove("VISUAL")
+        .env("EDITOR", "touch -c")
+        .arg("open-config")
+        .assert()
+        .success();
+    assert!(config_dir.join("kevi").is_dir());
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_CONFIG_DIR", &config_dir)
+        .env("VISUAL", "touch")
+        .env("EDITOR", "false")
+        .arg("open-config")
+        .assert()
+        .success();
+    assert!(config_dir.join("kevi").join("config.toml").is_file());
+
+    Command::cargo_bin("kevi")
+        .unwrap()
+        .env("KEVI_CONFIG_DIR", &config_dir)
+        .env_remove("VISUAL")
+        .env("EDITOR", "false")
+        .arg("open-config")
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("editor 'false' exited"));
+}